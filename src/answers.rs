@@ -0,0 +1,348 @@
+use crate::{
+    input,
+    pool::{self, Completion, Job},
+    Answer, Entry, Error, Part,
+};
+use std::{fmt, fs, path::Path, time::Duration};
+
+/// Known answers, one check per line:
+///
+/// ```text
+/// # day[:variant] part input expected [slow] [broken]
+/// 08:fast 2 day08 8906539031197
+/// 10 2 day10 >325 broken
+/// 12 2 day12 unknown slow
+/// ```
+///
+/// A check without a variant applies to every variant of the day. `expected`
+/// is the answer itself, `unknown`, or comma separated bounds left by wrong
+/// submissions: `>325` after 325 was too low, `<400` after 400 was too high.
+/// A solution known to get a check wrong is marked `broken`, so that the
+/// check is reported but does not fail.
+pub const PATH: &str = "answers.txt";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Above(i128),
+    Below(i128),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expected {
+    Exact(String),
+    Unknown,
+    Bounds(Vec<Bound>),
+}
+
+impl Expected {
+    fn from_str(s: &str) -> Result<Self, String> {
+        if s == "unknown" {
+            return Ok(Expected::Unknown);
+        }
+        if !s.starts_with(['<', '>']) {
+            return Ok(Expected::Exact(s.to_owned()));
+        }
+        s.split(',')
+            .map(|b| {
+                let bound = |n: &str| n.parse().map_err(|_| format!("bad bound `{b}`"));
+                match b.split_at(1) {
+                    (">", n) => Ok(Bound::Above(bound(n)?)),
+                    ("<", n) => Ok(Bound::Below(bound(n)?)),
+                    _ => Err(format!("bad bound `{b}`")),
+                }
+            })
+            .collect::<Result<_, _>>()
+            .map(Expected::Bounds)
+    }
+
+    /// Why `answer` is wrong, if it is known to be.
+    pub fn check(&self, answer: &Answer) -> Result<(), String> {
+        match self {
+            Expected::Exact(expected) if answer.to_string() != *expected => {
+                Err(format!("expected {expected}, got {answer}"))
+            }
+            Expected::Exact(_) | Expected::Unknown => Ok(()),
+            Expected::Bounds(bounds) => {
+                let n = match answer {
+                    Answer::Signed(n) => *n,
+                    Answer::Unsigned(n) => i128::try_from(*n).unwrap_or(i128::MAX),
+                    Answer::Text(_) => return Err(format!("expected a number, got {answer}")),
+                };
+                match bounds.iter().find(|bound| match bound {
+                    Bound::Above(min) => n <= *min,
+                    Bound::Below(max) => n >= *max,
+                }) {
+                    Some(Bound::Above(min)) => Err(format!("{n} is too low, must be above {min}")),
+                    Some(Bound::Below(max)) => Err(format!("{n} is too high, must be below {max}")),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expected::Exact(expected) => write!(f, "{expected}"),
+            Expected::Unknown => write!(f, "unknown"),
+            Expected::Bounds(bounds) => {
+                let bounds: Vec<_> = bounds
+                    .iter()
+                    .map(|bound| match bound {
+                        Bound::Above(min) => format!(">{min}"),
+                        Bound::Below(max) => format!("<{max}"),
+                    })
+                    .collect();
+                write!(f, "{}", bounds.join(","))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub day: u8,
+    pub variant: Option<String>,
+    pub part: Part,
+    /// Name of the input, see `input::read`.
+    pub input: String,
+    pub expected: Expected,
+    pub slow: bool,
+    pub broken: bool,
+}
+
+impl Check {
+    /// A line of the answers file, without comments.
+    pub fn from_line(line: &str) -> Result<Self, String> {
+        let fields: Vec<_> = line.split_whitespace().collect();
+        let (name, part, input, expected, slow, broken) = match fields[..] {
+            [name, part, input, expected] => (name, part, input, expected, false, false),
+            [name, part, input, expected, "slow"] => (name, part, input, expected, true, false),
+            [name, part, input, expected, "broken"] => (name, part, input, expected, false, true),
+            [name, part, input, expected, "slow", "broken"] => {
+                (name, part, input, expected, true, true)
+            }
+            _ => {
+                return Err(
+                    "expected `day[:variant] part input expected [slow] [broken]`".to_owned(),
+                )
+            }
+        };
+        let (day, variant) = match name.split_once(':') {
+            Some((day, variant)) => (day, Some(variant.to_owned())),
+            None => (name, None),
+        };
+        Ok(Check {
+            day: day.parse().map_err(|_| format!("bad day `{day}`"))?,
+            variant,
+            part: match part {
+                "1" => Part::One,
+                "2" => Part::Two,
+                _ => return Err(format!("bad part `{part}`")),
+            },
+            input: input.to_owned(),
+            expected: Expected::from_str(expected)?,
+            slow,
+            broken,
+        })
+    }
+
+    pub fn applies_to(&self, entry: &Entry) -> bool {
+        self.day == entry.day
+            && (self.variant.is_none() || self.variant.as_deref() == entry.variant)
+    }
+}
+
+/// Parses an answers file, skipping blank lines and `#` comments.
+pub fn parse(text: &str) -> Result<Vec<Check>, String> {
+    text.lines()
+        .enumerate()
+        .map(|(i, l)| (i + 1, l.split('#').next().unwrap().trim()))
+        .filter(|(_, l)| !l.is_empty())
+        .map(|(nr, l)| Check::from_line(l).map_err(|e| format!("{PATH}:{nr}: {e}")))
+        .collect()
+}
+
+/// Loads `PATH` in the crate root.
+pub fn load() -> Result<Vec<Check>, String> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(PATH);
+    let text = fs::read_to_string(path).map_err(|e| format!("{PATH}: {e}"))?;
+    parse(&text)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// The answer is the expected one, or within its bounds.
+    Pass,
+    /// There is no known answer to compare with.
+    Unchecked,
+    Wrong(String),
+    /// The solution returned an error or panicked.
+    Failed(String),
+    TimedOut,
+    /// The input file is missing, e.g. in a checkout without inputs.
+    NoInput,
+    /// Wrong or failed, as the check says the solution is.
+    Broken(String),
+}
+
+impl Verdict {
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Verdict::Wrong(_) | Verdict::Failed(_))
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "pass"),
+            Verdict::Unchecked => write!(f, "unchecked"),
+            Verdict::Wrong(why) => write!(f, "FAIL: {why}"),
+            Verdict::Failed(why) => write!(f, "FAIL: {why}"),
+            Verdict::TimedOut => write!(f, "timed out"),
+            Verdict::NoInput => write!(f, "no input"),
+            Verdict::Broken(why) => write!(f, "known broken: {why}"),
+        }
+    }
+}
+
+/// How a solution did on a check.
+#[derive(Debug)]
+pub struct Outcome {
+    /// `Entry::name` of the solution.
+    pub name: String,
+    pub check: Check,
+    pub answer: Option<Answer>,
+    pub verdict: Verdict,
+    /// Reading the input, parsing and solving, if it finished.
+    pub elapsed: Option<Duration>,
+}
+
+/// Runs every solution in `registry` against every check in `checks` that
+/// applies to it, on a pool of `workers` threads, in the order of the
+/// registry.
+pub fn run_checks(
+    registry: Vec<Entry>,
+    checks: &[Check],
+    workers: usize,
+    timeout: Option<Duration>,
+) -> Vec<Outcome> {
+    let runs: Vec<(Entry, Check)> = registry
+        .into_iter()
+        .flat_map(|entry| {
+            checks
+                .iter()
+                .filter(move |check| check.applies_to(&entry))
+                .map(move |check| (entry, check.clone()))
+        })
+        .collect();
+    let jobs: Vec<Job<Option<Result<Answer, Error>>>> = runs
+        .iter()
+        .map(|(entry, check)| {
+            let (entry, check) = (*entry, check.clone());
+            Box::new(move || {
+                let input = input::read(&check.input).ok()?;
+                Some(entry.run(&input, check.part))
+            }) as Job<_>
+        })
+        .collect();
+    let completions = pool::run(jobs, workers, timeout);
+    runs.into_iter()
+        .zip(completions)
+        .map(|((entry, check), completion)| {
+            let (answer, verdict, elapsed) = match completion {
+                Completion::Returned(None, _) => (None, Verdict::NoInput, None),
+                // a day just made from the template, with nothing to check yet
+                Completion::Returned(Some(Err(Error::Unsolved)), elapsed)
+                    if check.expected == Expected::Unknown =>
+                {
+                    (None, Verdict::Unchecked, Some(elapsed))
+                }
+                Completion::Returned(Some(Err(e)), elapsed) => {
+                    (None, Verdict::Failed(e.to_string()), Some(elapsed))
+                }
+                Completion::Returned(Some(Ok(answer)), elapsed) => {
+                    let verdict = match (check.expected.check(&answer), &check.expected) {
+                        (Err(why), _) => Verdict::Wrong(why),
+                        (Ok(()), Expected::Unknown) => Verdict::Unchecked,
+                        (Ok(()), _) => Verdict::Pass,
+                    };
+                    (Some(answer), verdict, Some(elapsed))
+                }
+                Completion::Panicked(msg, elapsed) => (
+                    None,
+                    Verdict::Failed(format!("panicked: {msg}")),
+                    Some(elapsed),
+                ),
+                Completion::TimedOut => (None, Verdict::TimedOut, None),
+            };
+            let verdict = match verdict {
+                Verdict::Wrong(why) | Verdict::Failed(why) if check.broken => Verdict::Broken(why),
+                verdict => verdict,
+            };
+            Outcome {
+                name: entry.name(),
+                check,
+                answer,
+                verdict,
+                elapsed,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{answers::*, registry};
+    use std::{env, thread};
+    #[test]
+    fn parse_checks() {
+        let checks =
+            parse("# comment\n\n08:fast 2 day08 42\n10 2 day10 >325,<400 broken # wrong twice\n")
+                .unwrap();
+        assert_eq!(checks[0].variant.as_deref(), Some("fast"));
+        assert_eq!(checks[0].expected, Expected::Exact("42".to_owned()));
+        assert!(!checks[0].broken && checks[1].broken);
+        let bounds = &checks[1].expected;
+        assert!(bounds.check(&Answer::Unsigned(325)).is_err());
+        assert!(bounds.check(&Answer::Unsigned(326)).is_ok());
+        assert!(bounds.check(&Answer::Unsigned(400)).is_err());
+        assert_eq!(bounds.to_string(), ">325,<400");
+        assert!(parse("10 3 day10 1")
+            .unwrap_err()
+            .starts_with("answers.txt:1:"));
+    }
+    /// Runs every registered solution against every check for its day. Checks
+    /// marked slow only run when `AOC_SLOW` is set, and those marked broken
+    /// are only reported.
+    #[test]
+    fn registered_solutions_match_answers() {
+        let slow = env::var_os("AOC_SLOW").is_some();
+        let checks = load().unwrap();
+        let registry = registry();
+        for entry in &registry {
+            assert!(
+                checks.iter().any(|check| check.applies_to(entry)),
+                "no answers for {}",
+                entry.name()
+            );
+        }
+        let checks: Vec<_> = checks.into_iter().filter(|c| slow || !c.slow).collect();
+        let workers = thread::available_parallelism().map_or(4, |n| n.get());
+        let (broken, failures): (Vec<_>, Vec<_>) = run_checks(registry, &checks, workers, None)
+            .into_iter()
+            .filter(|o| !matches!(o.verdict, Verdict::Pass | Verdict::Unchecked))
+            .map(|o| {
+                let check = &o.check;
+                let line = format!("{} {:?} {}: {}", o.name, check.part, check.input, o.verdict);
+                (matches!(o.verdict, Verdict::Broken(_)), line)
+            })
+            .partition(|(broken, _)| *broken);
+        for (_, line) in broken {
+            eprintln!("{line}");
+        }
+        let failures: Vec<String> = failures.into_iter().map(|(_, line)| line).collect();
+        assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+    }
+}
@@ -0,0 +1,178 @@
+use crate::{json::Json, Entry, Error, Part};
+use std::{
+    hint::black_box,
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+pub struct Options {
+    pub warmup: usize,
+    pub runs: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            warmup: 3,
+            runs: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Parse,
+    Solve(Part),
+}
+
+impl Phase {
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Parse => "parse",
+            Phase::Solve(Part::One) => "part1",
+            Phase::Solve(Part::Two) => "part2",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    /// `samples` must not be empty.
+    pub fn from_samples(mut samples: Vec<Duration>) -> Self {
+        samples.sort();
+        let mid = samples.len() / 2;
+        let median = if samples.len() % 2 == 0 {
+            (samples[mid - 1] + samples[mid]) / 2
+        } else {
+            samples[mid]
+        };
+        Self {
+            min: samples[0],
+            median,
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+pub struct Measurement {
+    /// `Entry::name` of the measured solution.
+    pub name: String,
+    pub phase: Phase,
+    pub runs: usize,
+    pub stats: Stats,
+}
+
+fn measure<T>(opts: &Options, mut f: impl FnMut() -> T) -> Stats {
+    for _ in 0..opts.warmup {
+        black_box(f());
+    }
+    let samples = (0..opts.runs.max(1))
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .collect();
+    Stats::from_samples(samples)
+}
+
+/// Times parsing `input` and then solving each of `parts` on the parsed input.
+pub fn bench(
+    entry: &Entry,
+    input: &str,
+    parts: &[Part],
+    opts: &Options,
+) -> Result<Vec<Measurement>, Error> {
+    let parsed = entry.parse(input)?;
+    for part in parts {
+        entry.solve(parsed.as_ref(), *part)?;
+    }
+    let measurement = |phase, stats| Measurement {
+        name: entry.name(),
+        phase,
+        runs: opts.runs.max(1),
+        stats,
+    };
+    let mut res = vec![measurement(
+        Phase::Parse,
+        measure(opts, || entry.parse(black_box(input))),
+    )];
+    for part in parts {
+        let stats = measure(opts, || entry.solve(black_box(parsed.as_ref()), *part));
+        res.push(measurement(Phase::Solve(*part), stats));
+    }
+    Ok(res)
+}
+
+/// One line per measurement, times in nanoseconds.
+pub fn write_csv(measurements: &[Measurement], mut w: impl Write) -> io::Result<()> {
+    writeln!(w, "name,phase,runs,min_ns,median_ns,max_ns")?;
+    for m in measurements {
+        writeln!(
+            w,
+            "{},{},{},{},{},{}",
+            m.name,
+            m.phase.name(),
+            m.runs,
+            m.stats.min.as_nanos(),
+            m.stats.median.as_nanos(),
+            m.stats.max.as_nanos()
+        )?;
+    }
+    Ok(())
+}
+
+/// The same fields as `write_csv`, as an array of objects.
+pub fn write_json(measurements: &[Measurement], mut w: impl Write) -> io::Result<()> {
+    writeln!(w, "[")?;
+    for (i, m) in measurements.iter().enumerate() {
+        let comma = if i + 1 < measurements.len() { "," } else { "" };
+        let object = Json::object([
+            ("name", m.name.as_str().into()),
+            ("phase", m.phase.name().into()),
+            ("runs", m.runs.into()),
+            ("min_ns", m.stats.min.as_nanos().into()),
+            ("median_ns", m.stats.median.as_nanos().into()),
+            ("max_ns", m.stats.max.as_nanos().into()),
+        ]);
+        writeln!(w, "  {object}{comma}")?;
+    }
+    writeln!(w, "]")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{bench::*, lookup};
+    #[test]
+    fn median_of_even_and_odd() {
+        let ms = |v: &[u64]| v.iter().map(|n| Duration::from_millis(*n)).collect();
+        let stats = Stats::from_samples(ms(&[5, 1, 3]));
+        assert_eq!(stats.median, Duration::from_millis(3));
+        let stats = Stats::from_samples(ms(&[4, 1, 2, 8]));
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.median, Duration::from_millis(3));
+        assert_eq!(stats.max, Duration::from_millis(8));
+    }
+    #[test]
+    fn bench_writes_a_row_per_phase() {
+        let entry = lookup(9, Some("inplace")).unwrap();
+        let opts = Options { warmup: 0, runs: 2 };
+        let rows = bench(&entry, "0 3 6 9\n", &[Part::One, Part::Two], &opts).unwrap();
+        let mut csv = vec![];
+        write_csv(&rows, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let phases: Vec<_> = csv
+            .lines()
+            .skip(1)
+            .map(|l| l.split(',').nth(1).unwrap())
+            .collect();
+        assert_eq!(phases, ["parse", "part1", "part2"]);
+        assert!(csv.lines().skip(1).all(|l| l.starts_with("day09:inplace,")));
+    }
+}
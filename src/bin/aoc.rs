@@ -0,0 +1,656 @@
+use advent_of_code_2023::{
+    answers::{self, Verdict},
+    bench::{self, Measurement, Options},
+    days, diff, generate, input, lookup,
+    pool::{self, Completion, Job},
+    puzzle, registry,
+    report::{self, Report},
+    rng::Rng,
+    scaffold, stream,
+    submit::{self, Client, History},
+    variants, Answer, Entry, Part,
+};
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, BufReader, Read},
+    path::Path,
+    process::ExitCode,
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const USAGE: &str = "\
+usage: aoc <DAY> [PART] [--variant NAME] [--input PATH] [--json] [--stream]
+       aoc all [--json] [--timeout SECS] [--jobs N]
+       aoc list [DAY]
+       aoc check [DAY] [--timeout SECS] [--jobs N]
+       aoc new <DAY>
+       aoc examples <DAY> <HTML>
+       aoc submit <DAY> <PART> [ANSWER] [--variant NAME]
+       aoc diff [DAY] [--cases N] [--seed SEED]
+       aoc generate <DAY> [--seed SEED]
+       aoc bench [DAY] [--variant NAME] [--runs N] [--warmup N] [--csv PATH] [--json PATH]
+
+  DAY        day number, e.g. 7
+  PART       1 or 2, both parts are run if left out
+  --variant  run an alternative implementation of the day, e.g. fast
+  --input    input file, the name of one in the input directory, e.g.
+             example10_part2, or '-' to read stdin (default dayNN)
+  --json     print a JSON object per part instead, with the answer or error,
+             day, part, variant, input hash, elapsed time and warnings
+  --stream   read the input a line at a time instead of all at once, for
+             inputs too big for memory; only days 1, 2, 4, 6, 7, 9, 12 and 15,
+             with lines of at most 65536 bytes
+
+list shows the solutions and inputs of every day, or of DAY. Inputs are read
+from input/ in the crate root, or from the directory in AOC_INPUT_DIR, with
+line endings and trailing whitespace normalized.
+
+all runs both parts of every day on input/dayNN, with the fast variant of
+days that have one, in parallel like check and with the same options.
+
+check runs every solution, or DAY's, against every input it has an answer
+for in answers.txt, in parallel, and prints a table of the results. It fails
+if any answer is wrong or any solution fails, unless the check is marked
+broken.
+  --timeout  seconds before a run is reported as timed out (default 10)
+  --jobs     number of runs at a time (default the number of cores)
+
+new creates src/dayNN.rs from the day00 template, registers it, adds unknown
+answers for it and creates empty input/dayNN and input/exampleNN.
+
+examples reads the example of each part, and its answer, from HTML, a saved
+puzzle page of DAY, and saves them as input/exampleNN, or exampleNN_part2 if
+part 2 has its own, and in answers.txt. Existing examples and answers are not
+replaced unless new ones left them empty or unknown.
+
+submit posts ANSWER, or the one the solution gives for input/dayNN, to
+AOC_URL, e.g. http://localhost:8080/2023 for a proxy to the site, logged in
+with the session cookie in AOC_SESSION. Every attempt is kept in
+submissions.txt, and answers known to be wrong from there or from the bounds
+in answers.txt are not submitted.
+
+diff runs all variants of every day that has some, or of DAY, on generated
+inputs and shows the smallest input found on which they disagree.
+  --cases    number of inputs to generate (default 100)
+  --seed     seed of the first input (default from the clock)
+
+generate prints a random input for DAY, made from SEED (default from the
+clock, shown on stderr).
+
+bench times parsing and each part of every solution, or of DAY's, on
+input/dayNN. Parts without a known answer in answers.txt, or marked slow
+there, are skipped.
+  --runs     timed runs per measurement (default 10)
+  --warmup   untimed runs before those (default 3)
+  --csv      also write the results to PATH as CSV
+  --json     also write the results to PATH as JSON
+
+Solutions log nothing unless AOC_LOG says what to show on stderr: a level
+(warn, info, debug or trace) for every day and DAY=LEVEL for one, e.g.
+AOC_LOG=warn,day16=trace.
+";
+
+struct Args {
+    day: u8,
+    parts: Vec<Part>,
+    variant: Option<String>,
+    input: Option<String>,
+    json: bool,
+    stream: bool,
+}
+
+fn parse_part(s: &str) -> Result<Part, String> {
+    match s {
+        "1" => Ok(Part::One),
+        "2" => Ok(Part::Two),
+        _ => Err(format!("bad part: {s}")),
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let day = args.next().ok_or("missing day")?;
+    let day = day.parse().map_err(|_| format!("bad day: {day}"))?;
+    let mut parsed = Args {
+        day,
+        parts: vec![Part::One, Part::Two],
+        variant: None,
+        input: None,
+        json: false,
+        stream: false,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--variant" => parsed.variant = Some(args.next().ok_or("missing variant")?),
+            "--input" => parsed.input = Some(args.next().ok_or("missing input")?),
+            "--json" => parsed.json = true,
+            "--stream" => parsed.stream = true,
+            part => parsed.parts = vec![parse_part(part)?],
+        }
+    }
+    Ok(parsed)
+}
+
+struct BenchArgs {
+    day: Option<u8>,
+    variant: Option<String>,
+    opts: Options,
+    csv: Option<String>,
+    json: Option<String>,
+}
+
+fn parse_count(arg: Option<String>, what: &str) -> Result<usize, String> {
+    let arg = arg.ok_or_else(|| format!("missing {what}"))?;
+    arg.parse().map_err(|_| format!("bad {what}: {arg}"))
+}
+
+fn parse_bench_args(mut args: impl Iterator<Item = String>) -> Result<BenchArgs, String> {
+    let mut parsed = BenchArgs {
+        day: None,
+        variant: None,
+        opts: Options::default(),
+        csv: None,
+        json: None,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--variant" => parsed.variant = Some(args.next().ok_or("missing variant")?),
+            "--runs" => parsed.opts.runs = parse_count(args.next(), "runs")?,
+            "--warmup" => parsed.opts.warmup = parse_count(args.next(), "warmup")?,
+            "--csv" => parsed.csv = Some(args.next().ok_or("missing csv path")?),
+            "--json" => parsed.json = Some(args.next().ok_or("missing json path")?),
+            day => parsed.day = Some(day.parse().map_err(|_| format!("bad day: {day}"))?),
+        }
+    }
+    Ok(parsed)
+}
+
+fn read_input(path: &str) -> Result<String, String> {
+    if path == "-" {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| format!("stdin: {e}"))?;
+        Ok(input::normalize(&input))
+    } else if !Path::new(path).exists() && input::path(path).is_file() {
+        input::read(path)
+    } else {
+        input::read_path(Path::new(path))
+    }
+}
+
+/// Answers each part from the input file read a line at a time.
+fn run_streamed(args: &Args) -> Result<(), String> {
+    if args.json || args.variant.is_some() || !stream::DAYS.contains(&args.day) {
+        return Err(format!(
+            "--stream works without --json and --variant for days {:?}",
+            stream::DAYS
+        ));
+    }
+    let path = match args.input.as_deref() {
+        Some("-") if args.parts.len() > 1 => {
+            return Err("--stream can only read stdin once, give a PART".to_owned())
+        }
+        Some("-") => None,
+        Some(path) if !Path::new(path).exists() && input::path(path).is_file() => {
+            Some(input::path(path))
+        }
+        Some(path) => Some(Path::new(path).to_owned()),
+        None => Some(input::path(&input::real(args.day))),
+    };
+    for &part in &args.parts {
+        let answer = match &path {
+            None => stream::run(args.day, part, io::stdin().lock()),
+            Some(path) => {
+                let file = File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+                stream::run(args.day, part, BufReader::new(file))
+            }
+        };
+        let answer = answer
+            .expect("a day of stream::DAYS")
+            .map_err(|e| e.to_string())?;
+        println!("{answer}");
+    }
+    Ok(())
+}
+
+fn part_number(part: Part) -> u8 {
+    match part {
+        Part::One => 1,
+        Part::Two => 2,
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let entry = lookup(args.day, args.variant.as_deref()).ok_or_else(|| match &args.variant {
+        Some(variant) => format!("day {} has no variant {variant}", args.day),
+        None => format!("day {} is not solved", args.day),
+    })?;
+    if args.stream {
+        return run_streamed(&args);
+    }
+    let input = match &args.input {
+        Some(path) => read_input(path)?,
+        None => input::read(&input::real(args.day))?,
+    };
+    if args.json {
+        let reports = report::run(&entry, &input, &args.parts);
+        for report in &reports {
+            println!("{}", report.to_json());
+        }
+        return match reports.iter().find_map(|r| r.answer.as_ref().err()) {
+            Some(e) => Err(e.clone()),
+            None => Ok(()),
+        };
+    }
+    let parsed = entry.parse(&input).map_err(|e| e.to_string())?;
+    for part in args.parts {
+        let answer = entry
+            .solve(parsed.as_ref(), part)
+            .map_err(|e| e.to_string())?;
+        println!("{answer}");
+    }
+    Ok(())
+}
+
+fn run_all(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut json = false;
+    let mut timeout = DEFAULT_TIMEOUT;
+    let mut workers = default_workers();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--timeout" => {
+                timeout = Duration::from_secs(parse_count(args.next(), "timeout")? as u64)
+            }
+            "--jobs" => workers = parse_count(args.next(), "jobs")?,
+            arg => return Err(format!("unexpected argument: {arg}, see aoc --help")),
+        }
+    }
+    let mut runs = vec![];
+    for day in days() {
+        let entry = lookup(day, Some("fast"))
+            .or_else(|| lookup(day, None))
+            .unwrap();
+        match input::read(&input::real(day)) {
+            Ok(input) => {
+                runs.extend([Part::One, Part::Two].map(|part| (entry, part, input.clone())))
+            }
+            Err(e) => match json {
+                true => eprintln!("{}: no input: {e}", entry.name()),
+                false => println!("{}: no input: {e}", entry.name()),
+            },
+        }
+    }
+    let jobs: Vec<Job<Report>> = runs
+        .iter()
+        .map(|(entry, part, input)| {
+            let (entry, part, input) = (*entry, *part, input.clone());
+            Box::new(move || report::run(&entry, &input, &[part]).remove(0)) as Job<_>
+        })
+        .collect();
+    let completions = pool::run(jobs, workers, Some(timeout));
+    for ((entry, part, input), completion) in runs.iter().zip(completions) {
+        let report = match completion {
+            Completion::Returned(report, _) => report,
+            Completion::Panicked(msg, elapsed) => Report {
+                answer: Err(format!("panicked: {msg}")),
+                elapsed,
+                ..failed_report(entry, *part, input)
+            },
+            Completion::TimedOut => Report {
+                answer: Err(format!("timed out after {timeout:?}")),
+                elapsed: timeout,
+                ..failed_report(entry, *part, input)
+            },
+        };
+        match (json, &report.answer) {
+            (true, _) => println!("{}", report.to_json()),
+            (false, Ok(answer)) => {
+                println!("{} part {}: {answer}", entry.name(), part_number(*part))
+            }
+            (false, Err(e)) => println!("{} part {}: {e}", entry.name(), part_number(*part)),
+        }
+    }
+    Ok(())
+}
+
+/// A report of `part` of `entry` on `input` that never returned.
+fn failed_report(entry: &Entry, part: Part, input: &str) -> Report {
+    Report {
+        day: entry.day,
+        part,
+        variant: entry.variant.map(str::to_owned),
+        input_hash: report::input_hash(input),
+        answer: Err(String::new()),
+        elapsed: Duration::ZERO,
+        warnings: vec![],
+    }
+}
+
+fn run_bench(args: BenchArgs) -> Result<(), String> {
+    let checks = answers::load()?;
+    let mut measurements: Vec<Measurement> = vec![];
+    let entries = registry().into_iter().filter(|e| {
+        args.day.map_or(true, |day| e.day == day)
+            && (args.variant.is_none() || e.variant == args.variant.as_deref())
+    });
+    for entry in entries {
+        let input_name = input::real(entry.day);
+        let parts: Vec<_> = [Part::One, Part::Two]
+            .into_iter()
+            .filter(|part| {
+                checks.iter().any(|c| {
+                    c.applies_to(&entry) && c.part == *part && c.input == input_name && !c.slow
+                })
+            })
+            .collect();
+        if parts.is_empty() {
+            println!("{}: no part known to finish, skipped", entry.name());
+            continue;
+        }
+        let input = match input::read(&input_name) {
+            Ok(input) => input,
+            Err(e) => {
+                println!("{}: no input: {e}", entry.name());
+                continue;
+            }
+        };
+        let rows = match bench::bench(&entry, &input, &parts, &args.opts) {
+            Ok(rows) => rows,
+            Err(e) => {
+                println!("{}: {e}", entry.name());
+                continue;
+            }
+        };
+        for m in &rows {
+            println!(
+                "{:<14} {:<5}  min {:>10.3?}  median {:>10.3?}  max {:>10.3?}",
+                m.name,
+                m.phase.name(),
+                m.stats.min,
+                m.stats.median,
+                m.stats.max
+            );
+        }
+        measurements.extend(rows);
+    }
+    if let Some(path) = &args.csv {
+        let file = fs::File::create(path).map_err(|e| format!("{path}: {e}"))?;
+        bench::write_csv(&measurements, file).map_err(|e| format!("{path}: {e}"))?;
+    }
+    if let Some(path) = &args.json {
+        let file = fs::File::create(path).map_err(|e| format!("{path}: {e}"))?;
+        bench::write_json(&measurements, file).map_err(|e| format!("{path}: {e}"))?;
+    }
+    Ok(())
+}
+
+fn new_day(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let day = args.next().ok_or("missing day, see aoc --help")?;
+    let day = day.parse().map_err(|_| format!("bad day: {day}"))?;
+    for path in scaffold::new_day(Path::new(env!("CARGO_MANIFEST_DIR")), day)? {
+        println!("created {}", path.display());
+    }
+    Ok(())
+}
+
+fn save_examples(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let day = args.next().ok_or("missing day, see aoc --help")?;
+    let day = day.parse().map_err(|_| format!("bad day: {day}"))?;
+    let path = args.next().ok_or("missing puzzle page, see aoc --help")?;
+    let html = fs::read_to_string(&path).map_err(|e| format!("{path}: {e}"))?;
+    let examples = puzzle::extract(day, &html).map_err(|e| format!("{path}: {e}"))?;
+    for example in &examples {
+        let answer = example.answer.as_deref().unwrap_or("not found");
+        println!(
+            "part {}: {}, answer {answer}",
+            part_number(example.part),
+            example.name
+        );
+    }
+    for path in puzzle::save(Path::new(env!("CARGO_MANIFEST_DIR")), day, &examples)? {
+        println!("wrote {}", path.display());
+    }
+    Ok(())
+}
+
+fn run_submit(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut positional = vec![];
+    let mut variant = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--variant" => variant = Some(args.next().ok_or("missing variant")?),
+            _ => positional.push(arg),
+        }
+    }
+    let (day, part, answer) = match &positional[..] {
+        [day, part] => (day, part, None),
+        [day, part, answer] => (day, part, Some(answer.clone())),
+        _ => return Err("expected a day and part, see aoc --help".to_owned()),
+    };
+    let day: u8 = day.parse().map_err(|_| format!("bad day: {day}"))?;
+    let part = match part.as_str() {
+        "1" => Part::One,
+        "2" => Part::Two,
+        _ => return Err(format!("bad part: {part}")),
+    };
+    let answer = match answer {
+        Some(answer) => match answer.parse() {
+            Ok(n) => Answer::Signed(n),
+            Err(_) => Answer::Text(answer),
+        },
+        None => {
+            let entry = lookup(day, variant.as_deref())
+                .ok_or_else(|| format!("day {day} has no such solution"))?;
+            let input = input::read(&input::real(day))?;
+            entry.run(&input, part).map_err(|e| e.to_string())?
+        }
+    };
+    let known = answers::load()?.into_iter().find(|c| {
+        c.day == day && c.variant.is_none() && c.part == part && c.input == input::real(day)
+    });
+    if let Some(Err(why)) = known.map(|c| c.expected.check(&answer)) {
+        return Err(format!("not submitting {answer}: {why}"));
+    }
+    let client = Client::from_env()?;
+    let mut history = History::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join(submit::HISTORY))?;
+    let response = submit::submit(&client, &mut history, day, part, &answer.to_string())?;
+    println!("{answer}: {response}");
+    Ok(())
+}
+
+fn parse_seed(arg: Option<String>) -> Result<u64, String> {
+    let arg = arg.ok_or("missing seed")?;
+    arg.parse().map_err(|_| format!("bad seed: {arg}"))
+}
+
+fn clock_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |t| t.as_nanos() as u64)
+}
+
+fn run_diff(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut day = None;
+    let mut cases = 100;
+    let mut seed = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--cases" => cases = parse_count(args.next(), "cases")?,
+            "--seed" => seed = Some(parse_seed(args.next())?),
+            d => day = Some(d.parse::<u8>().map_err(|_| format!("bad day: {d}"))?),
+        }
+    }
+    let seed = seed.unwrap_or_else(clock_seed);
+    let days: Vec<u8> = match day {
+        Some(day) => vec![day],
+        None => days()
+            .into_iter()
+            .filter(|day| variants(*day).len() > 1)
+            .collect(),
+    };
+    let mut agree = true;
+    for day in days {
+        match diff::differential(day, cases, seed) {
+            None => return Err(format!("day {day} has no input generator")),
+            Some(Ok(())) => println!("day{day:02}: {cases} inputs from seed {seed} agree"),
+            Some(Err(mismatch)) => {
+                println!("{mismatch}");
+                agree = false;
+            }
+        }
+    }
+    match agree {
+        true => Ok(()),
+        false => Err("variants disagree".to_owned()),
+    }
+}
+
+fn run_generate(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut day = None;
+    let mut seed = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => seed = Some(parse_seed(args.next())?),
+            d => day = Some(d.parse::<u8>().map_err(|_| format!("bad day: {d}"))?),
+        }
+    }
+    let day = day.ok_or("missing day, see aoc --help")?;
+    let seed = seed.unwrap_or_else(|| {
+        let seed = clock_seed();
+        eprintln!("seed {seed}");
+        seed
+    });
+    let input = generate::input(day, &mut Rng::new(seed))
+        .ok_or_else(|| format!("day {day} has no input generator"))?;
+    print!("{input}");
+    Ok(())
+}
+
+/// How long `all` and `check` wait for a run before reporting it timed out.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn default_workers() -> usize {
+    thread::available_parallelism().map_or(4, |n| n.get())
+}
+
+fn run_check(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut day = None;
+    let mut timeout = DEFAULT_TIMEOUT;
+    let mut workers = default_workers();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--timeout" => {
+                timeout = Duration::from_secs(parse_count(args.next(), "timeout")? as u64)
+            }
+            "--jobs" => workers = parse_count(args.next(), "jobs")?,
+            d => day = Some(d.parse::<u8>().map_err(|_| format!("bad day: {d}"))?),
+        }
+    }
+    let registry = registry()
+        .into_iter()
+        .filter(|e| day.map_or(true, |day| e.day == day))
+        .collect();
+    let outcomes = answers::run_checks(registry, &answers::load()?, workers, Some(timeout));
+    let rows: Vec<[String; 7]> = outcomes
+        .iter()
+        .map(|o| {
+            [
+                o.name.clone(),
+                part_number(o.check.part).to_string(),
+                o.check.input.clone(),
+                o.answer.as_ref().map_or("-".to_owned(), |a| a.to_string()),
+                o.check.expected.to_string(),
+                o.verdict.to_string(),
+                match (&o.verdict, o.elapsed) {
+                    (Verdict::TimedOut, _) => format!(">{timeout:?}"),
+                    (_, Some(elapsed)) => format!("{elapsed:.3?}"),
+                    (_, None) => "-".to_owned(),
+                },
+            ]
+        })
+        .collect();
+    let header = [
+        "solution", "part", "input", "answer", "expected", "result", "time",
+    ];
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].len())
+                .chain([header[i].len()])
+                .max()
+                .unwrap()
+        })
+        .collect();
+    for row in [header.map(str::to_owned)].iter().chain(&rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        println!("{}", cells.join("  ").trim_end());
+    }
+    let count = |f: fn(&Verdict) -> bool| outcomes.iter().filter(|o| f(&o.verdict)).count();
+    let failed = count(Verdict::is_failure);
+    println!(
+        "\n{} passed, {failed} failed, {} known broken, {} timed out, {} unchecked, {} without input",
+        count(|v| *v == Verdict::Pass),
+        count(|v| matches!(v, Verdict::Broken(_))),
+        count(|v| *v == Verdict::TimedOut),
+        count(|v| *v == Verdict::Unchecked),
+        count(|v| *v == Verdict::NoInput),
+    );
+    match failed {
+        0 => Ok(()),
+        _ => Err(format!("{failed} checks failed")),
+    }
+}
+
+fn list(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let day: Option<u8> = match args.next() {
+        Some(day) => Some(day.parse().map_err(|_| format!("bad day: {day}"))?),
+        None => None,
+    };
+    for day in days()
+        .into_iter()
+        .filter(|d| day.map_or(true, |day| day == *d))
+    {
+        let names: Vec<String> = variants(day).iter().map(|e| e.name()).collect();
+        println!("{}", names.join(" "));
+        println!("  inputs: {}", input::all(day).join(" "));
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let mut args = env::args().skip(1).peekable();
+    let result = match args.peek().map(String::as_str) {
+        None | Some("-h" | "--help") => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Some("all") => run_all(args.skip(1)),
+        Some("list") => list(args.skip(1)),
+        Some("check") => run_check(args.skip(1)),
+        Some("new") => new_day(args.skip(1)),
+        Some("examples") => save_examples(args.skip(1)),
+        Some("submit") => run_submit(args.skip(1)),
+        Some("diff") => run_diff(args.skip(1)),
+        Some("generate") => run_generate(args.skip(1)),
+        Some("bench") => parse_bench_args(args.skip(1))
+            .map_err(|e| format!("{e}, see aoc --help"))
+            .and_then(run_bench),
+        Some(_) => parse_args(args)
+            .map_err(|e| format!("{e}, see aoc --help"))
+            .and_then(run),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("aoc: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use crate::error::Overflow;
+use num::{CheckedAdd, CheckedMul, One, Zero};
+
+/// Sums and products that give `None` instead of wrapping, like
+/// `checked_add` and `checked_mul` do for two numbers.
+pub trait CheckedIter: Iterator + Sized {
+    fn checked_sum(mut self) -> Option<Self::Item>
+    where
+        Self::Item: CheckedAdd + Zero,
+    {
+        self.try_fold(Zero::zero(), |acc: Self::Item, n| acc.checked_add(&n))
+    }
+
+    fn checked_product(mut self) -> Option<Self::Item>
+    where
+        Self::Item: CheckedMul + One,
+    {
+        self.try_fold(One::one(), |acc: Self::Item, n| acc.checked_mul(&n))
+    }
+}
+
+impl<I: Iterator> CheckedIter for I {}
+
+/// Turns the `None` of a checked operation into an `Overflow`.
+pub trait OrOverflow<T> {
+    fn or_overflow(self, day: u8, what: &str) -> Result<T, Overflow>;
+}
+
+impl<T> OrOverflow<T> for Option<T> {
+    fn or_overflow(self, day: u8, what: &str) -> Result<T, Overflow> {
+        self.ok_or_else(|| Overflow {
+            day,
+            what: what.to_owned(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::checked::*;
+    #[test]
+    fn stops_at_overflow() {
+        assert_eq!([1u8, 2, 3].into_iter().checked_sum(), Some(6));
+        assert_eq!([200u8, 56].into_iter().checked_sum(), None);
+        assert_eq!([16u8, 16].into_iter().checked_product(), None);
+        assert_eq!(Vec::<i32>::new().into_iter().checked_product(), Some(1));
+        let err = None::<u8>.or_overflow(4, "total score").unwrap_err();
+        assert_eq!(err.to_string(), "day04: total score overflowed");
+    }
+}
@@ -1,32 +1,43 @@
+use crate::{Answer, Error, ParseError, Solution};
+
+pub fn try_part_1(_input: &str) -> Result<u32, Error> {
+    Err(Error::Unsolved)
+}
+
+pub fn try_part_2(_input: &str) -> Result<u32, Error> {
+    Err(Error::Unsolved)
+}
+
 pub fn part_1(input: &str) -> u32 {
-    todo!()
+    try_part_1(input).unwrap()
 }
+
 pub fn part_2(input: &str) -> u32 {
-    todo!()
+    try_part_2(input).unwrap()
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::dayXX::*;
-    use std::fs;
-    #[test]
-    fn exampleXX_part1() {
-        let input = fs::read_to_string("input/exampleXX").unwrap();
-        assert_eq!(part_1(&input), 0);
+pub struct DayXX;
+
+impl Solution for DayXX {
+    type Input = String;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        Ok(input.to_owned())
     }
-    #[test]
-    fn dayXX_part1() {
-        let input = fs::read_to_string("input/dayXX").unwrap();
-        assert_eq!(part_1(&input), 0);
+    fn part_1(input: &Self::Input) -> Result<Answer, Error> {
+        Ok(try_part_1(input)?.into())
     }
-    #[test]
-    fn exampleXX_part2() {
-        let input = fs::read_to_string("input/exampleXX").unwrap();
-        assert_eq!(part_2(&input), 0);
+    fn part_2(input: &Self::Input) -> Result<Answer, Error> {
+        Ok(try_part_2(input)?.into())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{dayXX::*, input};
     #[test]
-    fn dayXX_part2() {
-        let input = fs::read_to_string("input/dayXX").unwrap();
-        assert_eq!(part_2(&input), 0);
+    fn exampleXX() {
+        let input = input::read("exampleXX").unwrap();
+        assert_eq!(try_part_1(&input), Err(Error::Unsolved));
+        assert_eq!(try_part_2(&input), Err(Error::Unsolved));
     }
 }
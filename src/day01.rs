@@ -1,20 +1,50 @@
-fn first_and_last_digit(line: &str) -> (char, char) {
-    let digits = line.chars().filter(|c| c.is_ascii_digit());
-    let first = digits.clone().next().unwrap();
-    let last = digits.last().unwrap();
-    println!("{first}:{last}");
-    (first, last)
+use crate::{
+    checked::{CheckedIter, OrOverflow},
+    debug,
+    error::Source,
+    stream, Answer, Error, ParseError, Solution,
+};
+use std::{collections::VecDeque, io::BufRead, ops::Range};
+
+fn first_and_last_digit(line: &str) -> Option<(char, char)> {
+    let mut digits = line.chars().filter(|c| c.is_ascii_digit());
+    let first = digits.clone().next()?;
+    let last = digits.next_back()?;
+    debug!("first digit {first}, last digit {last} in {line}");
+    Some((first, last))
 }
 
-pub fn part_1(input: &str) -> u32 {
-    input
+fn total(values: Vec<u32>) -> Result<u32, Error> {
+    Ok(values
+        .into_iter()
+        .checked_sum()
+        .or_overflow(1, "sum of calibration values")?)
+}
+
+fn calibration_value(src: &Source, line: &str) -> Result<u32, ParseError> {
+    let (f, l) = first_and_last_digit(line).ok_or_else(|| src.error(line, "a digit"))?;
+    let number: String = [f, l].iter().collect();
+    Ok(number.parse::<u32>().unwrap())
+}
+
+pub fn try_part_1(input: &str) -> Result<u32, Error> {
+    let src = Source::new(1, input);
+    let values = src
         .lines()
-        .map(first_and_last_digit)
-        .map(|(f, l)| {
-            let number: String = [f, l].iter().collect();
-            number.parse::<u32>().unwrap()
-        })
-        .sum()
+        .map(|line| calibration_value(&src, line))
+        .collect::<Result<_, ParseError>>()?;
+    total(values)
+}
+
+/// `try_part_1` on an input read line by line.
+pub fn try_part_1_reader(reader: impl BufRead) -> Result<u32, Error> {
+    stream::sum_lines(1, reader, "sum of calibration values", |src, line| {
+        Ok(calibration_value(src, line)?)
+    })
+}
+
+pub fn part_1(input: &str) -> u32 {
+    try_part_1(input).unwrap()
 }
 
 const DIGITS: [(&str, &str); 9] = [
@@ -29,76 +59,344 @@ const DIGITS: [(&str, &str); 9] = [
     ("nine", "9"),
 ];
 
-fn first_spelled_out(line: &str) -> Option<(usize, &str)> {
-    DIGITS
-        .iter()
-        .map(|(needle, ch)| (line.find(needle), ch))
-        .filter_map(|(pos, ch)| pos.map(|pos| (pos, *ch)))
-        .min_by_key(|(pos, _)| *pos)
-}
-
-fn last_spelled_out(line: &str) -> Option<(usize, &str)> {
-    DIGITS
-        .iter()
-        .map(|(needle, ch)| (line.rfind(needle), ch))
-        .filter_map(|(pos, ch)| pos.map(|pos| (pos, *ch)))
-        .max_by_key(|(pos, _)| *pos)
-}
-
-fn first_and_last_digit_part2(line: &str) -> (&str, &str) {
-    let first_spelled_out = first_spelled_out(line);
-    let first_digit = line.match_indices(char::is_numeric).next();
-    let first = [first_spelled_out, first_digit]
-        .iter()
-        .filter_map(|candidate| *candidate)
-        .min_by_key(|(pos, _)| *pos)
-        .unwrap()
-        .1;
-    let last_spelled_out = last_spelled_out(line);
-    let last_digit = line.rmatch_indices(char::is_numeric).next();
-    let last = [last_spelled_out, last_digit]
-        .iter()
-        .filter_map(|candidate| *candidate)
-        .max_by_key(|(pos, _)| *pos)
-        .unwrap()
-        .1;
-    (first, last)
+/// Whether a digit of a calibration value was written as a digit or a word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    Numeral,
+    Word,
+}
+
+/// The first or last digits found in a line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    /// Byte offsets in the line.
+    pub span: Range<usize>,
+    pub origin: Origin,
+    pub digits: String,
+}
+
+/// What a line of the calibration document says, see `report`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Calibration {
+    Value {
+        value: u64,
+        first: Match,
+        last: Match,
+    },
+    NoValue,
+}
+
+/// Words that stand for digits in a calibration line, besides the digits
+/// themselves, e.g. "seven" for 7 or "ten" for 1 and 0. A word stands for at
+/// most 9 digits. Matches may overlap, as in "eightwo", and a line is scanned
+/// once, however many words there are.
+pub struct Lexicon {
+    /// Length in bytes and digits of each word.
+    words: Vec<(usize, String)>,
+    /// Aho-Corasick automaton over classes of bytes, where bytes in no word
+    /// share class 0: `next[state * class_count + class]`.
+    classes: [usize; 256],
+    class_count: usize,
+    next: Vec<usize>,
+    /// The word ending in each state.
+    word: Vec<Option<usize>>,
+    /// The closest state on each state's failure path that ends a word.
+    dict: Vec<Option<usize>>,
+}
+
+impl Lexicon {
+    /// `words` with the digits they stand for, e.g. `("zero", "0")`. The
+    /// digits `0` to `9` always stand for themselves.
+    pub fn new<'w>(words: impl IntoIterator<Item = (&'w str, &'w str)>) -> Result<Self, String> {
+        let digits = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+        let mut patterns: Vec<(&str, &str)> = vec![];
+        for (word, value) in digits.iter().map(|d| (*d, *d)).chain(words) {
+            if word.is_empty() {
+                return Err("empty word".to_owned());
+            }
+            if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(format!("{word} stands for {value:?}, not for digits"));
+            }
+            if value.len() > 9 {
+                return Err(format!("{word} stands for more than 9 digits"));
+            }
+            match patterns.iter().find(|(w, _)| *w == word) {
+                Some((_, v)) if *v == value => {}
+                Some((_, v)) => return Err(format!("{word} stands for both {v} and {value}")),
+                None => patterns.push((word, value)),
+            }
+        }
+
+        let mut classes = [0; 256];
+        let mut class_count = 1;
+        for b in patterns.iter().flat_map(|(w, _)| w.bytes()) {
+            if classes[b as usize] == 0 {
+                classes[b as usize] = class_count;
+                class_count += 1;
+            }
+        }
+        // the trie, with missing transitions as `NONE`
+        const NONE: usize = usize::MAX;
+        let mut next = vec![NONE; class_count];
+        let mut word = vec![None];
+        for (i, (w, _)) in patterns.iter().enumerate() {
+            let mut state = 0;
+            for b in w.bytes() {
+                let t = state * class_count + classes[b as usize];
+                if next[t] == NONE {
+                    next[t] = word.len();
+                    next.extend([NONE].repeat(class_count));
+                    word.push(None);
+                }
+                state = next[t];
+            }
+            word[state] = Some(i);
+        }
+        // then the failure links, breadth first, turning missing transitions
+        // into the ones of the failure state
+        let mut fail = vec![0; word.len()];
+        let mut dict = vec![None; word.len()];
+        let mut queue = VecDeque::from([0]);
+        while let Some(state) = queue.pop_front() {
+            for class in 0..class_count {
+                let t = state * class_count + class;
+                let via_fail = match state {
+                    0 => 0,
+                    _ => next[fail[state] * class_count + class],
+                };
+                if next[t] == NONE {
+                    next[t] = via_fail;
+                } else {
+                    let child = next[t];
+                    fail[child] = via_fail;
+                    dict[child] = match word[via_fail] {
+                        Some(_) => Some(via_fail),
+                        None => dict[via_fail],
+                    };
+                    queue.push_back(child);
+                }
+            }
+        }
+
+        Ok(Self {
+            words: patterns
+                .into_iter()
+                .map(|(w, v)| (w.len(), v.to_owned()))
+                .collect(),
+            classes,
+            class_count,
+            next,
+            word,
+            dict,
+        })
+    }
+
+    /// Only the digits, as in part 1.
+    pub fn numerals() -> Self {
+        Self::new([]).unwrap()
+    }
+
+    /// The English words for 1 to 9 of part 2.
+    pub fn english() -> Self {
+        Self::new(DIGITS).unwrap()
+    }
+
+    /// The words starting first and last in `line`, and the number they make.
+    /// Of words starting at the same place, e.g. "four" and "fourteen", the
+    /// longer one counts.
+    pub fn calibrate(&self, line: &str) -> Calibration {
+        // (start, length, word)
+        let mut first: Option<(usize, usize, usize)> = None;
+        let mut last: Option<(usize, usize, usize)> = None;
+        let mut state = 0;
+        for (i, b) in line.bytes().enumerate() {
+            state = self.next[state * self.class_count + self.classes[b as usize]];
+            let mut ending = self.word[state].map(|_| state).or(self.dict[state]);
+            while let Some(s) = ending {
+                let w = self.word[s].unwrap();
+                let len = self.words[w].0;
+                let start = i + 1 - len;
+                if first.map_or(true, |(f, l, _)| (start, l) < (f, len)) {
+                    first = Some((start, len, w));
+                }
+                if last.map_or(true, |(f, l, _)| (start, len) > (f, l)) {
+                    last = Some((start, len, w));
+                }
+                ending = self.dict[s];
+            }
+        }
+        let (Some(first), Some(last)) = (first, last) else {
+            return Calibration::NoValue;
+        };
+        let found = |(start, len, w): (usize, usize, usize)| Match {
+            span: start..start + len,
+            // the digits come first, see `new`
+            origin: if w < 10 {
+                Origin::Numeral
+            } else {
+                Origin::Word
+            },
+            digits: self.words[w].1.clone(),
+        };
+        let (first, last) = (found(first), found(last));
+        debug!("first {first:?}, last {last:?} in {line}");
+        Calibration::Value {
+            // at most 18 digits, which fit
+            value: format!("{}{}", first.digits, last.digits).parse().unwrap(),
+            first,
+            last,
+        }
+    }
+
+    /// The number made of the first and last digits in `line`.
+    pub fn calibration_value(&self, src: &Source, line: &str) -> Result<u32, Error> {
+        match self.calibrate(line) {
+            Calibration::Value { value, .. } => Ok(u32::try_from(value)
+                .ok()
+                .or_overflow(1, "calibration value")?),
+            Calibration::NoValue => Err(src.error(line, "a digit or a spelled out digit").into()),
+        }
+    }
+}
+
+/// What each line of `input` says with digits spelled out as in `lexicon`, to
+/// compare lines between lexicons, e.g. `numerals` and `english`.
+pub fn report(input: &str, lexicon: &Lexicon) -> Vec<Calibration> {
+    input.lines().map(|line| lexicon.calibrate(line)).collect()
+}
+
+/// The sum of the calibration values of `input`, with digits spelled out as
+/// in `lexicon`.
+pub fn calibration_sum(input: &str, lexicon: &Lexicon) -> Result<u32, Error> {
+    let src = Source::new(1, input);
+    let values = src
+        .lines()
+        .map(|line| lexicon.calibration_value(&src, line))
+        .collect::<Result<_, _>>()?;
+    total(values)
+}
+
+/// `calibration_sum` of an input read line by line.
+pub fn calibration_sum_reader(reader: impl BufRead, lexicon: &Lexicon) -> Result<u32, Error> {
+    stream::sum_lines(1, reader, "sum of calibration values", |src, line| {
+        lexicon.calibration_value(src, line)
+    })
+}
+
+pub fn try_part_2(input: &str) -> Result<u32, Error> {
+    calibration_sum(input, &Lexicon::english())
+}
+
+pub fn try_part_2_reader(reader: impl BufRead) -> Result<u32, Error> {
+    calibration_sum_reader(reader, &Lexicon::english())
 }
 
 pub fn part_2(input: &str) -> u32 {
-    input
+    try_part_2(input).unwrap()
+}
+
+/// The calibration value of each line as part 1 and part 2 read it. A line
+/// may only have a value in part 2, e.g. "two", so errors wait for the part
+/// that needs the value.
+pub struct Document {
+    part_1: Vec<Result<u32, Error>>,
+    part_2: Vec<Result<u32, Error>>,
+}
+
+pub fn parse_document(input: &str) -> Document {
+    let src = Source::new(1, input);
+    let english = Lexicon::english();
+    let (part_1, part_2) = src
         .lines()
-        .map(first_and_last_digit_part2)
-        .map(|(f, l)| {
-            let number: String = [f, l].into_iter().collect();
-            number.parse::<u32>().unwrap()
+        .map(|line| {
+            let value = calibration_value(&src, line).map_err(Error::from);
+            (value, english.calibration_value(&src, line))
         })
-        .sum()
+        .unzip();
+    Document { part_1, part_2 }
+}
+
+fn sum_values(values: &[Result<u32, Error>]) -> Result<u32, Error> {
+    total(values.iter().cloned().collect::<Result<_, _>>()?)
+}
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    type Input = Document;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        Ok(parse_document(input))
+    }
+    fn part_1(document: &Self::Input) -> Result<Answer, Error> {
+        Ok(sum_values(&document.part_1)?.into())
+    }
+    fn part_2(document: &Self::Input) -> Result<Answer, Error> {
+        Ok(sum_values(&document.part_2)?.into())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day01::*;
-    use std::fs;
-    #[test]
-    fn example01_part1() {
-        let input = fs::read_to_string("input/example01").unwrap();
-        assert_eq!(part_1(&input), 142);
-    }
     #[test]
-    fn day01_part1() {
-        let input = fs::read_to_string("input/day01").unwrap();
-        assert_eq!(part_1(&input), 54573);
-    }
-    #[test]
-    fn example01_part2() {
-        let input = fs::read_to_string("input/example01_part2").unwrap();
-        assert_eq!(part_2(&input), 281);
+    fn overlapping_and_custom_words() {
+        let english = Lexicon::english();
+        let src = Source::new(1, "eightwo\nxtwone3four\n7pqrstsixteen\nfoo\n");
+        let values: Vec<_> = src
+            .lines()
+            .map(|line| english.calibration_value(&src, line))
+            .collect();
+        assert_eq!(values[..3], [Ok(82), Ok(24), Ok(76)]);
+        assert!(matches!(&values[3], Err(Error::Parse(e)) if e.line == 4));
+
+        let lexicon = Lexicon::new([
+            ("zero", "0"),
+            ("ten", "10"),
+            ("four", "4"),
+            ("fourteen", "14"),
+            ("drei", "3"),
+            ("zwölf", "12"),
+        ])
+        .unwrap();
+        let src = Source::new(1, "");
+        let value = |line| lexicon.calibration_value(&src, line);
+        assert_eq!(value("xtenzwölfdreiy"), Ok(103));
+        assert_eq!(value("fourteenx"), Ok(1414));
+        assert_eq!(value("zwölfourzero"), Ok(120));
+        assert_eq!(value("5drei"), Ok(53));
+        assert_eq!(value("tententen"), Ok(1010));
+        assert!(Lexicon::new([("ten", "10"), ("ten", "1")]).is_err());
+        assert!(Lexicon::new([("ten", "x")]).is_err());
     }
     #[test]
-    fn day01_part2() {
-        let input = fs::read_to_string("input/day01").unwrap();
-        //54086 too low
-        assert_eq!(part_2(&input), 54591);
+    fn reports_where_digits_come_from() {
+        let input = "1abc2\neightwo\nfoo\n";
+        assert_eq!(
+            report(input, &Lexicon::numerals()),
+            [
+                Calibration::Value {
+                    value: 12,
+                    first: Match {
+                        span: 0..1,
+                        origin: Origin::Numeral,
+                        digits: "1".to_owned()
+                    },
+                    last: Match {
+                        span: 4..5,
+                        origin: Origin::Numeral,
+                        digits: "2".to_owned()
+                    },
+                },
+                Calibration::NoValue,
+                Calibration::NoValue,
+            ]
+        );
+        let Calibration::Value { value, first, last } = &report(input, &Lexicon::english())[1]
+        else {
+            panic!("expected a value");
+        };
+        assert_eq!(*value, 82);
+        assert_eq!((first.span.clone(), first.origin), (0..5, Origin::Word));
+        assert_eq!((last.span.clone(), last.origin), (4..7, Origin::Word));
     }
 }
@@ -1,99 +1,377 @@
-const MAX_RED: usize = 12;
-const MAX_GREEN: usize = 13;
-const MAX_BLUE: usize = 14;
+use crate::{
+    checked::{CheckedIter, OrOverflow},
+    error::{Overflow, Source},
+    stream, Answer, Error, ParseError, Solution,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs,
+    io::BufRead,
+    path::Path,
+};
 
-struct Reveal {
-    red: usize,
-    green: usize,
-    blue: usize,
+/// The limits of part 1.
+const LIMITS: [(&str, usize); 3] = [("red", 12), ("green", 13), ("blue", 14)];
+
+/// Numbers of cubes by colour, in a reveal or a bag. Colours not in it have
+/// none.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cubes(BTreeMap<String, usize>);
+
+impl<S: Into<String>> FromIterator<(S, usize)> for Cubes {
+    fn from_iter<I: IntoIterator<Item = (S, usize)>>(iter: I) -> Self {
+        Self(iter.into_iter().map(|(c, n)| (c.into(), n)).collect())
+    }
 }
 
-impl Reveal {
-    fn from_str(s: &str) -> Self {
-        let mut red = 0;
-        let mut green = 0;
-        let mut blue = 0;
+impl Cubes {
+    pub fn count(&self, colour: &str) -> usize {
+        self.0.get(colour).copied().unwrap_or(0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.0.iter().map(|(c, n)| (c.as_str(), *n))
+    }
+
+    pub fn fits_in(&self, bag: &Cubes) -> bool {
+        self.iter().all(|(c, n)| n <= bag.count(c))
+    }
+
+    /// Adds the cubes in `s`, e.g. `3 blue, 4 red`, failing on colours it
+    /// already has.
+    fn add(&mut self, src: &Source, s: &str) -> Result<(), ParseError> {
         for part in s.split(", ") {
-            let (count, color) = part.split_once(' ').unwrap();
-            match color {
-                "red" => red = count.parse().unwrap(),
-                "green" => green = count.parse().unwrap(),
-                "blue" => blue = count.parse().unwrap(),
-                _ => panic!(),
+            let (count, colour) = src.split_once(part, " ")?;
+            let count = src.parse(count, "a cube count")?;
+            if colour.is_empty() {
+                return Err(src.error(colour, "a colour"));
             }
+            if self.0.insert(colour.to_owned(), count).is_some() {
+                return Err(src.error(colour, "a colour not counted already"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Written like reveals, e.g. `3 blue, 4 red`.
+impl fmt::Display for Cubes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (colour, n)) in self.iter().enumerate() {
+            let sep = if i == 0 { "" } else { ", " };
+            write!(f, "{sep}{n} {colour}")?;
         }
-        Self { red, green, blue }
+        Ok(())
     }
 }
 
-struct Game {
+/// The bag of part 1: 12 red, 13 green and 14 blue cubes.
+pub fn standard_limits() -> Cubes {
+    LIMITS.into_iter().collect()
+}
+
+/// Bag limits written like reveals, on one line or several, e.g.
+/// `12 red, 13 green\n14 blue`.
+pub fn parse_limits(text: &str) -> Result<Cubes, ParseError> {
+    let src = Source::new(2, text);
+    let mut limits = Cubes::default();
+    for line in src.lines().filter(|line| !line.trim().is_empty()) {
+        limits.add(&src, line.trim())?;
+    }
+    Ok(limits)
+}
+
+pub fn read_limits(path: &Path) -> Result<Cubes, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    parse_limits(&text).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// The cubes shown at once in a game.
+pub type Reveal = Cubes;
+
+pub struct Game {
+    pub id: usize,
     reveals: Vec<Reveal>,
 }
 
 impl Game {
-    fn from_str(line: &str) -> Self {
-        let (_, rest) = line.split_once(": ").unwrap();
-        let reveals: Vec<_> = rest.split("; ").map(Reveal::from_str).collect();
-        Game { reveals }
+    fn from_str(src: &Source, line: &str) -> Result<Self, ParseError> {
+        let line = src.strip_prefix(line, "Game ")?;
+        let (id, rest) = src.split_once(line, ": ")?;
+        let id = src.parse(id, "a game id")?;
+        let reveals = rest
+            .split("; ")
+            .map(|s| {
+                let mut reveal = Reveal::default();
+                reveal.add(src, s)?;
+                Ok(reveal)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Game { id, reveals })
     }
 
-    fn is_valid(&self) -> bool {
-        self.reveals
-            .iter()
-            .all(|rev| rev.red <= MAX_RED && rev.green <= MAX_GREEN && rev.blue <= MAX_BLUE)
+    pub fn reveals(&self) -> &[Reveal] {
+        &self.reveals
     }
 
-    fn power(&self) -> usize {
-        let max = self.reveals.iter().fold((0, 0, 0), |max, rev| {
-            (
-                max.0.max(rev.red),
-                max.1.max(rev.green),
-                max.2.max(rev.blue),
-            )
-        });
-        max.0 * max.1 * max.2
+    pub fn is_possible(&self, bag: &Cubes) -> bool {
+        self.reveals.iter().all(|rev| rev.fits_in(bag))
+    }
+
+    /// The first reveal `bag` could not have given, if any.
+    pub fn excess(&self, bag: &Cubes) -> Option<Excess> {
+        self.reveals.iter().enumerate().find_map(|(reveal, rev)| {
+            let (colour, shown) = rev.iter().find(|(c, n)| *n > bag.count(c))?;
+            Some(Excess {
+                reveal,
+                colour: colour.to_owned(),
+                shown,
+                limit: bag.count(colour),
+            })
+        })
+    }
+
+    /// The fewest cubes of each colour shown that the game could be played
+    /// with.
+    pub fn min_bag(&self) -> Cubes {
+        let mut bag = Cubes::default();
+        for (colour, n) in self.reveals.iter().flat_map(Cubes::iter) {
+            let max = bag.0.entry(colour.to_owned()).or_default();
+            *max = (*max).max(n);
+        }
+        bag
+    }
+
+    /// The product of the cubes of `colours` in the minimum bag, 0 if one of
+    /// them was never shown.
+    pub fn power<'c>(&self, colours: impl IntoIterator<Item = &'c str>) -> Option<usize> {
+        let bag = self.min_bag();
+        let counts: Vec<usize> = colours.into_iter().map(|c| bag.count(c)).collect();
+        if counts.contains(&0) {
+            return Some(0);
+        }
+        counts.into_iter().checked_product()
+    }
+}
+
+/// A reveal showing more cubes of a colour than a bag has.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Excess {
+    /// Index of the reveal in the game, from 0.
+    pub reveal: usize,
+    /// The colour there are too many of, the first by name if there are
+    /// several.
+    pub colour: String,
+    pub shown: usize,
+    pub limit: usize,
+}
+
+/// What a bag makes of a game, see `report`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameReport {
+    pub id: usize,
+    /// Why the game is impossible, if it is.
+    pub excess: Option<Excess>,
+    pub min_bag: Cubes,
+}
+
+impl fmt::Display for GameReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Game {}: ", self.id)?;
+        match &self.excess {
+            Some(e) => write!(
+                f,
+                "impossible, reveal {} shows {} {} of {}",
+                e.reveal + 1,
+                e.shown,
+                e.colour,
+                e.limit
+            )?,
+            None => write!(f, "possible")?,
+        }
+        write!(f, "; fewest cubes {}", self.min_bag)
     }
 }
 
+/// Whether each game is possible with `limits`, why not, and the fewest
+/// cubes it could be played with, to check the answer of part 1 game by game.
+pub fn report(games: &[Game], limits: &Cubes) -> Vec<GameReport> {
+    games
+        .iter()
+        .map(|game| GameReport {
+            id: game.id,
+            excess: game.excess(limits),
+            min_bag: game.min_bag(),
+        })
+        .collect()
+}
+
+pub fn parse_games(input: &str) -> Result<Vec<Game>, ParseError> {
+    let src = Source::new(2, input);
+    src.lines().map(|line| Game::from_str(&src, line)).collect()
+}
+
+/// Every colour shown in `games`.
+pub fn colours(games: &[Game]) -> BTreeSet<&str> {
+    games
+        .iter()
+        .flat_map(|game| &game.reveals)
+        .flat_map(|rev| rev.0.keys().map(String::as_str))
+        .collect()
+}
+
+pub fn sum_possible_ids(games: &[Game], limits: &Cubes) -> Result<usize, Overflow> {
+    games
+        .iter()
+        .filter_map(|game| game.is_possible(limits).then_some(game.id))
+        .checked_sum()
+        .or_overflow(2, "sum of game ids")
+}
+
+/// The sum of the powers of `games` over every colour shown in them.
+pub fn sum_powers(games: &[Game]) -> Result<usize, Overflow> {
+    let colours = colours(games);
+    games
+        .iter()
+        .try_fold(0_usize, |sum, game| {
+            sum.checked_add(game.power(colours.iter().copied())?)
+        })
+        .or_overflow(2, "sum of powers")
+}
+
+pub fn try_part_1_with(input: &str, limits: &Cubes) -> Result<usize, Error> {
+    Ok(sum_possible_ids(&parse_games(input)?, limits)?)
+}
+
+pub fn try_part_1(input: &str) -> Result<usize, Error> {
+    try_part_1_with(input, &standard_limits())
+}
+
+pub fn try_part_2(input: &str) -> Result<usize, Error> {
+    Ok(sum_powers(&parse_games(input)?)?)
+}
+
+/// `try_part_1_with` on an input read line by line, a game at a time.
+pub fn try_part_1_reader_with(reader: impl BufRead, limits: &Cubes) -> Result<usize, Error> {
+    stream::sum_lines(2, reader, "sum of game ids", |src, line| {
+        let game = Game::from_str(src, line)?;
+        Ok(if game.is_possible(limits) { game.id } else { 0 })
+    })
+}
+
+pub fn try_part_1_reader(reader: impl BufRead) -> Result<usize, Error> {
+    try_part_1_reader_with(reader, &standard_limits())
+}
+
+/// `try_part_2` on an input read line by line. A colour first shown in a
+/// game was shown in none before it, so their powers all become 0.
+pub fn try_part_2_reader(reader: impl BufRead) -> Result<usize, Error> {
+    let mut colours: BTreeSet<String> = BTreeSet::new();
+    // `None` once it overflowed
+    let mut sum = Some(0_usize);
+    stream::for_each_line(2, reader, |src, line| {
+        let game = Game::from_str(src, line)?;
+        for (colour, _) in game.min_bag().iter() {
+            if colours.insert(colour.to_owned()) {
+                sum = Some(0);
+            }
+        }
+        let power = game.power(colours.iter().map(String::as_str));
+        sum = sum
+            .zip(power)
+            .and_then(|(sum, power)| sum.checked_add(power));
+        Ok(())
+    })?;
+    Ok(sum.or_overflow(2, "sum of powers")?)
+}
+
 pub fn part_1(input: &str) -> usize {
-    input
-        .lines()
-        .map(Game::from_str)
-        .enumerate()
-        .filter_map(|(i, game)| game.is_valid().then_some(i + 1))
-        .sum()
+    try_part_1(input).unwrap()
 }
 
 pub fn part_2(input: &str) -> usize {
-    input
-        .lines()
-        .map(Game::from_str)
-        .map(|game| game.power())
-        .sum()
+    try_part_2(input).unwrap()
+}
+
+pub struct Day02;
+
+impl Solution for Day02 {
+    type Input = Vec<Game>;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_games(input)
+    }
+    fn part_1(games: &Self::Input) -> Result<Answer, Error> {
+        Ok(sum_possible_ids(games, &standard_limits())?.into())
+    }
+    fn part_2(games: &Self::Input) -> Result<Answer, Error> {
+        Ok(sum_powers(games)?.into())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day02::*;
-    use std::fs;
-    #[test]
-    fn example02_part1() {
-        let input = fs::read_to_string("input/example02").unwrap();
-        assert_eq!(part_1(&input), 8);
-    }
     #[test]
-    fn day02_part1() {
-        let input = fs::read_to_string("input/day02").unwrap();
-        assert_eq!(part_1(&input), 2551);
+    fn repeated_colour_part1() {
+        let input = "Game 1: 3 blue\nGame 2: 1 red, 4 red\n";
+        let Err(Error::Parse(err)) = try_part_1(input) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!((err.line, err.column), (2, 18));
+        assert_eq!(err.found, "`red`");
     }
     #[test]
-    fn example02_part2() {
-        let input = fs::read_to_string("input/example02").unwrap();
-        assert_eq!(part_2(&input), 2286);
+    fn any_colours_and_limits() {
+        let input = "Game 1: 3 blue, 2 purple; 1 teal\nGame 2: 1 red, 4 purple\n\
+                     Game 3: 2 teal, 1 blue, 1 red, 2 purple\n";
+        let games = parse_games(input).unwrap();
+        assert_eq!(
+            colours(&games).into_iter().collect::<Vec<_>>(),
+            ["blue", "purple", "red", "teal"]
+        );
+        assert_eq!(sum_possible_ids(&games, &standard_limits()), Ok(0));
+        let limits = parse_limits("3 blue, 2 purple\n2 teal, 1 red\n").unwrap();
+        assert_eq!(sum_possible_ids(&games, &limits), Ok(4));
+        assert_eq!(
+            games[0].min_bag(),
+            [("blue", 3), ("purple", 2), ("teal", 1)]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(sum_powers(&games), Ok(4));
+        assert_eq!(try_part_2_reader(input.as_bytes()), Ok(4));
+        assert!(parse_limits("1 red, 2 red").is_err());
     }
     #[test]
-    fn day02_part2() {
-        let input = fs::read_to_string("input/day02").unwrap();
-        assert_eq!(part_2(&input), 62811);
+    fn parsed_ids_and_reports() {
+        let input = "Game 7: 3 blue, 4 red; 1 red, 2 green\n\
+                     Game 3: 20 red, 8 green; 15 blue, 13 red\nGame 12: 1 green\n";
+        assert_eq!(try_part_1(input), Ok(19));
+        let reports = report(&parse_games(input).unwrap(), &standard_limits());
+        assert_eq!(
+            reports[1].excess,
+            Some(Excess {
+                reveal: 0,
+                colour: "red".to_owned(),
+                shown: 20,
+                limit: 12
+            })
+        );
+        assert_eq!(
+            reports[1].to_string(),
+            "Game 3: impossible, reveal 1 shows 20 red of 12; fewest cubes 15 blue, 8 green, 20 red"
+        );
+        assert_eq!(
+            reports[0].to_string(),
+            "Game 7: possible; fewest cubes 3 blue, 2 green, 4 red"
+        );
+        assert_eq!(
+            parse_limits(&reports[1].min_bag.to_string()),
+            Ok(reports[1].min_bag.clone())
+        );
+        let Err(Error::Parse(err)) = try_part_1("Game x: 1 red\n") else {
+            panic!("expected a parse error");
+        };
+        assert_eq!((err.column, err.expected.as_str()), (6, "a game id"));
     }
 }
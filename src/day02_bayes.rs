@@ -0,0 +1,248 @@
+use crate::day02::{Cubes, Reveal};
+use std::collections::BTreeMap;
+
+/// Independent weights for how many cubes of each colour the bag has, from 0
+/// up. Weights need not add up to 1.
+#[derive(Debug, Clone)]
+pub struct Prior {
+    weights: BTreeMap<String, Vec<f64>>,
+}
+
+impl Prior {
+    pub fn new<S: Into<String>>(
+        weights: impl IntoIterator<Item = (S, Vec<f64>)>,
+    ) -> Result<Self, String> {
+        let weights: BTreeMap<String, Vec<f64>> =
+            weights.into_iter().map(|(c, w)| (c.into(), w)).collect();
+        for (colour, w) in &weights {
+            if w.iter().any(|w| !w.is_finite() || *w < 0.0) {
+                return Err(format!("{colour}: weights must be finite and not negative"));
+            }
+            if !w.iter().any(|w| *w > 0.0) {
+                return Err(format!("{colour}: no count has any weight"));
+            }
+        }
+        Ok(Self { weights })
+    }
+
+    /// Any number of each of `colours` from 0 to `max`, all as likely.
+    pub fn uniform<'c>(colours: impl IntoIterator<Item = &'c str>, max: usize) -> Self {
+        Self::new(colours.into_iter().map(|c| (c, vec![1.0; max + 1]))).unwrap()
+    }
+}
+
+/// `ln(n!)` for every `n` up to some maximum.
+struct LnFactorials(Vec<f64>);
+
+impl LnFactorials {
+    fn new(max: usize) -> Self {
+        let mut ln = vec![0.0; max + 1];
+        for n in 1..=max {
+            ln[n] = ln[n - 1] + (n as f64).ln();
+        }
+        Self(ln)
+    }
+
+    /// `ln` of `n` choose `k`, for `k <= n`.
+    fn choose(&self, n: usize, k: usize) -> f64 {
+        self.0[n] - self.0[k] - self.0[n - k]
+    }
+}
+
+/// How likely each bag is after seeing some reveals, see `posterior`.
+#[derive(Debug, Clone)]
+pub struct Posterior {
+    colours: Vec<String>,
+    /// Counts in the order of `colours` and the probability of every bag that
+    /// has one, the most likely first.
+    bags: Vec<(Vec<usize>, f64)>,
+}
+
+impl Posterior {
+    pub fn colours(&self) -> &[String] {
+        &self.colours
+    }
+
+    fn cubes(&self, counts: &[usize]) -> Cubes {
+        self.colours
+            .iter()
+            .map(String::as_str)
+            .zip(counts.iter().copied())
+            .collect()
+    }
+
+    /// Every bag the reveals could have come from, the most likely first.
+    pub fn bags(&self) -> impl Iterator<Item = (Cubes, f64)> + '_ {
+        self.bags.iter().map(|(n, p)| (self.cubes(n), *p))
+    }
+
+    /// The most likely bag, which with a uniform prior is the maximum
+    /// likelihood estimate.
+    pub fn most_likely(&self) -> Cubes {
+        self.cubes(&self.bags[0].0)
+    }
+
+    pub fn probability(&self, bag: &Cubes) -> f64 {
+        self.bags
+            .iter()
+            .find(|(n, _)| self.colours.iter().zip(n).all(|(c, n)| bag.count(c) == *n))
+            .map_or(0.0, |(_, p)| *p)
+    }
+
+    /// The probability of each number of cubes of `colour`, from 0 up.
+    pub fn marginal(&self, colour: &str) -> Vec<f64> {
+        let Some(i) = self.colours.iter().position(|c| c == colour) else {
+            return vec![1.0];
+        };
+        let mut res = vec![];
+        for (n, p) in &self.bags {
+            if res.len() <= n[i] {
+                res.resize(n[i] + 1, 0.0);
+            }
+            res[n[i]] += p;
+        }
+        res
+    }
+
+    pub fn mean(&self, colour: &str) -> f64 {
+        self.marginal(colour)
+            .iter()
+            .enumerate()
+            .map(|(n, p)| n as f64 * p)
+            .sum()
+    }
+}
+
+/// The probability of each bag of the colours of `prior` given `reveals` of
+/// it. Each reveal draws its cubes from the bag at once, without replacement,
+/// and puts them back before the next, so that a reveal of `k` cubes is a
+/// draw from the multivariate hypergeometric distribution.
+///
+/// Every bag the prior allows is weighed, as many as the product of the
+/// lengths of its weights, so the prior should stay small.
+pub fn posterior<'r>(
+    reveals: impl IntoIterator<Item = &'r Reveal>,
+    prior: &Prior,
+) -> Result<Posterior, String> {
+    let colours: Vec<String> = prior.weights.keys().cloned().collect();
+    // identical reveals are weighed once, times how many there are
+    let mut seen: BTreeMap<Vec<usize>, f64> = BTreeMap::new();
+    for reveal in reveals {
+        if let Some((colour, _)) = reveal.iter().find(|(c, _)| !prior.weights.contains_key(*c)) {
+            return Err(format!(
+                "{colour} was shown, but the prior has no {colour} cubes"
+            ));
+        }
+        let shown = colours.iter().map(|c| reveal.count(c)).collect();
+        *seen.entry(shown).or_default() += 1.0;
+    }
+    let weights: Vec<&[f64]> = prior.weights.values().map(Vec::as_slice).collect();
+    let ln = LnFactorials::new(weights.iter().map(|w| w.len() - 1).sum());
+    let fewest: Vec<usize> = (0..colours.len())
+        .map(|i| seen.keys().map(|shown| shown[i]).max().unwrap_or(0))
+        .collect();
+
+    let mut bags: Vec<(Vec<usize>, f64)> = vec![];
+    let mut counts = fewest.clone();
+    'bags: loop {
+        let mut ln_p: f64 = counts
+            .iter()
+            .zip(&weights)
+            .map(|(n, w)| w.get(*n).map_or(f64::NEG_INFINITY, |w| w.ln()))
+            .sum();
+        if ln_p > f64::NEG_INFINITY {
+            let total = counts.iter().sum();
+            for (shown, times) in &seen {
+                let drawn = shown.iter().sum();
+                if drawn > total {
+                    ln_p = f64::NEG_INFINITY;
+                    break;
+                }
+                let ways: f64 = counts
+                    .iter()
+                    .zip(shown)
+                    .map(|(n, k)| ln.choose(*n, *k))
+                    .sum();
+                ln_p += times * (ways - ln.choose(total, drawn));
+            }
+            if ln_p > f64::NEG_INFINITY {
+                bags.push((counts.clone(), ln_p));
+            }
+        }
+        // the next bag, counting up like an odometer
+        for i in 0..counts.len() {
+            if counts[i] + 1 < weights[i].len() {
+                counts[i] += 1;
+                continue 'bags;
+            }
+            counts[i] = fewest[i];
+        }
+        break;
+    }
+
+    let Some(max) = bags.iter().map(|(_, ln_p)| *ln_p).reduce(f64::max) else {
+        return Err("no bag the prior allows could have given the reveals".to_owned());
+    };
+    let total: f64 = bags.iter().map(|(_, ln_p)| (ln_p - max).exp()).sum();
+    for (_, p) in &mut bags {
+        *p = (*p - max).exp() / total;
+    }
+    bags.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(Posterior { colours, bags })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{day02::parse_games, day02_bayes::*};
+    #[test]
+    fn two_red_of_small_bags() {
+        // a reveal of 2 red from bags of at most 2 red and 2 blue: 2 red and
+        // no blue is 1, with 1 blue 1/3 and with 2 blue 1/6 as likely
+        let games = parse_games("Game 1: 2 red\n").unwrap();
+        let prior = Prior::uniform(["red", "blue"], 2);
+        let post = posterior(games[0].reveals(), &prior).unwrap();
+        assert_eq!(
+            post.most_likely(),
+            [("red", 2), ("blue", 0)].into_iter().collect()
+        );
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-12;
+        assert!(close(post.probability(&post.most_likely()), 2.0 / 3.0));
+        let marginal = post.marginal("blue");
+        assert!(close(marginal[1], 2.0 / 9.0) && close(marginal[2], 1.0 / 9.0));
+        assert!(close(post.mean("blue"), 4.0 / 9.0));
+        let red = post.marginal("red");
+        assert!(red.len() == 3 && red[0] == 0.0 && close(red[2], 1.0));
+        assert_eq!(post.bags().count(), 3);
+    }
+    #[test]
+    fn reveals_of_several_games() {
+        let games = parse_games(
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\n\
+             Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue\n",
+        )
+        .unwrap();
+        let reveals = games.iter().flat_map(|game| game.reveals());
+        let prior = Prior::uniform(["red", "green", "blue"], 15);
+        let post = posterior(reveals.clone(), &prior).unwrap();
+        let best = post.most_likely();
+        assert!(reveals.clone().all(|rev| rev.fits_in(&best)));
+        let total: f64 = post.bags().map(|(_, p)| p).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        // a prior sure of 6 green cubes
+        let mut weights = vec![0.0; 7];
+        weights[6] = 1.0;
+        let prior = Prior::new([
+            ("red", vec![1.0; 16]),
+            ("green", weights),
+            ("blue", vec![1.0; 16]),
+        ])
+        .unwrap();
+        let post = posterior(reveals.clone(), &prior).unwrap();
+        assert!((post.marginal("green")[6] - 1.0).abs() < 1e-9);
+
+        assert!(posterior(reveals.clone(), &Prior::uniform(["red", "blue"], 9)).is_err());
+        assert!(posterior(reveals, &Prior::uniform(["red", "green", "blue"], 3)).is_err());
+        assert!(Prior::new([("red", vec![0.0, -1.0])]).is_err());
+    }
+}
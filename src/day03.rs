@@ -1,145 +1,352 @@
-struct EngineNumber {
+use crate::{
+    checked::{CheckedIter, OrOverflow},
+    error::{Overflow, Source},
+    geometry::Point,
+    grid::{Grid, Pos},
+    Answer, Error, ParseError, Solution,
+};
+use std::ops::RangeInclusive;
+
+pub struct EngineNumber {
     x_start: usize,
     x_end: usize,
     y: usize,
     n: u32,
 }
-impl EngineNumber {
-    fn symbol_is_adjacent(&self, symbol: &Symbol) -> bool {
-        let (sx, sy) = (symbol.x, symbol.y);
-        let x_start = self.x_start.saturating_sub(1);
-        let y_start = self.y.saturating_sub(1);
-        x_start <= sx && sx <= self.x_end + 1 && y_start <= sy && sy <= self.y + 1
+
+pub struct Symbol {
+    x: usize,
+    y: usize,
+    ch: char,
+}
+
+/// What is in a cell of a schematic, by index in `Schematic`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Empty,
+    Number(usize),
+    Symbol(usize),
+}
+
+/// The numbers and symbols of a schematic, and where each one is, so that
+/// their neighbours are found without searching.
+pub struct Schematic {
+    numbers: Vec<EngineNumber>,
+    symbols: Vec<Symbol>,
+    cells: Grid<Cell>,
+}
+
+impl Schematic {
+    fn new(numbers: Vec<EngineNumber>, symbols: Vec<Symbol>, width: usize, height: usize) -> Self {
+        let mut cells = Grid::new(width, height, Cell::Empty);
+        for (i, en) in numbers.iter().enumerate() {
+            for x in en.x_start..=en.x_end {
+                cells[Point::new(x, en.y)] = Cell::Number(i);
+            }
+        }
+        for (i, symbol) in symbols.iter().enumerate() {
+            cells[Point::new(symbol.x, symbol.y)] = Cell::Symbol(i);
+        }
+        Self {
+            numbers,
+            symbols,
+            cells,
+        }
     }
 
-    fn is_part_number(&self, symbols: &[Symbol]) -> bool {
-        symbols.iter().any(|s| self.symbol_is_adjacent(s))
+    /// The symbols sharing an edge with `en`, or a corner too if `diagonal`,
+    /// going round it.
+    fn symbols_next_to<'s>(
+        &'s self,
+        en: &EngineNumber,
+        diagonal: bool,
+    ) -> impl Iterator<Item = &'s Symbol> + 's {
+        let (y, x_start, x_end) = (en.y, en.x_start, en.x_end);
+        let row = move |y: usize| {
+            let xs = match diagonal {
+                true => x_start.saturating_sub(1)..=x_end + 1,
+                false => x_start..=x_end,
+            };
+            xs.map(move |x| (x, y))
+        };
+        y.checked_sub(1)
+            .into_iter()
+            .flat_map(row)
+            .chain(x_start.checked_sub(1).map(|x| (x, y)))
+            .chain([(x_end + 1, y)])
+            .chain(row(y + 1))
+            .filter_map(|(x, y)| match self.cells.get(Point::new(x, y))? {
+                Cell::Symbol(i) => Some(&self.symbols[*i]),
+                _ => None,
+            })
+    }
+
+    fn is_part_number(&self, en: &EngineNumber) -> bool {
+        self.symbols_next_to(en, true).next().is_some()
+    }
+
+    /// The numbers sharing an edge with `symbol`, or a corner too if
+    /// `diagonal`, each once, in reading order.
+    fn numbers_next_to(&self, symbol: &Symbol, diagonal: bool) -> Vec<&EngineNumber> {
+        let pos: Pos = Point::new(symbol.x, symbol.y);
+        let neighbours: Vec<Pos> = match diagonal {
+            true => self.cells.neighbours_8(pos).collect(),
+            false => self.cells.neighbours(pos).collect(),
+        };
+        let mut indices: Vec<usize> = neighbours
+            .into_iter()
+            .filter_map(|p| match self.cells[p] {
+                Cell::Number(i) => Some(i),
+                _ => None,
+            })
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        indices.into_iter().map(|i| &self.numbers[i]).collect()
+    }
+
+    /// Every symbol that is a gear by `rule`, in reading order.
+    pub fn gears(&self, rule: &GearRule) -> Result<Vec<Gear>, Overflow> {
+        let mut gears = vec![];
+        for symbol in &self.symbols {
+            if !rule.symbols.contains(&symbol.ch) {
+                continue;
+            }
+            let numbers = self.numbers_next_to(symbol, rule.diagonal);
+            if !rule.neighbours.contains(&numbers.len()) {
+                continue;
+            }
+            let values = numbers.iter().map(|en| en.n);
+            let value = match rule.aggregation {
+                Aggregation::Product => values.checked_product(),
+                Aggregation::Sum => values.checked_sum(),
+                Aggregation::Max => Some(values.max().unwrap_or(0)),
+            };
+            gears.push(Gear {
+                pos: Point::new(symbol.x, symbol.y),
+                symbol: symbol.ch,
+                numbers: numbers
+                    .iter()
+                    .map(|en| (Point::new(en.x_start, en.y), en.n))
+                    .collect(),
+                value: value.or_overflow(3, "gear ratio")?,
+            });
+        }
+        Ok(gears)
     }
 }
 
-struct Symbol {
-    x: usize,
-    y: usize,
-    ch: char,
+/// How the numbers next to a gear make its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregation {
+    Product,
+    Sum,
+    Max,
+}
+
+/// Which symbols are gears, and what they are worth. The default is part 2's:
+/// a `*` next to exactly two numbers, worth their product.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GearRule {
+    pub symbols: Vec<char>,
+    /// How many numbers a gear is next to, e.g. `2..=2`.
+    pub neighbours: RangeInclusive<usize>,
+    pub aggregation: Aggregation,
+    /// Whether numbers touching only a corner of the symbol are next to it.
+    pub diagonal: bool,
+}
+
+impl Default for GearRule {
+    fn default() -> Self {
+        Self {
+            symbols: vec!['*'],
+            neighbours: 2..=2,
+            aggregation: Aggregation::Product,
+            diagonal: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gear {
+    pub pos: Pos,
+    pub symbol: char,
+    /// Where each number next to it starts, and the number, in reading order.
+    pub numbers: Vec<(Pos, u32)>,
+    pub value: u32,
 }
 
 fn parse_line(
+    src: &Source,
     line: &str,
     line_nr: usize,
     engine_numbers: &mut Vec<EngineNumber>,
     symbols: &mut Vec<Symbol>,
-) {
-    let mut digits = String::new();
-    let mut digits_start = 0;
-    let mut digits_end = 0;
-    let mut close_digits = |to_close: &mut String, x_start, x_end| {
-        let n: u32 = to_close.parse().unwrap();
+) -> Result<(), ParseError> {
+    // byte offset and x of the first digit of the number being read
+    let mut digits_start = None;
+    let mut close_digits = |(i_start, x_start), i_end, x_end| -> Result<(), ParseError> {
+        let n: u32 = src.parse(&line[i_start..i_end], "a number that fits in u32")?;
         engine_numbers.push(EngineNumber {
             x_start,
             x_end,
             y: line_nr,
             n,
         });
-        to_close.clear();
+        Ok(())
     };
 
-    for (x, ch) in line.chars().enumerate() {
+    for (x, (i, ch)) in line.char_indices().enumerate() {
         if ch.is_ascii_digit() {
-            if digits.is_empty() {
-                digits_start = x;
-                digits_end = x;
-            } else {
-                digits_end += 1;
-            }
-            digits.push(ch);
+            digits_start.get_or_insert((i, x));
             continue;
         }
 
         if ch != '.' {
             symbols.push(Symbol { x, y: line_nr, ch });
         }
-        if !digits.is_empty() {
-            close_digits(&mut digits, digits_start, digits_end);
+        if let Some(start) = digits_start.take() {
+            close_digits(start, i, x - 1)?;
         }
     }
-    if !digits.is_empty() {
-        close_digits(&mut digits, digits_start, digits_end);
+    if let Some(start) = digits_start {
+        close_digits(start, line.len(), line.chars().count() - 1)?;
     }
+    Ok(())
 }
 
-fn parse_numbers_symbols(input: &str) -> (Vec<EngineNumber>, Vec<Symbol>) {
+pub fn parse_numbers_symbols(input: &str) -> Result<Schematic, ParseError> {
+    let src = Source::new(3, input);
     let mut engine_numbers = Vec::new();
     let mut symbols = Vec::new();
-    for (line_nr, line) in input.lines().enumerate() {
-        parse_line(line, line_nr, &mut engine_numbers, &mut symbols);
+    let (mut width, mut height) = (0, 0);
+    for (line_nr, line) in src.lines().enumerate() {
+        parse_line(&src, line, line_nr, &mut engine_numbers, &mut symbols)?;
+        width = width.max(line.chars().count());
+        height = line_nr + 1;
     }
-    (engine_numbers, symbols)
+    Ok(Schematic::new(engine_numbers, symbols, width, height))
 }
 
-fn sum_part_numbers(engine_numbers: &[EngineNumber], sym_map: &[Symbol]) -> u32 {
-    engine_numbers
+fn sum_part_numbers(schematic: &Schematic) -> Result<u32, Overflow> {
+    schematic
+        .numbers
         .iter()
-        .filter(|en| en.is_part_number(sym_map))
+        .filter(|en| schematic.is_part_number(en))
         .map(|en| en.n)
-        .sum()
+        .checked_sum()
+        .or_overflow(3, "sum of part numbers")
+}
+
+pub fn try_part_1(input: &str) -> Result<u32, Error> {
+    Ok(sum_part_numbers(&parse_numbers_symbols(input)?)?)
 }
 
 pub fn part_1(input: &str) -> u32 {
-    let (engine_numbers, symbols) = parse_numbers_symbols(input);
-    sum_part_numbers(&engine_numbers, &symbols)
+    try_part_1(input).unwrap()
 }
 
-fn sum_gear_ratios(engine_numbers: &[EngineNumber], symbols: &[Symbol]) -> u32 {
-    let mut sum = 0;
-    for symbol in symbols.iter() {
-        if symbol.ch != '*' {
-            continue;
-        }
-        let mut adjacent_numbers = Vec::new();
-        for en in engine_numbers {
-            if en.symbol_is_adjacent(symbol) {
-                if adjacent_numbers.len() == 2 {
-                    // too many numbers
-                    adjacent_numbers.clear();
-                    break;
-                }
-                adjacent_numbers.push(en.n);
-            }
-        }
-        if adjacent_numbers.len() == 2 {
-            sum += adjacent_numbers[0] * adjacent_numbers[1];
-        }
-    }
-    sum
+fn sum_gear_ratios(schematic: &Schematic) -> Result<u32, Overflow> {
+    schematic
+        .gears(&GearRule::default())?
+        .iter()
+        .map(|gear| gear.value)
+        .checked_sum()
+        .or_overflow(3, "sum of gear ratios")
+}
+
+pub fn try_part_2(input: &str) -> Result<u32, Error> {
+    Ok(sum_gear_ratios(&parse_numbers_symbols(input)?)?)
 }
 
 pub fn part_2(input: &str) -> u32 {
-    let (engine_numbers, symbols) = parse_numbers_symbols(input);
-    sum_gear_ratios(&engine_numbers, &symbols)
+    try_part_2(input).unwrap()
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::day03::*;
-    use std::fs;
-    #[test]
-    fn example03_part1() {
-        let input = fs::read_to_string("input/example03").unwrap();
-        assert_eq!(part_1(&input), 4361);
+pub struct Day03;
+
+impl Solution for Day03 {
+    type Input = Schematic;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_numbers_symbols(input)
     }
-    #[test]
-    fn day03_part1() {
-        let input = fs::read_to_string("input/day03").unwrap();
-        assert_eq!(part_1(&input), 525911);
+    fn part_1(schematic: &Self::Input) -> Result<Answer, Error> {
+        Ok(sum_part_numbers(schematic)?.into())
+    }
+    fn part_2(schematic: &Self::Input) -> Result<Answer, Error> {
+        Ok(sum_gear_ratios(schematic)?.into())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{day03::*, generate, rng::Rng};
     #[test]
-    fn example03_part2() {
-        let input = fs::read_to_string("input/example03").unwrap();
-        assert_eq!(part_2(&input), 467835);
+    fn index_finds_what_a_search_does() {
+        let next_to = |en: &EngineNumber, s: &Symbol| {
+            en.x_start <= s.x + 1 && s.x <= en.x_end + 1 && en.y <= s.y + 1 && s.y <= en.y + 1
+        };
+        for seed in 0..20 {
+            let input = generate::input(3, &mut Rng::new(seed)).unwrap();
+            let schematic = parse_numbers_symbols(&input).unwrap();
+            for en in &schematic.numbers {
+                let found: Vec<_> = schematic
+                    .symbols_next_to(en, true)
+                    .map(|s| (s.x, s.y))
+                    .collect();
+                let mut searched: Vec<_> = schematic
+                    .symbols
+                    .iter()
+                    .filter(|s| next_to(en, s))
+                    .map(|s| (s.x, s.y))
+                    .collect();
+                assert_eq!(found.len(), searched.len(), "seed {seed}");
+                searched.retain(|s| !found.contains(s));
+                assert!(searched.is_empty(), "seed {seed}");
+            }
+            for s in &schematic.symbols {
+                let found: Vec<_> = schematic
+                    .numbers_next_to(s, true)
+                    .iter()
+                    .map(|en| (en.x_start, en.y))
+                    .collect();
+                let searched: Vec<_> = schematic
+                    .numbers
+                    .iter()
+                    .filter(|en| next_to(en, s))
+                    .map(|en| (en.x_start, en.y))
+                    .collect();
+                assert_eq!(found, searched, "seed {seed}");
+            }
+        }
     }
     #[test]
-    fn day03_part2() {
-        let input = fs::read_to_string("input/day03").unwrap();
-        assert_eq!(part_2(&input), 75805607);
+    fn gears_by_rule() {
+        let schematic = parse_numbers_symbols(&crate::input::read("example03").unwrap()).unwrap();
+        let gears = schematic.gears(&GearRule::default()).unwrap();
+        assert_eq!(gears.len(), 2);
+        assert_eq!(
+            gears[0],
+            Gear {
+                pos: Point::new(3, 1),
+                symbol: '*',
+                numbers: vec![(Point::new(0, 0), 467), (Point::new(2, 2), 35)],
+                value: 16345,
+            }
+        );
+        assert_eq!(gears[1].value, 755 * 598);
+
+        let rule = GearRule {
+            symbols: vec!['*', '$', '#'],
+            neighbours: 1..=3,
+            aggregation: Aggregation::Max,
+            diagonal: false,
+        };
+        let gears = schematic.gears(&rule).unwrap();
+        let found: Vec<_> = gears.iter().map(|g| (g.symbol, g.value)).collect();
+        assert_eq!(
+            found,
+            [('*', 35), ('#', 633), ('*', 617), ('$', 664), ('*', 598)]
+        );
     }
 }
@@ -1,27 +1,29 @@
-use std::collections::HashSet;
+use crate::{
+    checked::OrOverflow,
+    error::{Overflow, Source},
+    stream, Answer, Error, ParseError, Solution,
+};
+use std::{
+    collections::{HashSet, VecDeque},
+    io::BufRead,
+};
 
-struct Card {
+pub struct Card {
     winning_numbers: HashSet<u32>,
     lotto_numbers: Vec<u32>,
 }
 
 impl Card {
-    fn from_str(line: &str) -> Self {
+    fn from_str(src: &Source, line: &str) -> Result<Self, ParseError> {
         // Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
-        let line = line.split_once(": ").unwrap().1;
-        let (winners, lotto) = line.split_once(" | ").unwrap();
-        let winning_numbers = winners
-            .split_whitespace()
-            .map(|n| n.parse().unwrap())
-            .collect();
-        let lotto_numbers = lotto
-            .split_whitespace()
-            .map(|n| n.parse().unwrap())
-            .collect();
-        Card {
+        let line = src.split_once(line, ": ")?.1;
+        let (winners, lotto) = src.split_once(line, " | ")?;
+        let winning_numbers = src.numbers(winners)?.into_iter().collect();
+        let lotto_numbers = src.numbers(lotto)?;
+        Ok(Card {
             winning_numbers,
             lotto_numbers,
-        }
+        })
     }
 
     fn winners(&self) -> usize {
@@ -30,56 +32,107 @@ impl Card {
             .filter(|l| self.winning_numbers.contains(l))
             .count()
     }
-    
-    fn score(&self) -> u32 {
+
+    fn score(&self) -> Option<u32> {
         let winners = self.winners();
         if winners == 0 {
-            return 0;
+            return Some(0);
         }
-        2_u32.pow(winners as u32 - 1)
+        2_u32.checked_pow(u32::try_from(winners - 1).ok()?)
     }
 }
 
-pub fn part_1(input: &str) -> u32 {
-    let cards: Vec<Card> = input.lines().map(Card::from_str).collect();
-    cards.iter().map(|c| c.score()).sum()
+fn parse_cards(input: &str) -> Result<Vec<Card>, ParseError> {
+    let src = Source::new(4, input);
+    src.lines().map(|line| Card::from_str(&src, line)).collect()
 }
 
-pub fn part_2(input: &str) -> u32 {
-    let cards: Vec<Card> = input.lines().map(Card::from_str).collect();
-    let mut card_count = vec![1; cards.len()];
+fn total_score(cards: &[Card]) -> Result<u32, Overflow> {
+    cards
+        .iter()
+        .try_fold(0_u32, |sum, c| sum.checked_add(c.score()?))
+        .or_overflow(4, "total score")
+}
+
+fn total_cards(cards: &[Card]) -> Result<u32, Overflow> {
+    let mut card_count = vec![1_u32; cards.len()];
+    let mut total: u32 = 0;
     for i in 0..cards.len() {
         let count = card_count[i];
+        total = total
+            .checked_add(count)
+            .or_overflow(4, "total card count")?;
         let winners = cards[i].winners();
         for to_increment in card_count.iter_mut().skip(i + 1).take(winners) {
-            *to_increment += count;
+            *to_increment = to_increment
+                .checked_add(count)
+                .or_overflow(4, "card count")?;
         }
     }
-    card_count.iter().sum()
+    Ok(total)
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::day04::*;
-    use std::fs;
-    #[test]
-    fn example04_part1() {
-        let input = fs::read_to_string("input/example04").unwrap();
-        assert_eq!(part_1(&input), 13);
-    }
-    #[test]
-    fn day04_part1() {
-        let input = fs::read_to_string("input/day04").unwrap();
-        assert_eq!(part_1(&input), 24706);
+pub fn try_part_1(input: &str) -> Result<u32, Error> {
+    Ok(total_score(&parse_cards(input)?)?)
+}
+
+pub fn try_part_2(input: &str) -> Result<u32, Error> {
+    Ok(total_cards(&parse_cards(input)?)?)
+}
+
+/// `try_part_1` on an input read line by line, a card at a time.
+pub fn try_part_1_reader(reader: impl BufRead) -> Result<u32, Error> {
+    stream::sum_lines(4, reader, "total score", |src, line| {
+        Ok(Card::from_str(src, line)?
+            .score()
+            .or_overflow(4, "total score")?)
+    })
+}
+
+/// `try_part_2` on an input read line by line. Only the counts of the cards
+/// the last ones won copies of are kept, `None` once a count overflowed.
+pub fn try_part_2_reader(reader: impl BufRead) -> Result<u32, Error> {
+    let mut card_count: VecDeque<Option<u32>> = VecDeque::new();
+    let mut total: u32 = 0;
+    stream::for_each_line(4, reader, |src, line| {
+        let winners = Card::from_str(src, line)?.winners();
+        let count = card_count
+            .pop_front()
+            .unwrap_or(Some(1))
+            .or_overflow(4, "card count")?;
+        total = total
+            .checked_add(count)
+            .or_overflow(4, "total card count")?;
+        for i in 0..winners {
+            if i == card_count.len() {
+                card_count.push_back(Some(1));
+            }
+            card_count[i] = card_count[i].and_then(|c| c.checked_add(count));
+        }
+        Ok(())
+    })?;
+    Ok(total)
+}
+
+pub fn part_1(input: &str) -> u32 {
+    try_part_1(input).unwrap()
+}
+
+pub fn part_2(input: &str) -> u32 {
+    try_part_2(input).unwrap()
+}
+
+pub struct Day04;
+
+impl Solution for Day04 {
+    type Input = Vec<Card>;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_cards(input)
     }
-    #[test]
-    fn example04_part2() {
-        let input = fs::read_to_string("input/example04").unwrap();
-        assert_eq!(part_2(&input), 30);
+    fn part_1(cards: &Self::Input) -> Result<Answer, Error> {
+        Ok(total_score(cards)?.into())
     }
-    #[test]
-    fn day04_part2() {
-        let input = fs::read_to_string("input/day04").unwrap();
-        assert_eq!(part_2(&input), 13114317);
+    fn part_2(cards: &Self::Input) -> Result<Answer, Error> {
+        Ok(total_cards(cards)?.into())
     }
 }
@@ -1,3 +1,9 @@
+use crate::{
+    checked::OrOverflow,
+    error::{Overflow, Source},
+    Answer, Error, ParseError, Solution,
+};
+
 struct Mapper {
     destination_start: i64,
     source_start: i64,
@@ -5,103 +11,118 @@ struct Mapper {
 }
 
 impl Mapper {
-    fn from_str(line: &str) -> Self {
-        let numbers: Vec<i64> = line
-            .split_whitespace()
-            .map(|s| s.parse().unwrap())
-            .collect();
-        Mapper {
-            destination_start: numbers[0],
-            source_start: numbers[1],
-            len: numbers[2],
-        }
+    fn from_str(src: &Source, line: &str) -> Result<Self, ParseError> {
+        let numbers: Vec<i64> = src.numbers(line)?;
+        let [destination_start, source_start, len] = numbers[..] else {
+            return Err(src.error(line, "three numbers"));
+        };
+        Ok(Mapper {
+            destination_start,
+            source_start,
+            len,
+        })
     }
 
-    fn try_map(&self, source: i64) -> Option<i64> {
-        if source < self.source_start || self.source_start + self.len <= source {
-            return None;
+    fn try_map(&self, source: i64) -> Result<Option<i64>, Overflow> {
+        let end = self
+            .source_start
+            .checked_add(self.len)
+            .or_overflow(5, "end of source range")?;
+        if source < self.source_start || end <= source {
+            return Ok(None);
         }
-        let destination = source - self.source_start + self.destination_start;
-        Some(destination)
+        let destination = source
+            .checked_sub(self.source_start)
+            .and_then(|offset| offset.checked_add(self.destination_start))
+            .or_overflow(5, "destination")?;
+        Ok(Some(destination))
     }
 }
 
-fn parse_block(block: &str) -> Vec<Mapper> {
-    block.lines().skip(1).map(Mapper::from_str).collect()
+fn parse_block(src: &Source, block: &str) -> Result<Vec<Mapper>, ParseError> {
+    let mut lines = block.lines();
+    let header = lines.next().unwrap_or(block);
+    src.strip_suffix(header, " map:")?;
+    lines.map(|line| Mapper::from_str(src, line)).collect()
 }
 
-fn seed_to_location(mut seed: i64, maps: &[Vec<Mapper>]) -> i64 {
+fn seed_to_location(mut seed: i64, maps: &[Vec<Mapper>]) -> Result<i64, Overflow> {
     for map in maps {
         for mapper in map {
-            if let Some(mapped) = mapper.try_map(seed) {
+            if let Some(mapped) = mapper.try_map(seed)? {
                 seed = mapped;
                 break;
             }
         }
     }
-    seed
+    Ok(seed)
+}
+
+pub struct Almanac {
+    seeds: Vec<i64>,
+    maps: Vec<Vec<Mapper>>,
+}
+
+fn parse_almanac(input: &str) -> Result<Almanac, ParseError> {
+    let src = Source::new(5, input);
+    let (seeds_line, rest) = src.split_once(input, "\n\n")?;
+    let seeds: Vec<i64> = src.numbers(src.strip_prefix(seeds_line, "seeds: ")?)?;
+    if seeds.is_empty() {
+        return Err(src.error(src.end_of(seeds_line), "a seed"));
+    }
+    let maps = rest
+        .split("\n\n")
+        .map(|block| parse_block(&src, block))
+        .collect::<Result<_, _>>()?;
+    Ok(Almanac { seeds, maps })
+}
+
+fn min_location(Almanac { seeds, maps }: &Almanac) -> Result<i64, Overflow> {
+    let mut min = i64::MAX;
+    for seed in seeds {
+        min = min.min(seed_to_location(*seed, maps)?);
+    }
+    Ok(min)
+}
+
+fn min_location_of_ranges(Almanac { seeds, maps }: &Almanac) -> Result<i64, Overflow> {
+    let mut min = i64::MAX;
+    for chunk in seeds.chunks_exact(2) {
+        let end = chunk[0]
+            .checked_add(chunk[1])
+            .or_overflow(5, "end of seed range")?;
+        for seed in chunk[0]..end {
+            min = min.min(seed_to_location(seed, maps)?);
+        }
+    }
+    Ok(min)
+}
+
+pub fn try_part_1(input: &str) -> Result<i64, Error> {
+    Ok(min_location(&parse_almanac(input)?)?)
+}
+pub fn try_part_2(input: &str) -> Result<i64, Error> {
+    Ok(min_location_of_ranges(&parse_almanac(input)?)?)
 }
 
 pub fn part_1(input: &str) -> i64 {
-    let (seeds, rest) = input.split_once("\n\n").unwrap();
-    let seeds: Vec<i64> = seeds
-        .strip_prefix("seeds: ")
-        .unwrap()
-        .split_whitespace()
-        .map(|n| n.parse().unwrap())
-        .collect();
-    let maps: Vec<Vec<Mapper>> = rest.split("\n\n").map(parse_block).collect();
-    let min_location = seeds
-        .iter()
-        .map(|s| seed_to_location(*s, &maps))
-        .min()
-        .unwrap();
-    min_location
+    try_part_1(input).unwrap()
 }
 pub fn part_2(input: &str) -> i64 {
-    let (seeds, rest) = input.split_once("\n\n").unwrap();
-    let seeds: Vec<i64> = seeds
-        .strip_prefix("seeds: ")
-        .unwrap()
-        .split_whitespace()
-        .map(|n| n.parse().unwrap())
-        .collect();
-    let maps: Vec<Vec<Mapper>> = rest.split("\n\n").map(parse_block).collect();
-    let min_location = seeds
-        .chunks_exact(2)
-        .map(|chunk| {
-            (chunk[0]..chunk[0] + chunk[1])
-                .map(|s| seed_to_location(s, &maps))
-                .min()
-                .unwrap()
-        })
-        .min()
-        .unwrap();
-    min_location
+    try_part_2(input).unwrap()
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::day05::*;
-    use std::fs;
-    #[test]
-    fn example05_part1() {
-        let input = fs::read_to_string("input/example05").unwrap();
-        assert_eq!(part_1(&input), 35);
-    }
-    #[test]
-    fn day05_part1() {
-        let input = fs::read_to_string("input/day05").unwrap();
-        assert_eq!(part_1(&input), 3374647);
+pub struct Day05;
+
+impl Solution for Day05 {
+    type Input = Almanac;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_almanac(input)
     }
-    #[test]
-    fn example05_part2() {
-        let input = fs::read_to_string("input/example05").unwrap();
-        assert_eq!(part_2(&input), 46);
+    fn part_1(almanac: &Self::Input) -> Result<Answer, Error> {
+        Ok(min_location(almanac)?.into())
     }
-    #[test]
-    fn day05_part2() {
-        let input = fs::read_to_string("input/day05").unwrap();
-        assert_eq!(part_2(&input), 6082852);
+    fn part_2(almanac: &Self::Input) -> Result<Answer, Error> {
+        Ok(min_location_of_ranges(almanac)?.into())
     }
 }
@@ -1,81 +1,250 @@
-fn parse_times_distances(input: &str) -> (Vec<u64>, Vec<u64>) {
-    let (times, distances) = input.split_once('\n').unwrap();
-    let times: Vec<u64> = times
-        .trim_start_matches("Time:")
-        .split_whitespace()
-        .map(|n| n.parse().unwrap())
-        .collect();
-    let distances: Vec<u64> = distances
-        .trim_start_matches("Distance:")
-        .split_whitespace()
-        .map(|n| n.parse().unwrap())
-        .collect();
-
-    (times, distances)
-}
-
-fn ways_to_win(time: u64, distance: u64) -> u64 {
-    (1..time)
-        .map(|wait| {
-            let travel = (time - wait) * wait;
-            u64::from(travel > distance)
-        })
-        .sum()
+use crate::{
+    checked::{CheckedIter, OrOverflow},
+    error::{Overflow, Source},
+    stream, Answer, Error, ParseError, Solution,
+};
+use std::{cell::RefCell, io::BufRead};
+
+fn split_lines<'a>(src: &Source<'a>) -> Result<(&'a str, &'a str), ParseError> {
+    let (times, distances) = src.split_once(src.text(), "\n")?;
+    let times = src.strip_prefix(times, "Time:")?;
+    let distances = src.strip_prefix(distances.trim_end(), "Distance:")?;
+    Ok((times, distances))
 }
 
-pub fn part_1(input: &str) -> u64 {
-    let (times, distances) = parse_times_distances(input);
-    times
+fn parse_times_distances(input: &str) -> Result<(Vec<u64>, Vec<u64>), ParseError> {
+    let src = Source::new(6, input);
+    let (times_line, distances_line) = split_lines(&src)?;
+    let times: Vec<u64> = src.numbers(times_line)?;
+    let distances: Vec<u64> = src.numbers(distances_line)?;
+    check_counts(
+        &src,
+        src.end_of(distances_line),
+        times.len(),
+        distances.len(),
+    )?;
+    Ok((times, distances))
+}
+
+fn check_counts(src: &Source, end: &str, times: usize, distances: usize) -> Result<(), ParseError> {
+    if distances != times {
+        return Err(src.error(end, format!("{times} distances")));
+    }
+    Ok(())
+}
+
+/// Reads the two lines of a race document one at a time, calling `times` with
+/// what follows `Time:` and `distances` with what follows `Distance:` and any
+/// later line, the way `split_lines` splits them. Returns an empty source at
+/// the end of the distances.
+fn read_lines(
+    reader: impl BufRead,
+    mut times: impl FnMut(&Source, &str) -> Result<(), ParseError>,
+    mut distances: impl FnMut(&Source, &str) -> Result<(), ParseError>,
+) -> Result<Source<'static>, Error> {
+    let mut times_line = String::new();
+    let mut end = (0, 0);
+    stream::for_each_line(6, reader, |src, line| {
+        match end.0 {
+            0 => {
+                src.strip_prefix(line, "Time:")?;
+                times_line = format!("{line}\n");
+            }
+            1 => {
+                let rest = src.strip_prefix(line, "Distance:")?;
+                let times_src = Source::at(6, &times_line, 1, 1);
+                times(&times_src, &times_line["Time:".len()..times_line.len() - 1])?;
+                distances(src, rest)?;
+            }
+            _ => distances(src, line)?,
+        }
+        end = (end.0 + 1, line.chars().count() + 1);
+        Ok(())
+    })?;
+    // the input ran out where `split_lines` expects more
+    let (line, expected) = match end.0 {
+        0 => (1, "`\n`"),
+        1 => (2, "`Distance:`"),
+        _ => return Ok(Source::at(6, "\n", end.0, end.1)),
+    };
+    let src = Source::at(6, "", line, 1);
+    Err(src.error(src.text(), expected).into())
+}
+
+fn ways_to_win(time: u64, distance: u64) -> Result<u64, Overflow> {
+    let mut ways: u64 = 0;
+    for wait in 1..time {
+        let travel = (time - wait)
+            .checked_mul(wait)
+            .or_overflow(6, "distance travelled")?;
+        ways = ways
+            .checked_add(u64::from(travel > distance))
+            .or_overflow(6, "ways to win")?;
+    }
+    Ok(ways)
+}
+
+fn product_of_ways(times: &[u64], distances: &[u64]) -> Result<u64, Error> {
+    let ways = times
         .iter()
         .zip(distances.iter())
         .map(|(t, d)| ways_to_win(*t, *d))
-        .product()
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(ways
+        .into_iter()
+        .checked_product()
+        .or_overflow(6, "product of ways to win")?)
 }
 
-fn parse_times_distances_part2(input: &str) -> (u64, u64) {
-    let (times, distances) = input.split_once('\n').unwrap();
-    let time: u64 = times
-        .trim_start_matches("Time:")
-        .split_whitespace()
-        .collect::<String>()
-        .parse()
-        .unwrap();
-    let distance: u64 = distances
-        .trim_start_matches("Distance:")
-        .split_whitespace()
-        .collect::<String>()
+pub fn try_part_1(input: &str) -> Result<u64, Error> {
+    let (times, distances) = parse_times_distances(input)?;
+    product_of_ways(&times, &distances)
+}
+
+/// `try_part_1` on an input read line by line. Only the times are held, as
+/// they are on one line, and each race is settled as its distance is read.
+pub fn try_part_1_reader(reader: impl BufRead) -> Result<u64, Error> {
+    let times = RefCell::new(vec![]);
+    let mut races = 0;
+    // `product_of_ways` fails on any race before it fails on the product
+    let mut race_error = None;
+    let mut product = Some(1u64);
+    let end = read_lines(
+        reader,
+        |src, line| {
+            *times.borrow_mut() = src.numbers(line)?;
+            Ok(())
+        },
+        |src, line| {
+            for distance in src.numbers::<u64>(line)? {
+                if let Some(time) = times.borrow().get(races) {
+                    match ways_to_win(*time, distance) {
+                        Ok(ways) => product = product.and_then(|p| p.checked_mul(ways)),
+                        Err(e) => {
+                            race_error.get_or_insert(e);
+                        }
+                    }
+                }
+                races += 1;
+            }
+            Ok(())
+        },
+    )?;
+    check_counts(&end, &end.text()[..0], times.borrow().len(), races)?;
+    if let Some(e) = race_error {
+        return Err(e.into());
+    }
+    Ok(product.or_overflow(6, "product of ways to win")?)
+}
+
+pub fn part_1(input: &str) -> u64 {
+    try_part_1(input).unwrap()
+}
+
+fn join(src: &Source, line: &str) -> Result<u64, ParseError> {
+    let digits = line.split_whitespace().collect::<String>();
+    digits
         .parse()
-        .unwrap();
-    (time, distance)
+        .map_err(|_| src.error(line.trim_start(), "a number when joined"))
+}
+
+fn parse_times_distances_part2(input: &str) -> Result<(u64, u64), ParseError> {
+    let src = Source::new(6, input);
+    let (times_line, distances_line) = split_lines(&src)?;
+    Ok((join(&src, times_line)?, join(&src, distances_line)?))
+}
+
+/// Adds `token` to the digits of a number being joined, leaving out leading
+/// zeros and keeping only as many characters as it takes for `parse` to fail
+/// on a number too long for a `u64`.
+fn push_joined(joined: &mut String, token: &str) {
+    for ch in token.chars() {
+        if joined == "0" && ch.is_ascii_digit() {
+            joined.clear();
+        }
+        if joined.len() <= u64::MAX.to_string().len() + 1 {
+            joined.push(ch);
+        }
+    }
+}
+
+pub fn try_part_2(input: &str) -> Result<u64, Error> {
+    let (time, distance) = parse_times_distances_part2(input)?;
+    Ok(ways_to_win(time, distance)?)
+}
+
+pub fn try_part_2_reader(reader: impl BufRead) -> Result<u64, Error> {
+    let mut time = 0;
+    let mut joined = String::new();
+    // the error `join` would give, at the first distance
+    let mut error = None;
+    let end = read_lines(
+        reader,
+        |src, line| {
+            time = join(src, line)?;
+            Ok(())
+        },
+        |src, line| {
+            if error.is_none() && !line.trim().is_empty() {
+                error = Some(src.error(line.trim_start(), "a number when joined"));
+            }
+            line.split_whitespace()
+                .for_each(|token| push_joined(&mut joined, token));
+            Ok(())
+        },
+    )?;
+    let distance = joined.parse().map_err(|_| {
+        error.unwrap_or_else(|| end.error(&end.text()[..0], "a number when joined"))
+    })?;
+    Ok(ways_to_win(time, distance)?)
 }
 
 pub fn part_2(input: &str) -> u64 {
-    let (time, distance) = parse_times_distances_part2(input);
-    ways_to_win(time, distance)
+    try_part_2(input).unwrap()
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::day06::*;
-    use std::fs;
-    #[test]
-    fn example06_part1() {
-        let input = fs::read_to_string("input/example06").unwrap();
-        assert_eq!(part_1(&input), 288);
+/// The races of part 1 and the one race of part 2. Numbers may only make
+/// sense to one part, e.g. when they join into one too long, so errors wait
+/// for the part that reads them.
+pub struct Races {
+    races: Result<(Vec<u64>, Vec<u64>), ParseError>,
+    race: Result<(u64, u64), ParseError>,
+}
+
+pub fn parse_races(input: &str) -> Result<Races, ParseError> {
+    let src = Source::new(6, input);
+    split_lines(&src)?;
+    Ok(Races {
+        races: parse_times_distances(input),
+        race: parse_times_distances_part2(input),
+    })
+}
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    type Input = Races;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_races(input)
     }
-    #[test]
-    fn day06_part1() {
-        let input = fs::read_to_string("input/day06").unwrap();
-        assert_eq!(part_1(&input), 440000);
+    fn part_1(races: &Self::Input) -> Result<Answer, Error> {
+        let (times, distances) = races.races.as_ref().map_err(Clone::clone)?;
+        Ok(product_of_ways(times, distances)?.into())
     }
-    #[test]
-    fn example06_part2() {
-        let input = fs::read_to_string("input/example06").unwrap();
-        assert_eq!(part_2(&input), 71503);
+    fn part_2(races: &Self::Input) -> Result<Answer, Error> {
+        let (time, distance) = races.race.clone()?;
+        Ok(ways_to_win(time, distance)?.into())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day06::*;
     #[test]
-    fn day06_part2() {
-        let input = fs::read_to_string("input/day06").unwrap();
-        assert_eq!(part_2(&input), 26187338);
+    fn long_race_overflows() {
+        let input = "Time: 9223372036854775807\nDistance: 1\n";
+        let err = try_part_1(input).unwrap_err();
+        assert!(matches!(err, Error::Overflow(_)));
+        assert_eq!(err.to_string(), "day06: distance travelled overflowed");
     }
 }
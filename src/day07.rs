@@ -1,4 +1,12 @@
-use std::collections::HashMap;
+use crate::{
+    checked::{CheckedIter, OrOverflow},
+    error::{Overflow, Source},
+    stream, Answer, Error, ParseError, Solution,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::BufRead,
+};
 
 const CARDS: [char; 13] = [
     '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A',
@@ -35,38 +43,144 @@ struct Hand {
     cards: Vec<u8>,
 }
 
+fn check_hand_len(src: &Source, s: &str) -> Result<(), ParseError> {
+    match s.chars().count() {
+        5 => Ok(()),
+        _ => Err(src.error(s, "a hand of 5 cards")),
+    }
+}
+
+fn card_value(src: &Source, s: &str, i: usize, c: char, cards: &[char]) -> Result<u8, ParseError> {
+    match cards.iter().position(|card| c == *card) {
+        Some(value) => Ok(value as u8),
+        None => Err(src.error(&s[i..], "a card")),
+    }
+}
+
 impl Hand {
-    fn from_str(s: &str) -> Self {
+    fn from_str(src: &Source, s: &str) -> Result<Self, ParseError> {
+        check_hand_len(src, s)?;
         let mut char_count: HashMap<char, u8> = HashMap::new();
         let cards: Vec<u8> = s
-            .chars()
-            .map(|c| {
+            .char_indices()
+            .map(|(i, c)| {
                 *char_count.entry(c).or_default() += 1;
-                CARDS.iter().position(|card| c == *card).unwrap() as u8
+                card_value(src, s, i, c, &CARDS)
             })
-            .collect();
+            .collect::<Result<_, _>>()?;
         let mut counts: Vec<_> = char_count.values().cloned().collect();
         counts.sort();
         let hand_type = HandType::from_card_counts(&counts);
-        Self { hand_type, cards }
+        Ok(Self { hand_type, cards })
+    }
+}
+
+type HandFromStr = fn(&Source, &str) -> Result<Hand, ParseError>;
+
+fn hand_n_bid(
+    src: &Source,
+    l: &str,
+    hand_from_str: HandFromStr,
+) -> Result<(Hand, u32), ParseError> {
+    let (h, b) = src.split_once(l, " ")?;
+    Ok((hand_from_str(src, h)?, src.parse(b, "a bid")?))
+}
+
+fn parse_hands_n_bids(
+    input: &str,
+    hand_from_str: HandFromStr,
+) -> Result<Vec<(Hand, u32)>, ParseError> {
+    let src = Source::new(7, input);
+    src.lines()
+        .map(|l| hand_n_bid(&src, l, hand_from_str))
+        .collect()
+}
+
+/// Winnings of the bids of hands from the weakest to the strongest.
+fn winnings(bids: impl Iterator<Item = u32>) -> Result<u32, Overflow> {
+    bids.enumerate()
+        .map(|(i, bid)| u32::try_from(i + 1).ok()?.checked_mul(bid))
+        .collect::<Option<Vec<_>>>()
+        .and_then(|w| w.into_iter().checked_sum())
+        .or_overflow(7, "total winnings")
+}
+
+/// `winnings` of the hands counted by bid, which is all the ranks depend on.
+/// Equal hands rank by bid, as they do when sorting the pairs.
+fn winnings_of_counts(counts: &BTreeMap<Hand, BTreeMap<u32, u64>>) -> Result<u32, Overflow> {
+    let mut ranked: u128 = 0;
+    let mut total: u128 = 0;
+    for (bid, n) in counts.values().flatten() {
+        let n = u128::from(*n);
+        // the ranks ranked + 1 ..= ranked + n
+        let ranks = n * ranked + n * (n + 1) / 2;
+        total = total
+            .checked_add(ranks * u128::from(*bid))
+            .filter(|total| *total <= u128::from(u32::MAX))
+            .or_overflow(7, "total winnings")?;
+        ranked += n;
     }
+    u32::try_from(ranked)
+        .ok()
+        .or_overflow(7, "total winnings")?;
+    Ok(total as u32)
+}
+
+/// Winnings of an input read line by line, keeping only a count per hand and
+/// bid. There are few enough hands to count all bids of 0, and each other
+/// bid counted adds a higher rank to the winnings, so past
+/// `MAX_NONZERO_BIDS` of them the total overflows and counting stops.
+fn read_winnings(reader: impl BufRead, hand_from_str: HandFromStr) -> Result<u32, Error> {
+    let mut counts: BTreeMap<Hand, BTreeMap<u32, u64>> = BTreeMap::new();
+    let mut nonzero_bids = 0;
+    stream::for_each_line(7, reader, |src, l| {
+        let (hand, bid) = hand_n_bid(src, l, hand_from_str)?;
+        if nonzero_bids > MAX_NONZERO_BIDS {
+            return Ok(());
+        }
+        let count = counts.entry(hand).or_default().entry(bid).or_default();
+        *count += 1;
+        if *count == 1 && bid != 0 {
+            nonzero_bids += 1;
+            if nonzero_bids > MAX_NONZERO_BIDS {
+                counts.clear();
+            }
+        }
+        Ok(())
+    })?;
+    if nonzero_bids > MAX_NONZERO_BIDS {
+        return Err(Overflow {
+            day: 7,
+            what: "total winnings".to_owned(),
+        }
+        .into());
+    }
+    Ok(winnings_of_counts(&counts)?)
+}
+
+/// The most distinct hand and bid pairs with a bid other than 0 whose
+/// winnings can fit, as `n` of them win at least `1 + 2 + ... + n`.
+const MAX_NONZERO_BIDS: usize = 92_681;
+
+fn sorted_winnings(hands_n_bids: &[(Hand, u32)]) -> Result<u32, Overflow> {
+    let mut sorted: Vec<_> = hands_n_bids.iter().collect();
+    sorted.sort();
+    winnings(sorted.into_iter().map(|(_, bid)| *bid))
+}
+
+pub fn try_part_1(input: &str) -> Result<u32, Error> {
+    Ok(sorted_winnings(&parse_hands_n_bids(
+        input,
+        Hand::from_str,
+    )?)?)
+}
+
+pub fn try_part_1_reader(reader: impl BufRead) -> Result<u32, Error> {
+    read_winnings(reader, Hand::from_str)
 }
 
 pub fn part_1(input: &str) -> u32 {
-    let mut hands_n_bids: Vec<(Hand, u32)> = input
-        .lines()
-        .map(|l| {
-            l.split_once(' ')
-                .map(|(h, b)| (Hand::from_str(h), b.parse().unwrap()))
-                .unwrap()
-        })
-        .collect();
-    hands_n_bids.sort();
-    hands_n_bids
-        .iter()
-        .enumerate()
-        .map(|(i, (_, bid))| (i + 1) as u32 * bid)
-        .sum()
+    try_part_1(input).unwrap()
 }
 
 const CARDS_PART2: [char; 13] = [
@@ -74,15 +188,16 @@ const CARDS_PART2: [char; 13] = [
 ];
 
 impl Hand {
-    fn from_str_part2(s: &str) -> Self {
+    fn from_str_part2(src: &Source, s: &str) -> Result<Self, ParseError> {
+        check_hand_len(src, s)?;
         let mut char_count: HashMap<char, u8> = HashMap::new();
         let cards: Vec<u8> = s
-            .chars()
-            .map(|c| {
+            .char_indices()
+            .map(|(i, c)| {
                 *char_count.entry(c).or_default() += 1;
-                CARDS_PART2.iter().position(|card| c == *card).unwrap() as u8
+                card_value(src, s, i, c, &CARDS_PART2)
             })
-            .collect();
+            .collect::<Result<_, _>>()?;
 
         let mut counts: Vec<_> = char_count
             .iter()
@@ -92,57 +207,57 @@ impl Hand {
 
         let jokers = char_count.get(&'J').cloned().unwrap_or(0);
         if jokers == 5 {
-            return Self {
+            return Ok(Self {
                 hand_type: HandType::FiveOfAKind,
                 cards,
-            };
+            });
         }
         *counts.last_mut().unwrap() += jokers;
 
         let hand_type = HandType::from_card_counts(&counts);
-        Self { hand_type, cards }
+        Ok(Self { hand_type, cards })
     }
 }
 
+pub fn try_part_2(input: &str) -> Result<u32, Error> {
+    Ok(sorted_winnings(&parse_hands_n_bids(
+        input,
+        Hand::from_str_part2,
+    )?)?)
+}
+
+pub fn try_part_2_reader(reader: impl BufRead) -> Result<u32, Error> {
+    read_winnings(reader, Hand::from_str_part2)
+}
+
 pub fn part_2(input: &str) -> u32 {
-    let mut hands_n_bids: Vec<(Hand, u32)> = input
-        .lines()
-        .map(|l| {
-            l.split_once(' ')
-                .map(|(h, b)| (Hand::from_str_part2(h), b.parse().unwrap()))
-                .unwrap()
-        })
-        .collect();
-    hands_n_bids.sort();
-    hands_n_bids
-        .iter()
-        .enumerate()
-        .map(|(i, (_, bid))| (i + 1) as u32 * bid)
-        .sum()
-}
-
-#[cfg(test)]
-mod tests {
-    use crate::day07::*;
-    use std::fs;
-    #[test]
-    fn example07_part1() {
-        let input = fs::read_to_string("input/example07").unwrap();
-        assert_eq!(part_1(&input), 6440);
-    }
-    #[test]
-    fn day07_part1() {
-        let input = fs::read_to_string("input/day07").unwrap();
-        assert_eq!(part_1(&input), 251216224);
+    try_part_2(input).unwrap()
+}
+
+/// The hands and bids, with jacks as part 1 reads them and as jokers.
+pub struct Hands {
+    jacks: Vec<(Hand, u32)>,
+    jokers: Vec<(Hand, u32)>,
+}
+
+pub fn parse_hands(input: &str) -> Result<Hands, ParseError> {
+    Ok(Hands {
+        jacks: parse_hands_n_bids(input, Hand::from_str)?,
+        jokers: parse_hands_n_bids(input, Hand::from_str_part2)?,
+    })
+}
+
+pub struct Day07;
+
+impl Solution for Day07 {
+    type Input = Hands;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_hands(input)
     }
-    #[test]
-    fn example07_part2() {
-        let input = fs::read_to_string("input/example07").unwrap();
-        assert_eq!(part_2(&input), 5905);
+    fn part_1(hands: &Self::Input) -> Result<Answer, Error> {
+        Ok(sorted_winnings(&hands.jacks)?.into())
     }
-    #[test]
-    fn day07_part2() {
-        let input = fs::read_to_string("input/day07").unwrap();
-        assert_eq!(part_2(&input), 250825971);
+    fn part_2(hands: &Self::Input) -> Result<Answer, Error> {
+        Ok(sorted_winnings(&hands.jokers)?.into())
     }
 }
@@ -1,129 +1,275 @@
+use crate::{
+    checked::OrOverflow,
+    error::{NoAnswer, Source},
+    Answer, Error, ParseError, Solution,
+};
+use num::Integer;
 use std::collections::HashMap;
 
-type Map<'a> = HashMap<&'a str, (&'a str, &'a str)>;
-fn parse_map<'a>(s: &'a str) -> Map<'a> {
-    let parse_line = |l: &'a str| {
+/// The instructions and the network they lead through, with nodes numbered
+/// in the order of the lines that name them.
+pub struct Network {
+    instructions: Vec<char>,
+    names: Vec<String>,
+    /// The left and right node of each node.
+    next: Vec<(usize, usize)>,
+}
+
+impl Network {
+    fn node(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    fn step(&self, node: usize, instruction: char) -> usize {
+        let (l, r) = self.next[node];
+        if instruction == 'L' {
+            l
+        } else {
+            r
+        }
+    }
+}
+
+pub fn parse_network(input: &str) -> Result<Network, ParseError> {
+    let src = Source::new(8, input);
+    let (instructions, map) = src.split_once(input, "\n\n")?;
+    if instructions.is_empty() {
+        return Err(src.error(instructions, "`L` or `R`"));
+    }
+    if let Some(i) = instructions.find(|ch| ch != 'L' && ch != 'R') {
+        return Err(src.error(&instructions[i..], "`L` or `R`"));
+    }
+    let parse_line = |l| {
         // AAA = (BBB, BBB)
-        let (start, left_right) = l.split_once(" = ").unwrap();
-        let (left, right) = left_right
-            .trim_start_matches('(')
-            .trim_end_matches(')')
-            .split_once(", ")
-            .unwrap();
-        (start, (left, right))
+        let (start, left_right) = src.split_once(l, " = ")?;
+        let left_right = src.strip_prefix(left_right, "(")?;
+        let left_right = src.strip_suffix(left_right, ")")?;
+        let (left, right) = src.split_once(left_right, ", ")?;
+        Ok((start, (left, right)))
     };
-    s.lines().map(parse_line).collect()
+    let nodes: Vec<_> = map.lines().map(parse_line).collect::<Result<_, _>>()?;
+    let index: HashMap<&str, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _))| (*name, i))
+        .collect();
+    let node = |name: &str| {
+        index
+            .get(name)
+            .copied()
+            .ok_or_else(|| src.error(name, "a node of the network"))
+    };
+    let next = nodes
+        .iter()
+        .map(|(_, (left, right))| Ok((node(left)?, node(right)?)))
+        .collect::<Result<_, ParseError>>()?;
+    Ok(Network {
+        instructions: instructions.chars().collect(),
+        names: nodes.iter().map(|(name, _)| name.to_string()).collect(),
+        next,
+    })
 }
 
-fn calc_steps(
-    instructions: Vec<char>,
-    map: HashMap<&str, (&str, &str)>,
-    start: &str,
-    goal: &str,
-) -> u32 {
+/// Steps from `start` until `done`, following the instructions over and over.
+/// After as many steps as there are nodes times instructions, every state has
+/// been seen, so `done` never will be.
+fn steps_until(network: &Network, start: usize, done: impl Fn(usize) -> bool) -> Option<usize> {
+    let states = network
+        .names
+        .len()
+        .saturating_mul(network.instructions.len());
     let mut pos = start;
-    for (steps, ins) in instructions.iter().cycle().enumerate() {
-        if pos == goal {
-            return steps as u32;
+    for (steps, ins) in network.instructions.iter().cycle().enumerate().take(states) {
+        if done(pos) {
+            return Some(steps);
         }
-        let (l, r) = map.get(pos).unwrap();
-        pos = if *ins == 'L' { l } else { r };
+        pos = network.step(pos, *ins);
     }
-    unreachable!()
+    None
+}
+
+fn calc_steps(network: &Network) -> Result<u32, Error> {
+    let (Some(start), Some(goal)) = (network.node("AAA"), network.node("ZZZ")) else {
+        return Err(NoAnswer {
+            day: 8,
+            why: "there are no nodes `AAA` and `ZZZ`".to_owned(),
+        }
+        .into());
+    };
+    let steps = steps_until(network, start, |pos| pos == goal).ok_or_else(|| NoAnswer {
+        day: 8,
+        why: "`ZZZ` cannot be reached from `AAA`".to_owned(),
+    })?;
+    Ok(u32::try_from(steps)
+        .ok()
+        .or_overflow(8, "number of steps")?)
+}
+
+pub fn try_part_1(input: &str) -> Result<u32, Error> {
+    calc_steps(&parse_network(input)?)
 }
 
 pub fn part_1(input: &str) -> u32 {
-    let (instructions, map): (Vec<char>, Map) = input
-        .split_once("\n\n")
-        .map(|(i, m)| (i.chars().collect(), parse_map(m)))
-        .unwrap();
-    calc_steps(instructions, map, "AAA", "ZZZ")
+    try_part_1(input).unwrap()
 }
 
-fn calc_steps_part2(
-    instructions: Vec<char>,
-    map: HashMap<&str, (&str, &str)>,
-    starts: &[&str],
-) -> usize {
-    let mut positions: Vec<&str> = starts.to_owned();
-    for (steps, ins) in instructions.iter().cycle().enumerate() {
-        if positions.iter().all(|p| p.ends_with('Z')) {
-            return steps;
+fn ghost_starts(network: &Network) -> Result<Vec<usize>, NoAnswer> {
+    let starts: Vec<usize> = (0..network.names.len())
+        .filter(|node| network.names[*node].ends_with('A'))
+        .collect();
+    if starts.is_empty() {
+        return Err(NoAnswer {
+            day: 8,
+            why: "there is no node ending in `A`".to_owned(),
+        });
+    }
+    Ok(starts)
+}
+
+/// The steps of each ghost until it first reaches a node ending in `Z`.
+fn ghost_periods(network: &Network, starts: &[usize]) -> Result<Vec<usize>, NoAnswer> {
+    starts
+        .iter()
+        .map(|start| {
+            steps_until(network, *start, |pos| network.names[pos].ends_with('Z')).ok_or_else(|| {
+                NoAnswer {
+                    day: 8,
+                    why: format!(
+                        "the ghost starting at `{}` never reaches a node ending in `Z`",
+                        network.names[*start]
+                    ),
+                }
+            })
+        })
+        .collect()
+}
+
+/// The steps before the ghost at `start` first comes back to a node at the
+/// same instruction, and how many steps it takes to come back.
+fn ghost_cycle(network: &Network, start: usize) -> (usize, usize) {
+    let mut seen = HashMap::new();
+    let mut pos = start;
+    let mut steps = 0;
+    loop {
+        let i = steps % network.instructions.len();
+        if let Some(first) = seen.insert((pos, i), steps) {
+            return (first, steps - first);
+        }
+        pos = network.step(pos, network.instructions[i]);
+        steps += 1;
+    }
+}
+
+fn calc_steps_part2(network: &Network) -> Result<usize, NoAnswer> {
+    let starts = ghost_starts(network)?;
+    ghost_periods(network, &starts)?;
+    // once every ghost goes around its cycle, they are all back where they
+    // were after the least common multiple of the cycles
+    let cycles: Vec<_> = starts.iter().map(|s| ghost_cycle(network, *s)).collect();
+    let states = cycles
+        .iter()
+        .try_fold(1, |acc: usize, (_, len)| {
+            (acc / acc.gcd(len)).checked_mul(*len)
+        })
+        .and_then(|lcm| lcm.checked_add(cycles.iter().map(|c| c.0).max()?))
+        .unwrap_or(usize::MAX);
+    let at_end: Vec<bool> = network.names.iter().map(|n| n.ends_with('Z')).collect();
+    let mut positions = starts;
+    for (steps, ins) in network.instructions.iter().cycle().enumerate().take(states) {
+        if positions.iter().all(|p| at_end[*p]) {
+            return Ok(steps);
         }
         for pos in positions.iter_mut() {
-            let (l, r) = map.get(pos).unwrap();
-            *pos = if *ins == 'L' { l } else { r };
+            *pos = network.step(*pos, *ins);
         }
     }
-    unreachable!()
+    Err(NoAnswer {
+        day: 8,
+        why: "the ghosts are never all on nodes ending in `Z` at once".to_owned(),
+    })
 }
 
-fn calc_steps_part2_fast(
-    instructions: Vec<char>,
-    map: HashMap<&str, (&str, &str)>,
-    starts: &[&str],
-) -> usize {
+fn calc_steps_part2_fast(network: &Network) -> Result<usize, Error> {
     // the ghosts travel in circles, need to find each period and then calculate the shared LCM.
-    let mut periods: Vec<_> = vec![];
-    for start in starts {
-        let mut pos = start;
-        for (steps, ins) in instructions.iter().cycle().enumerate() {
-            if pos.ends_with('Z') {
-                periods.push(steps);
-                break;
-            }
-            let (l, r) = map.get(pos).unwrap();
-            pos = if *ins == 'L' { l } else { r };
-        }
-    }
-    periods.iter().cloned().reduce(num::integer::lcm).unwrap()
+    let periods = ghost_periods(network, &ghost_starts(network)?)?;
+    Ok(periods.into_iter().try_fold(1, |acc: usize, period| {
+        (acc / acc.gcd(&period))
+            .checked_mul(period)
+            .or_overflow(8, "least common multiple of periods")
+    })?)
+}
+
+pub fn try_part_2(input: &str) -> Result<usize, Error> {
+    Ok(calc_steps_part2(&parse_network(input)?)?)
+}
+
+pub fn try_part_2_fast(input: &str) -> Result<usize, Error> {
+    calc_steps_part2_fast(&parse_network(input)?)
 }
 
 pub fn part_2(input: &str) -> usize {
-    let (instructions, map): (Vec<char>, Map) = input
-        .split_once("\n\n")
-        .map(|(i, m)| (i.chars().collect(), parse_map(m)))
-        .unwrap();
-    let starts: Vec<&str> = map.keys().filter(|k| k.ends_with('A')).cloned().collect();
-    calc_steps_part2(instructions, map, &starts)
+    try_part_2(input).unwrap()
 }
 
 pub fn part_2_fast(input: &str) -> usize {
-    let (instructions, map): (Vec<char>, Map) = input
-        .split_once("\n\n")
-        .map(|(i, m)| (i.chars().collect(), parse_map(m)))
-        .unwrap();
-    let starts: Vec<&str> = map.keys().filter(|k| k.ends_with('A')).cloned().collect();
-    calc_steps_part2_fast(instructions, map, &starts)
+    try_part_2_fast(input).unwrap()
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::day08::*;
-    use std::fs;
-    #[test]
-    fn example08_part1() {
-        let input = fs::read_to_string("input/example08").unwrap();
-        assert_eq!(part_1(&input), 2);
+pub struct Day08;
+
+impl Solution for Day08 {
+    type Input = Network;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_network(input)
     }
-    #[test]
-    fn example08_2_part1() {
-        let input = fs::read_to_string("input/example08_2").unwrap();
-        assert_eq!(part_1(&input), 6);
+    fn part_1(network: &Self::Input) -> Result<Answer, Error> {
+        Ok(calc_steps(network)?.into())
     }
-    #[test]
-    fn day08_part1() {
-        let input = fs::read_to_string("input/day08").unwrap();
-        assert_eq!(part_1(&input), 13939);
+    fn part_2(network: &Self::Input) -> Result<Answer, Error> {
+        Ok(calc_steps_part2(network)?.into())
+    }
+}
+
+/// `Day08` with ghosts that go around in circles, as in the puzzle input.
+pub struct Day08Fast;
+
+impl Solution for Day08Fast {
+    type Input = Network;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        Day08::parse(input)
+    }
+    fn part_1(network: &Self::Input) -> Result<Answer, Error> {
+        Day08::part_1(network)
     }
+    fn part_2(network: &Self::Input) -> Result<Answer, Error> {
+        Ok(calc_steps_part2_fast(network)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day08::*;
     #[test]
-    fn example08_part2() {
-        let input = fs::read_to_string("input/example08_part2").unwrap();
-        assert_eq!(part_2(&input), 6);
+    fn goal_out_of_reach() {
+        let input = "LR\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)\n";
+        assert_eq!(
+            try_part_1(input).unwrap_err().to_string(),
+            "day08: no answer, `ZZZ` cannot be reached from `AAA`"
+        );
+        let why =
+            "day08: no answer, the ghost starting at `AAA` never reaches a node ending in `Z`";
+        assert_eq!(try_part_2(input).unwrap_err().to_string(), why);
+        assert_eq!(try_part_2_fast(input).unwrap_err().to_string(), why);
     }
     #[test]
-    fn day08_part2() {
-        let input = fs::read_to_string("input/day08").unwrap();
-        assert_eq!(part_2_fast(&input), 8906539031197);
+    fn ghosts_out_of_step() {
+        // one ghost is on `Z` after odd steps and the other after even ones
+        let input = "L\n\n\
+            11A = (11Z, 11Z)\n11Z = (11A, 11A)\n\
+            22A = (22B, 22B)\n22B = (22Z, 22Z)\n22Z = (22B, 22B)\n";
+        assert_eq!(
+            try_part_2(input).unwrap_err().to_string(),
+            "day08: no answer, the ghosts are never all on nodes ending in `Z` at once"
+        );
     }
 }
@@ -1,63 +1,93 @@
+use crate::{
+    checked::{CheckedIter, OrOverflow},
+    error::{Overflow, Source},
+    stream, Answer, Error, ParseError, Solution,
+};
+use std::io::BufRead;
+
 #[derive(Clone, Copy)]
 enum PredictDirection {
     Forward,
     Backward,
 }
-fn predict(vals: &[i32], predict_dir: PredictDirection) -> i32 {
+/// `None` if a difference or the prediction overflows.
+fn predict(vals: &[i32], predict_dir: PredictDirection) -> Option<i32> {
     if vals.iter().all(|v| *v == 0) {
-        return 0;
+        return Some(0);
+    }
+    let diffs = vals
+        .windows(2)
+        .map(|w| w[1].checked_sub(w[0]))
+        .collect::<Option<Vec<_>>>()?;
+    let predicted_diff = predict(&diffs, predict_dir)?;
+    match predict_dir {
+        PredictDirection::Forward => vals.last().unwrap().checked_add(predicted_diff),
+        PredictDirection::Backward => vals.first().unwrap().checked_sub(predicted_diff),
     }
-    let diffs: Vec<_> = vals.windows(2).map(|w| w[1] - w[0]).collect();
-    let predicted_diff = predict(&diffs, predict_dir);
-    let predicted = match predict_dir {
-        PredictDirection::Forward => vals.last().unwrap() + predicted_diff,
-        PredictDirection::Backward => vals.first().unwrap() - predicted_diff,
-    };
-    predicted
+}
+
+fn parse_histories(input: &str) -> Result<Vec<Vec<i32>>, ParseError> {
+    let src = Source::new(9, input);
+    src.lines().map(|l| src.numbers(l)).collect()
+}
+
+fn sum_predictions(histories: &[Vec<i32>], predict_dir: PredictDirection) -> Result<i32, Overflow> {
+    histories
+        .iter()
+        .map(|vals| predict(vals, predict_dir))
+        .collect::<Option<Vec<_>>>()
+        .and_then(|predictions| predictions.into_iter().checked_sum())
+        .or_overflow(9, "sum of predictions")
+}
+
+pub fn try_part_1(input: &str) -> Result<i32, Error> {
+    Ok(sum_predictions(
+        &parse_histories(input)?,
+        PredictDirection::Forward,
+    )?)
+}
+
+pub fn try_part_2(input: &str) -> Result<i32, Error> {
+    Ok(sum_predictions(
+        &parse_histories(input)?,
+        PredictDirection::Backward,
+    )?)
+}
+
+/// The sum of predictions of an input read line by line, a history at a time.
+fn read_predictions(reader: impl BufRead, predict_dir: PredictDirection) -> Result<i32, Error> {
+    stream::sum_lines(9, reader, "sum of predictions", |src, l| {
+        Ok(predict(&src.numbers(l)?, predict_dir).or_overflow(9, "sum of predictions")?)
+    })
+}
+
+pub fn try_part_1_reader(reader: impl BufRead) -> Result<i32, Error> {
+    read_predictions(reader, PredictDirection::Forward)
+}
+
+pub fn try_part_2_reader(reader: impl BufRead) -> Result<i32, Error> {
+    read_predictions(reader, PredictDirection::Backward)
 }
 
 pub fn part_1(input: &str) -> i32 {
-    input
-        .lines()
-        .map(|l| {
-            let vals: Vec<_> = l.split_whitespace().map(|n| n.parse().unwrap()).collect();
-            predict(&vals, PredictDirection::Forward)
-        })
-        .sum()
+    try_part_1(input).unwrap()
 }
 
 pub fn part_2(input: &str) -> i32 {
-    input
-        .lines()
-        .map(|l| {
-            let vals: Vec<_> = l.split_whitespace().map(|n| n.parse().unwrap()).collect();
-            predict(&vals, PredictDirection::Backward)
-        })
-        .sum()
-}
-
-#[cfg(test)]
-mod tests {
-    use crate::day09::*;
-    use std::fs;
-    #[test]
-    fn example09_part1() {
-        let input = fs::read_to_string("input/example09").unwrap();
-        assert_eq!(part_1(&input), 114);
-    }
-    #[test]
-    fn day09_part1() {
-        let input = fs::read_to_string("input/day09").unwrap();
-        assert_eq!(part_1(&input), 1702218515);
+    try_part_2(input).unwrap()
+}
+
+pub struct Day09;
+
+impl Solution for Day09 {
+    type Input = Vec<Vec<i32>>;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_histories(input)
     }
-    #[test]
-    fn example09_part2() {
-        let input = fs::read_to_string("input/example09").unwrap();
-        assert_eq!(part_2(&input), 2);
+    fn part_1(histories: &Self::Input) -> Result<Answer, Error> {
+        Ok(sum_predictions(histories, PredictDirection::Forward)?.into())
     }
-    #[test]
-    fn day09_part2() {
-        let input = fs::read_to_string("input/day09").unwrap();
-        assert_eq!(part_2(&input), 925);
+    fn part_2(histories: &Self::Input) -> Result<Answer, Error> {
+        Ok(sum_predictions(histories, PredictDirection::Backward)?.into())
     }
 }
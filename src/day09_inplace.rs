@@ -1,3 +1,8 @@
+use crate::{
+    checked::{CheckedIter, OrOverflow},
+    error::{Overflow, Source},
+    Answer, Error, ParseError, Solution,
+};
 use std::cell::Cell;
 
 #[derive(Clone, Copy)]
@@ -5,73 +10,88 @@ enum PredictDirection {
     Forward,
     Backward,
 }
-fn predict_inplace(vals: &mut [i32], predict_dir: PredictDirection) -> i32 {
+/// `None` if a difference or the prediction overflows.
+fn predict_inplace(vals: &mut [i32], predict_dir: PredictDirection) -> Option<i32> {
     if vals.iter().all(|v| *v == 0) {
-        return 0;
+        return Some(0);
     }
     let first_or_last = match predict_dir {
         PredictDirection::Forward => *vals.last().unwrap(),
         PredictDirection::Backward => *vals.first().unwrap(),
     };
     // Rust doesn't like windows with mutability, need to use this workaround.
-    Cell::from_mut(vals)
-        .as_slice_of_cells()
-        .windows(2)
-        .for_each(|w| {
-            let diff = w[1].get() - w[0].get();
-            w[0].set(diff);
-        });
+    for w in Cell::from_mut(vals).as_slice_of_cells().windows(2) {
+        let diff = w[1].get().checked_sub(w[0].get())?;
+        w[0].set(diff);
+    }
     let len = vals.len();
-    let predicted_diff = predict_inplace(&mut vals[..len - 1], predict_dir);
+    let predicted_diff = predict_inplace(&mut vals[..len - 1], predict_dir)?;
 
     match predict_dir {
-        PredictDirection::Forward => first_or_last + predicted_diff,
-        PredictDirection::Backward => first_or_last - predicted_diff,
+        PredictDirection::Forward => first_or_last.checked_add(predicted_diff),
+        PredictDirection::Backward => first_or_last.checked_sub(predicted_diff),
     }
 }
 
-pub fn part_1(input: &str) -> i32 {
-    input
+fn parse_histories(input: &str) -> Result<Vec<Vec<i32>>, ParseError> {
+    let src = Source::new(9, input);
+    src.lines().map(|l| src.numbers(l)).collect()
+}
+
+fn sum_predictions(histories: &[Vec<i32>], predict_dir: PredictDirection) -> Result<i32, Overflow> {
+    histories
+        .iter()
+        .map(|vals| predict_inplace(&mut vals.clone(), predict_dir))
+        .collect::<Option<Vec<_>>>()
+        .and_then(|predictions| predictions.into_iter().checked_sum())
+        .or_overflow(9, "sum of predictions")
+}
+
+pub fn try_part_1(input: &str) -> Result<i32, Error> {
+    let src = Source::new(9, input);
+    Ok(src
         .lines()
         .map(|l| {
-            let mut vals: Vec<_> = l.split_whitespace().map(|n| n.parse().unwrap()).collect();
-            predict_inplace(&mut vals, PredictDirection::Forward)
+            let mut vals: Vec<_> = src.numbers(l)?;
+            Ok(predict_inplace(&mut vals, PredictDirection::Forward))
         })
-        .sum()
+        .collect::<Result<Option<Vec<_>>, ParseError>>()?
+        .and_then(|predictions| predictions.into_iter().checked_sum())
+        .or_overflow(9, "sum of predictions")?)
 }
 
-pub fn part_2(input: &str) -> i32 {
-    input
+pub fn try_part_2(input: &str) -> Result<i32, Error> {
+    let src = Source::new(9, input);
+    Ok(src
         .lines()
         .map(|l| {
-            let mut vals: Vec<_> = l.split_whitespace().map(|n| n.parse().unwrap()).collect();
-            predict_inplace(&mut vals, PredictDirection::Backward)
+            let mut vals: Vec<_> = src.numbers(l)?;
+            Ok(predict_inplace(&mut vals, PredictDirection::Backward))
         })
-        .sum()
+        .collect::<Result<Option<Vec<_>>, ParseError>>()?
+        .and_then(|predictions| predictions.into_iter().checked_sum())
+        .or_overflow(9, "sum of predictions")?)
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::day09::*;
-    use std::fs;
-    #[test]
-    fn example09_part1_inplace() {
-        let input = fs::read_to_string("input/example09").unwrap();
-        assert_eq!(part_1(&input), 114);
-    }
-    #[test]
-    fn day09_part1_inplace() {
-        let input = fs::read_to_string("input/day09").unwrap();
-        assert_eq!(part_1(&input), 1702218515);
+pub fn part_1(input: &str) -> i32 {
+    try_part_1(input).unwrap()
+}
+
+pub fn part_2(input: &str) -> i32 {
+    try_part_2(input).unwrap()
+}
+
+pub struct Day09Inplace;
+
+impl Solution for Day09Inplace {
+    type Input = Vec<Vec<i32>>;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_histories(input)
     }
-    #[test]
-    fn example09_part2_inplace() {
-        let input = fs::read_to_string("input/example09").unwrap();
-        assert_eq!(part_2(&input), 2);
+    fn part_1(histories: &Self::Input) -> Result<Answer, Error> {
+        Ok(sum_predictions(histories, PredictDirection::Forward)?.into())
     }
-    #[test]
-    fn day09_part2_inplace() {
-        let input = fs::read_to_string("input/day09").unwrap();
-        assert_eq!(part_2(&input), 925);
+    fn part_2(histories: &Self::Input) -> Result<Answer, Error> {
+        Ok(sum_predictions(histories, PredictDirection::Backward)?.into())
     }
 }
@@ -1,48 +1,52 @@
-use std::collections::{HashMap, HashSet, VecDeque};
-
-type Pos = (i32, i32);
-type Map = HashMap<Pos, char>;
-fn parse_map_n_start(input: &str) -> (Map, Pos) {
-    let mut start = None;
-    let mut map = HashMap::new();
-    for (row, line) in input.lines().enumerate() {
-        for (col, ch) in line.chars().enumerate() {
-            let pos = (col as i32, row as i32);
-            if ch == 'S' {
-                start = Some(pos);
-            }
-            map.insert(pos, ch);
-        }
+use crate::{
+    checked::OrOverflow,
+    error::{NoAnswer, Overflow, Source},
+    geometry::{Dir, Point},
+    grid::{Grid, Pos},
+    Answer, Error, ParseError, Solution,
+};
+use std::collections::{HashMap, VecDeque};
+
+type Map = Grid<char>;
+fn parse_map_n_start(input: &str) -> Result<(Map, Pos), ParseError> {
+    let src = Source::new(10, input);
+    let map = Grid::parse(&src, input, "a pipe, `.` or `S`", |ch| {
+        matches!(ch, 'S' | '.' | '|' | '-' | 'L' | 'J' | '7' | 'F').then_some(ch)
+    })?;
+    let mut starts = input.match_indices('S').map(|(i, _)| &input[i..]);
+    if starts.next().is_none() {
+        return Err(src.error(src.end_of(input), "a start tile `S`"));
+    }
+    if let Some(second) = starts.next() {
+        return Err(src.error(second, "a single start tile `S`"));
     }
-    (map, start.unwrap())
+    let start = map
+        .iter()
+        .find_map(|(pos, ch)| (*ch == 'S').then_some(pos))
+        .unwrap();
+    Ok((map, start))
 }
 
 fn get_neighbors(pos: Pos, map: &Map) -> Vec<Pos> {
-    let (x, y) = pos;
-    match map[&pos] {
-        '.' => vec![],
-        '|' => [(x, y + 1), (x, y - 1)].into_iter().collect(),
-        '-' => [(x - 1, y), (x + 1, y)].into_iter().collect(),
-        'L' => [(x + 1, y), (x, y - 1)].into_iter().collect(),
-        'J' => [(x - 1, y), (x, y - 1)].into_iter().collect(),
-        '7' => [(x - 1, y), (x, y + 1)].into_iter().collect(),
-        'F' => [(x + 1, y), (x, y + 1)].into_iter().collect(),
+    let dirs: &[Dir] = match map[pos] {
+        '.' => &[],
+        '|' => &[Dir::Down, Dir::Up],
+        '-' => &[Dir::Left, Dir::Right],
+        'L' => &[Dir::Right, Dir::Up],
+        'J' => &[Dir::Left, Dir::Up],
+        '7' => &[Dir::Left, Dir::Down],
+        'F' => &[Dir::Right, Dir::Down],
         bad => panic!("bad:{bad}"),
-    }
+    };
+    dirs.iter().filter_map(|dir| map.step(pos, *dir)).collect()
 }
 
 fn travel_the_loop(start: Pos, map: &Map) -> HashMap<Pos, usize> {
     let mut visited: HashMap<Pos, usize> = HashMap::new();
     visited.insert(start, 0);
     let mut to_visit = VecDeque::new();
-    let start_neighbors = [
-        (start.0 + 1, start.1),
-        (start.0 - 1, start.1),
-        (start.0, start.1 + 1),
-        (start.0, start.1 - 1),
-    ];
-    for sn in start_neighbors {
-        if map.contains_key(&sn) && get_neighbors(sn, map).contains(&start) {
+    for sn in map.neighbours(start) {
+        if get_neighbors(sn, map).contains(&start) {
             to_visit.push_back((sn, 1));
         }
     }
@@ -58,101 +62,125 @@ fn travel_the_loop(start: Pos, map: &Map) -> HashMap<Pos, usize> {
     visited
 }
 
+fn farthest_loop_tile(map: &Map, start: Pos) -> usize {
+    *travel_the_loop(start, map).values().max().unwrap()
+}
+
+pub fn try_part_1(input: &str) -> Result<usize, ParseError> {
+    let (map, start): (Map, Pos) = parse_map_n_start(input)?;
+    Ok(farthest_loop_tile(&map, start))
+}
+
 pub fn part_1(input: &str) -> usize {
-    let (map, start): (Map, Pos) = parse_map_n_start(input);
-    *travel_the_loop(start, &map).values().max().unwrap()
+    try_part_1(input).unwrap()
 }
 
-fn is_inside(moat: &[Pos], o: Pos) -> bool {
+/// `p - o` with signed coordinates.
+fn offset(p: Pos, o: Pos) -> Option<Point<i64>> {
+    let diff = |a: usize, b: usize| i64::try_from(a).ok()?.checked_sub(i64::try_from(b).ok()?);
+    Some(Point::new(diff(p.x, o.x)?, diff(p.y, o.y)?))
+}
+
+fn is_inside(moat: &[Pos], o: Pos) -> Result<bool, Overflow> {
     let mut winding = 0;
     let len = moat.len();
     for i in 0..len {
-        let p = moat[i];
-        let q = moat[(i + 1) % len];
-        if p == o {
-            return false;
+        if moat[i] == o {
+            return Ok(false);
         }
-        let delta = (p.0 - o.0) * (q.1 - o.1) - (p.1 - o.1) * (q.0 - o.0);
-        if p.0 <= o.0 && o.0 < q.0 && delta > 0 {
+        let (p, q, delta) = offset(moat[i], o)
+            .zip(offset(moat[(i + 1) % len], o))
+            .and_then(|(p, q)| {
+                let delta = p.x.checked_mul(q.y)?.checked_sub(p.y.checked_mul(q.x)?)?;
+                Some((p, q, delta))
+            })
+            .or_overflow(10, "winding number")?;
+        if p.x <= 0 && 0 < q.x && delta > 0 {
             winding += 1;
-        } else if q.0 <= o.0 && o.0 < p.0 && delta < 0 {
+        } else if q.x <= 0 && 0 < p.x && delta < 0 {
             winding -= 1;
         }
     }
-    winding != 0
+    Ok(winding != 0)
 }
 
-fn count_enclosed_tiles(map: &Map, loop_tiles: &[Pos]) -> usize {
-    map.keys().filter(|&&p| is_inside(loop_tiles, p)).count()
+fn count_enclosed_tiles(map: &Map, loop_tiles: &[Pos]) -> Result<usize, Overflow> {
+    let mut count: usize = 0;
+    for p in map.positions() {
+        count = count
+            .checked_add(usize::from(is_inside(loop_tiles, p)?))
+            .or_overflow(10, "enclosed tiles")?;
+    }
+    Ok(count)
 }
 
-fn travel_the_loop_part2(start: Pos, map: &Map) -> Vec<Pos> {
+/// The tiles of the loop through `start`, if the pipes there make one.
+fn travel_the_loop_part2(start: Pos, map: &Map) -> Option<Vec<Pos>> {
     let mut visited = vec![];
     visited.push(start);
-    let start_neighbors = [
-        (start.0 + 1, start.1),
-        (start.0 - 1, start.1),
-        (start.0, start.1 + 1),
-        (start.0, start.1 - 1),
-    ];
-    let mut pos = start_neighbors
-        .iter()
-        .find(|&&sn| map.contains_key(&sn) && get_neighbors(sn, map).contains(&start))
-        .cloned()
-        .unwrap();
+    let mut pos = map
+        .neighbours(start)
+        .find(|&sn| get_neighbors(sn, map).contains(&start))?;
     let mut prev = start;
     while pos != start {
         visited.push(pos);
-        let next = get_neighbors(pos, map)
-            .iter()
-            .find(|&&nei| nei != prev)
-            .cloned()
-            .unwrap();
+        let neighbours = get_neighbors(pos, map);
+        if !neighbours.contains(&prev) {
+            return None;
+        }
+        let next = neighbours.into_iter().find(|&nei| nei != prev)?;
         prev = pos;
         pos = next;
     }
-    visited
+    Some(visited)
+}
+
+fn enclosed_by_loop(map: &Map, start: Pos) -> Result<usize, Error> {
+    let loop_tiles = travel_the_loop_part2(start, map).ok_or_else(|| NoAnswer {
+        day: 10,
+        why: "the start tile is not on a loop".to_owned(),
+    })?;
+    Ok(count_enclosed_tiles(map, &loop_tiles)?)
+}
+
+pub fn try_part_2(input: &str) -> Result<usize, Error> {
+    let (map, start): (Map, Pos) = parse_map_n_start(input)?;
+    enclosed_by_loop(&map, start)
 }
 
 pub fn part_2(input: &str) -> usize {
-    let (map, start): (Map, Pos) = parse_map_n_start(input);
-    let loop_tiles = travel_the_loop_part2(start, &map);
-    count_enclosed_tiles(&map, &loop_tiles)
+    try_part_2(input).unwrap()
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::day10::*;
-    use std::fs;
-    #[test]
-    fn example10_part1() {
-        let input = fs::read_to_string("input/example10").unwrap();
-        assert_eq!(part_1(&input), 8);
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input = (Map, Pos);
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_map_n_start(input)
     }
-    #[test]
-    fn day10_part1() {
-        let input = fs::read_to_string("input/day10").unwrap();
-        assert_eq!(part_1(&input), 6846);
+    fn part_1((map, start): &Self::Input) -> Result<Answer, Error> {
+        Ok(farthest_loop_tile(map, *start).into())
     }
-    #[test]
-    fn example10_part2() {
-        let input = fs::read_to_string("input/example10_part2").unwrap();
-        assert_eq!(part_2(&input), 4);
-    }
-    #[test]
-    fn example10_part2_big() {
-        let input = fs::read_to_string("input/example10_part2_big").unwrap();
-        assert_eq!(part_2(&input), 8);
-    }
-    #[test]
-    fn example10_part2_big2() {
-        let input = fs::read_to_string("input/example10_part2_big2").unwrap();
-        assert_eq!(part_2(&input), 10);
+    fn part_2((map, start): &Self::Input) -> Result<Answer, Error> {
+        Ok(enclosed_by_loop(map, *start)?.into())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day10::*;
     #[test]
-    fn day10_part2() {
-        // 325 too low
-        let input = fs::read_to_string("input/day10").unwrap();
-        assert_eq!(part_2(&input), 0);
+    fn start_tile_not_on_a_loop() {
+        let err = try_part_1("S7\n.S\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day10:2:2: expected a single start tile `S`, found `S`"
+        );
+        assert_eq!(try_part_1("S-7\n..|\n"), Ok(3));
+        assert_eq!(
+            try_part_2("S-7\n..|\n").unwrap_err().to_string(),
+            "day10: no answer, the start tile is not on a loop"
+        );
     }
 }
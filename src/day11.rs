@@ -1,12 +1,16 @@
+use crate::{
+    checked::OrOverflow,
+    error::{Overflow, Source},
+    Answer, Error, ParseError, Solution,
+};
 use itertools::Itertools;
 use std::collections::HashSet;
 
 type Pos = (i64, i64);
-struct Map {
+pub struct Map {
     occupied_columns: HashSet<i64>,
     occupied_rows: HashSet<i64>,
     galaxies: Vec<Pos>,
-    expansion_factor: u64,
 }
 
 enum Part {
@@ -14,96 +18,102 @@ enum Part {
     Two,
 }
 
+impl Part {
+    fn expansion_factor(&self) -> u64 {
+        match self {
+            Part::One => 2,
+            Part::Two => 1_000_000,
+        }
+    }
+}
+
 impl Map {
-    fn from_str(s: &str, part: Part) -> Self {
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        let src = Source::new(11, s);
         let mut occupied_columns = HashSet::<i64>::new();
         let mut occupied_rows = HashSet::<i64>::new();
         let mut galaxies = vec![];
-        s.lines()
-            .enumerate()
-            .flat_map(|(y, l)| l.chars().enumerate().map(move |(x, ch)| (x, y, ch)))
-            .filter(|(_, _, ch)| *ch == '#')
-            .for_each(|(x, y, _ch)| {
-                occupied_columns.insert(x as i64);
-                occupied_rows.insert(y as i64);
-                galaxies.push((x as i64, y as i64))
-            });
-        let expansion_factor = if matches!(part, Part::One) {
-            2
-        } else {
-            1_000_000
-        };
-        Map {
+        for (y, l) in s.lines().enumerate() {
+            for (x, (i, ch)) in l.char_indices().enumerate() {
+                match ch {
+                    '#' => {
+                        occupied_columns.insert(x as i64);
+                        occupied_rows.insert(y as i64);
+                        galaxies.push((x as i64, y as i64))
+                    }
+                    '.' => (),
+                    _ => return Err(src.error(&l[i..], "`.` or `#`")),
+                }
+            }
+        }
+        Ok(Map {
             occupied_columns,
             occupied_rows,
             galaxies,
-            expansion_factor,
-        }
+        })
     }
-    fn distance(&self, p1: Pos, p2: Pos) -> u64 {
-        let mut dist = 0;
+    fn distance(&self, p1: Pos, p2: Pos, expansion_factor: u64) -> Option<u64> {
+        let mut dist: u64 = 0;
         let dx = (p2.0 - p1.0).signum();
         let dy = (p2.1 - p1.1).signum();
         let mut p_x = p1.0;
         while p_x != p2.0 {
             p_x += dx;
             if self.occupied_columns.contains(&p_x) {
-                dist += 1;
+                dist = dist.checked_add(1)?;
             } else {
-                dist += self.expansion_factor;
+                dist = dist.checked_add(expansion_factor)?;
             }
         }
         let mut p_y = p1.1;
         while p_y != p2.1 {
             p_y += dy;
             if self.occupied_rows.contains(&p_y) {
-                dist += 1;
+                dist = dist.checked_add(1)?;
             } else {
-                dist += self.expansion_factor;
+                dist = dist.checked_add(expansion_factor)?;
             }
         }
-        dist
+        Some(dist)
     }
-    fn distance_sum(&self) -> u64 {
+    fn distance_sum(&self, part: Part) -> Result<u64, Overflow> {
         self.galaxies
             .iter()
             .tuple_combinations()
-            .map(|(p1, p2)| self.distance(*p1, *p2))
-            .sum()
+            .try_fold(0u64, |sum, (p1, p2)| {
+                sum.checked_add(self.distance(*p1, *p2, part.expansion_factor())?)
+            })
+            .or_overflow(11, "sum of distances")
     }
 }
 
+pub fn try_part_1(input: &str) -> Result<u64, Error> {
+    let map = Map::from_str(input)?;
+    Ok(map.distance_sum(Part::One)?)
+}
+pub fn try_part_2(input: &str) -> Result<u64, Error> {
+    let map = Map::from_str(input)?;
+    Ok(map.distance_sum(Part::Two)?)
+}
+
 pub fn part_1(input: &str) -> u64 {
-    let map = Map::from_str(input, Part::One);
-    map.distance_sum()
+    try_part_1(input).unwrap()
 }
 pub fn part_2(input: &str) -> u64 {
-    let map = Map::from_str(input, Part::Two);
-    map.distance_sum()
+    try_part_2(input).unwrap()
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::day11::*;
-    use std::fs;
-    #[test]
-    fn example11_part1() {
-        let input = fs::read_to_string("input/example11").unwrap();
-        assert_eq!(part_1(&input), 374);
-    }
-    #[test]
-    fn day11_part1() {
-        let input = fs::read_to_string("input/day11").unwrap();
-        assert_eq!(part_1(&input), 9521776);
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input = Map;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        Map::from_str(input)
     }
-    #[test]
-    fn example11_part2() {
-        let input = fs::read_to_string("input/example11").unwrap();
-        assert_eq!(part_2(&input), 82000210);
+    fn part_1(map: &Self::Input) -> Result<Answer, Error> {
+        Ok(map.distance_sum(Part::One)?.into())
     }
-    #[test]
-    fn day11_part2() {
-        let input = fs::read_to_string("input/day11").unwrap();
-        assert_eq!(part_2(&input), 553224415344);
+    fn part_2(map: &Self::Input) -> Result<Answer, Error> {
+        Ok(map.distance_sum(Part::Two)?.into())
     }
 }
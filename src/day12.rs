@@ -1,3 +1,10 @@
+use crate::{
+    checked::{CheckedIter, OrOverflow},
+    error::{Overflow, Source},
+    stream, Answer, Error, ParseError, Solution,
+};
+use std::io::BufRead;
+
 fn match_pattern(periods: usize, hashes: usize, instruction: &[char]) -> bool {
     if periods + hashes > instruction.len() {
         return false;
@@ -19,83 +26,129 @@ fn count_variations(
     instruction: &[char],
     criteria_groupings: &[usize],
     prev_hash: bool,
-) -> usize {
+) -> Option<usize> {
     if criteria_groupings.is_empty() {
         if instruction.contains(&'#') {
-            return 0;
+            return Some(0);
         } else {
-            return 1;
+            return Some(1);
         }
     }
-    let mut sum = 0;
+    let mut sum: usize = 0;
     for p in 0..=extra_periods {
         let periods = if prev_hash { p + 1 } else { p };
         if match_pattern(periods, criteria_groupings[0], instruction) {
             let pattern_len = periods + criteria_groupings[0];
-            sum += count_variations(
+            sum = sum.checked_add(count_variations(
                 extra_periods - p,
                 &instruction[pattern_len..],
                 &criteria_groupings[1..],
                 true,
-            );
+            )?)?;
         }
     }
-    sum
+    Some(sum)
+}
+
+type Record = (Vec<char>, Vec<usize>);
+
+fn parse_record(src: &Source, l: &str) -> Result<Record, ParseError> {
+    let (instruction, criteria) = src.split_once(l, " ")?;
+    if let Some(i) = instruction.find(|ch| !matches!(ch, '.' | '#' | '?')) {
+        return Err(src.error(&instruction[i..], "`.`, `#` or `?`"));
+    }
+    let instruction: Vec<_> = instruction.chars().collect();
+    let criteria: Vec<usize> = criteria
+        .split(',')
+        .map(|n| src.parse(n, "a group size"))
+        .collect::<Result<_, _>>()?;
+    let min_len = criteria
+        .iter()
+        .copied()
+        .checked_sum()
+        .and_then(|sum| sum.checked_add(criteria.len() - 1));
+    if min_len.map_or(true, |min_len| min_len > instruction.len()) {
+        return Err(src.error(l, "a record long enough for its groups"));
+    }
+    Ok((instruction, criteria))
+}
+
+fn parse_records(input: &str) -> Result<Vec<Record>, ParseError> {
+    let src = Source::new(12, input);
+    src.lines().map(|l| parse_record(&src, l)).collect()
+}
+
+fn count_arrangements((instruction, criteria): &Record) -> Option<usize> {
+    let min_len: usize = criteria.iter().sum::<usize>() + criteria.len() - 1;
+    let extra_spaces = instruction.len() - min_len;
+    count_variations(extra_spaces, instruction, criteria, false)
+}
+
+fn unfold((instruction, criteria): &Record) -> Record {
+    let mut instruction = instruction.clone();
+    instruction.push('?');
+    instruction = instruction.repeat(5);
+    instruction.pop();
+    (instruction, criteria.repeat(5))
+}
+
+fn sum_arrangements(records: &[Record]) -> Result<usize, Overflow> {
+    records
+        .iter()
+        .map(count_arrangements)
+        .collect::<Option<Vec<_>>>()
+        .and_then(|counts| counts.into_iter().checked_sum())
+        .or_overflow(12, "number of arrangements")
+}
+
+fn sum_unfolded_arrangements(records: &[Record]) -> Result<usize, Overflow> {
+    sum_arrangements(&records.iter().map(unfold).collect::<Vec<_>>())
+}
+
+pub fn try_part_1(input: &str) -> Result<usize, Error> {
+    Ok(sum_arrangements(&parse_records(input)?)?)
+}
+pub fn try_part_2(input: &str) -> Result<usize, Error> {
+    Ok(sum_unfolded_arrangements(&parse_records(input)?)?)
+}
+
+/// The sum of arrangements of an input read line by line, a record at a time.
+fn read_arrangements(reader: impl BufRead, unfolded: bool) -> Result<usize, Error> {
+    stream::sum_lines(12, reader, "number of arrangements", |src, l| {
+        let mut record = parse_record(src, l)?;
+        if unfolded {
+            record = unfold(&record);
+        }
+        Ok(count_arrangements(&record).or_overflow(12, "number of arrangements")?)
+    })
+}
+
+pub fn try_part_1_reader(reader: impl BufRead) -> Result<usize, Error> {
+    read_arrangements(reader, false)
+}
+
+pub fn try_part_2_reader(reader: impl BufRead) -> Result<usize, Error> {
+    read_arrangements(reader, true)
 }
 
 pub fn part_1(input: &str) -> usize {
-    input
-        .lines()
-        .map(|l| {
-            let (instruction, criteria) = l.split_once(' ').unwrap();
-            let instruction: Vec<_> = instruction.chars().collect();
-            let criteria: Vec<_> = criteria.split(',').map(|n| n.parse().unwrap()).collect();
-            let min_len: usize = criteria.iter().sum::<usize>() + criteria.len() - 1;
-            let extra_spaces = instruction.len() - min_len;
-            count_variations(extra_spaces, &instruction, &criteria, false)
-        })
-        .sum()
+    try_part_1(input).unwrap()
 }
 pub fn part_2(input: &str) -> usize {
-    input
-        .lines()
-        .map(|l| {
-            let (instruction, criteria) = l.split_once(' ').unwrap();
-            let mut instruction: Vec<_> = instruction.chars().collect();
-            instruction.push('?');
-            instruction = instruction.repeat(5);
-            instruction.pop();
-            let criteria: Vec<_> = criteria.split(',').map(|n| n.parse().unwrap()).collect();
-            let criteria = criteria.repeat(5);
-            let min_len: usize = criteria.iter().sum::<usize>() + criteria.len() - 1;
-            let extra_spaces = instruction.len() - min_len;
-            count_variations(extra_spaces, &instruction, &criteria, false)
-        })
-        .sum()
-}
-
-#[cfg(test)]
-mod tests {
-    use crate::day12::*;
-    use std::fs;
-    #[test]
-    fn example12_part1() {
-        let input = fs::read_to_string("input/example12").unwrap();
-        assert_eq!(part_1(&input), 21);
-    }
-    #[test]
-    fn day12_part1() {
-        let input = fs::read_to_string("input/day12").unwrap();
-        assert_eq!(part_1(&input), 8193);
+    try_part_2(input).unwrap()
+}
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    type Input = Vec<Record>;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_records(input)
     }
-    #[test]
-    fn example12_part2() {
-        let input = fs::read_to_string("input/example12").unwrap();
-        assert_eq!(part_2(&input), 525152);
+    fn part_1(records: &Self::Input) -> Result<Answer, Error> {
+        Ok(sum_arrangements(records)?.into())
     }
-    #[test]
-    fn day12_part2() {
-        let input = fs::read_to_string("input/day12").unwrap();
-        assert_eq!(part_2(&input), 0);
+    fn part_2(records: &Self::Input) -> Result<Answer, Error> {
+        Ok(sum_unfolded_arrangements(records)?.into())
     }
 }
@@ -1,12 +1,23 @@
+use crate::{
+    checked::OrOverflow,
+    error::{NoAnswer, Source},
+    grid::Grid,
+    Answer, Error, ParseError, Solution,
+};
 use std::{collections::HashMap, mem};
 
-struct Pattern {
-    rows: Vec<Vec<bool>>,
-    columns: Vec<Vec<bool>>,
-    width: usize,
-    height: usize,
-    memo_row_diff: HashMap<(usize, usize), usize>,
-    memo_col_diff: HashMap<(usize, usize), usize>,
+pub struct Pattern {
+    /// Where the pattern starts in the input.
+    line: usize,
+    grid: Grid<bool>,
+}
+
+/// Differences between pairs of rows and columns, remembered while looking
+/// for the smudge.
+#[derive(Default)]
+struct Memo {
+    row_diff: HashMap<(usize, usize), usize>,
+    col_diff: HashMap<(usize, usize), usize>,
 }
 
 #[derive(Debug)]
@@ -16,180 +27,226 @@ enum Reflection {
 }
 
 impl Pattern {
-    fn from_str(s: &str) -> Self {
-        let width = s.lines().next().unwrap().chars().count();
-        let height = s.lines().count();
-        let mut rows = vec![vec![false; width]; height];
-        let mut columns = vec![vec![false; height]; width];
-        s.lines()
-            .enumerate()
-            .flat_map(|(y, l)| l.chars().enumerate().map(move |(x, ch)| (x, y, ch)))
-            .filter(|(_, _, ch)| *ch == '#')
-            .for_each(|(x, y, _ch)| {
-                rows[y][x] = true;
-                columns[x][y] = true;
-            });
-
-        Pattern {
-            rows,
-            columns,
-            width,
-            height,
-            memo_row_diff: HashMap::new(),
-            memo_col_diff: HashMap::new(),
-        }
+    fn from_str(src: &Source, s: &str, line: usize) -> Result<Self, ParseError> {
+        let grid = Grid::parse(src, s, "`.` or `#`", |ch| match ch {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })?;
+        Ok(Pattern { line, grid })
     }
-    fn find_reflection(&self) -> Reflection {
+    fn find_reflection(&self) -> Option<Reflection> {
         // column-wise
-        'outer: for _left in 0..self.width - 1 {
+        'outer: for _left in 0..self.grid.width() - 1 {
             let mut left = _left;
             let mut right = _left + 1;
             loop {
-                if self.columns[left] != self.columns[right] {
+                if !self.grid.column(left).eq(self.grid.column(right)) {
                     continue 'outer;
                 }
-                if left == 0 || right == self.width - 1 {
+                if left == 0 || right == self.grid.width() - 1 {
                     break;
                 }
                 left -= 1;
                 right += 1;
             }
-            return Reflection::Column(_left + 1);
+            return Some(Reflection::Column(_left + 1));
         }
         // row-wise
-        'outer: for _up in 0..self.height - 1 {
+        'outer: for _up in 0..self.grid.height() - 1 {
             let mut up = _up;
             let mut down = _up + 1;
             loop {
-                if self.rows[up] != self.rows[down] {
+                if self.grid.row(up) != self.grid.row(down) {
                     continue 'outer;
                 }
-                if up == 0 || down == self.height - 1 {
+                if up == 0 || down == self.grid.height() - 1 {
                     break;
                 }
                 up -= 1;
                 down += 1;
             }
-            return Reflection::Row(_up + 1);
+            return Some(Reflection::Row(_up + 1));
         }
-        panic!("No reflection!")
+        None
     }
 
-    fn find_reflection_part2(&mut self) -> Reflection {
+    fn find_reflection_part2(&self) -> Option<Reflection> {
+        let mut memo = Memo::default();
         // column-wise
-        for _left in 0..self.width - 1 {
+        for _left in 0..self.grid.width() - 1 {
             let mut left = _left;
             let mut right = _left + 1;
             let mut total_diff = 0;
             loop {
-                total_diff += self.col_diff(left, right);
-                if left == 0 || right == self.width - 1 {
+                total_diff += self.col_diff(&mut memo, left, right);
+                if left == 0 || right == self.grid.width() - 1 {
                     break;
                 }
                 left -= 1;
                 right += 1;
             }
             if total_diff == 1 {
-                return Reflection::Column(_left + 1);
+                return Some(Reflection::Column(_left + 1));
             }
         }
         // row-wise
-        for _up in 0..self.height - 1 {
+        for _up in 0..self.grid.height() - 1 {
             let mut up = _up;
             let mut down = _up + 1;
             let mut total_diff = 0;
             loop {
-                total_diff += self.row_diff(up, down);
+                total_diff += self.row_diff(&mut memo, up, down);
 
-                if up == 0 || down == self.height - 1 {
+                if up == 0 || down == self.grid.height() - 1 {
                     break;
                 }
                 up -= 1;
                 down += 1;
             }
             if total_diff == 1 {
-                return Reflection::Row(_up + 1);
+                return Some(Reflection::Row(_up + 1));
             }
         }
-        panic!("No reflection!")
+        None
     }
 
-    fn row_diff(&mut self, mut r1: usize, mut r2: usize) -> usize {
+    fn no_reflection(&self, kind: &str) -> NoAnswer {
+        NoAnswer {
+            day: 13,
+            why: format!("the pattern on line {} has no {kind}", self.line),
+        }
+    }
+
+    fn row_diff(&self, memo: &mut Memo, mut r1: usize, mut r2: usize) -> usize {
         if r1 > r2 {
             mem::swap(&mut r1, &mut r2);
         }
-        if let Some(memo) = self.memo_row_diff.get(&(r1, r2)) {
-            return *memo;
+        if let Some(diff) = memo.row_diff.get(&(r1, r2)) {
+            return *diff;
         }
-        let diff = self.rows[r1]
+        let diff = self
+            .grid
+            .row(r1)
             .iter()
-            .zip(self.rows[r2].iter())
+            .zip(self.grid.row(r2))
             .filter(|(b1, b2)| b1 != b2)
             .count();
-        self.memo_row_diff.insert((r1, r2), diff);
+        memo.row_diff.insert((r1, r2), diff);
         diff
     }
 
-    fn col_diff(&mut self, mut c1: usize, mut c2: usize) -> usize {
+    fn col_diff(&self, memo: &mut Memo, mut c1: usize, mut c2: usize) -> usize {
         if c1 > c2 {
             mem::swap(&mut c1, &mut c2);
         }
-        if let Some(memo) = self.memo_col_diff.get(&(c1, c2)) {
-            return *memo;
+        if let Some(diff) = memo.col_diff.get(&(c1, c2)) {
+            return *diff;
         }
-        let diff = self.columns[c1]
-            .iter()
-            .zip(self.columns[c2].iter())
+        let diff = self
+            .grid
+            .column(c1)
+            .zip(self.grid.column(c2))
             .filter(|(b1, b2)| b1 != b2)
             .count();
-        self.memo_col_diff.insert((c1, c2), diff);
+        memo.col_diff.insert((c1, c2), diff);
         diff
     }
 }
 
-pub fn part_1(input: &str) -> usize {
+fn parse_patterns(input: &str) -> Result<Vec<Pattern>, ParseError> {
+    let src = Source::new(13, input);
+    let mut line = 1;
     input
         .split("\n\n")
-        .map(|p| Pattern::from_str(p).find_reflection())
-        .map(|refl| match refl {
-            Reflection::Row(r) => 100 * r,
-            Reflection::Column(c) => c,
+        .map(|p| {
+            let pattern = Pattern::from_str(&src, p, line)?;
+            line += pattern.grid.height() + 1;
+            Ok(pattern)
         })
-        .sum()
+        .collect()
+}
+
+fn summarize(
+    reflections: impl Iterator<Item = Result<Reflection, NoAnswer>>,
+) -> Result<usize, Error> {
+    let mut sum = 0usize;
+    for refl in reflections {
+        let note = match refl? {
+            Reflection::Row(r) => r.checked_mul(100),
+            Reflection::Column(c) => Some(c),
+        };
+        sum = note
+            .and_then(|note| sum.checked_add(note))
+            .or_overflow(13, "summary of notes")?;
+    }
+    Ok(sum)
+}
+
+fn summarize_part1(patterns: &[Pattern]) -> Result<usize, Error> {
+    summarize(patterns.iter().map(|p| {
+        p.find_reflection()
+            .ok_or_else(|| p.no_reflection("line of reflection"))
+    }))
+}
+
+fn summarize_part2(patterns: &[Pattern]) -> Result<usize, Error> {
+    summarize(patterns.iter().map(|p| {
+        p.find_reflection_part2()
+            .ok_or_else(|| p.no_reflection("line of reflection with a smudge"))
+    }))
+}
+
+pub fn try_part_1(input: &str) -> Result<usize, Error> {
+    summarize_part1(&parse_patterns(input)?)
+}
+pub fn try_part_2(input: &str) -> Result<usize, Error> {
+    summarize_part2(&parse_patterns(input)?)
+}
+
+pub fn part_1(input: &str) -> usize {
+    try_part_1(input).unwrap()
 }
 pub fn part_2(input: &str) -> usize {
-    input
-        .split("\n\n")
-        .map(|p| Pattern::from_str(p).find_reflection_part2())
-        .map(|refl| match refl {
-            Reflection::Row(r) => 100 * r,
-            Reflection::Column(c) => c,
-        })
-        .sum()
+    try_part_2(input).unwrap()
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::day13::*;
-    use std::fs;
-    #[test]
-    fn example13_part1() {
-        let input = fs::read_to_string("input/example13").unwrap();
-        assert_eq!(part_1(&input), 405);
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Input = Vec<Pattern>;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_patterns(input)
     }
-    #[test]
-    fn day13_part1() {
-        let input = fs::read_to_string("input/day13").unwrap();
-        assert_eq!(part_1(&input), 35360);
+    fn part_1(patterns: &Self::Input) -> Result<Answer, Error> {
+        Ok(summarize_part1(patterns)?.into())
     }
-    #[test]
-    fn example13_part2() {
-        let input = fs::read_to_string("input/example13").unwrap();
-        assert_eq!(part_2(&input), 400);
+    fn part_2(patterns: &Self::Input) -> Result<Answer, Error> {
+        Ok(summarize_part2(patterns)?.into())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{day13::*, error::NoAnswer};
     #[test]
-    fn day13_part2() {
-        let input = fs::read_to_string("input/day13").unwrap();
-        assert_eq!(part_2(&input), 0);
+    fn pattern_without_mirror() {
+        let input = "#.\n..\n\n##\n##\n";
+        let no_answer = |why: &str| {
+            Error::NoAnswer(NoAnswer {
+                day: 13,
+                why: why.to_owned(),
+            })
+        };
+        assert_eq!(
+            try_part_1(input),
+            Err(no_answer("the pattern on line 1 has no line of reflection"))
+        );
+        assert_eq!(
+            try_part_2(input),
+            Err(no_answer(
+                "the pattern on line 4 has no line of reflection with a smudge"
+            ))
+        );
     }
 }
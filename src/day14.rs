@@ -1,9 +1,7 @@
-enum Dir {
-    Up,
-    Down,
-    Left,
-    Right,
-}
+use crate::{
+    error::Source, geometry::Point, grid::Grid, info, Answer, Error, ParseError, Solution,
+};
+use std::fmt;
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum Tile {
@@ -11,142 +9,76 @@ enum Tile {
     Ball,
     Cube,
 }
-struct Panel {
-    tiles: Vec<Vec<Tile>>,
+
+impl fmt::Display for Tile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ch = match self {
+            Tile::Air => '.',
+            Tile::Ball => 'O',
+            Tile::Cube => '#',
+        };
+        write!(f, "{ch}")
+    }
+}
+
+#[derive(Clone)]
+pub struct Panel {
+    tiles: Grid<Tile>,
 }
 
 impl Panel {
-    fn from_str(s: &str) -> Self {
-        let tiles = s
-            .lines()
-            .map(|l| {
-                l.chars()
-                    .map(|ch| match ch {
-                        '.' => Tile::Air,
-                        '#' => Tile::Cube,
-                        'O' => Tile::Ball,
-                        _ => panic!(),
-                    })
-                    .collect()
-            })
-            .collect();
-        Self { tiles }
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        let src = Source::new(14, s);
+        let tiles = Grid::parse(&src, s, "`.`, `#` or `O`", |ch| match ch {
+            '.' => Some(Tile::Air),
+            '#' => Some(Tile::Cube),
+            'O' => Some(Tile::Ball),
+            _ => None,
+        })?;
+        Ok(Self { tiles })
     }
 
     fn tilt_north(&mut self) {
-        let height = self.tiles.len();
-        let width = self.tiles[0].len();
-        for row in 0..height {
-            for col in 0..width {
-                if !matches!(self.tiles[row][col], Tile::Ball) {
+        for row in 0..self.tiles.height() {
+            for col in 0..self.tiles.width() {
+                if !matches!(self.tiles[Point::new(col, row)], Tile::Ball) {
                     continue;
                 }
                 let mut stop = row;
-                while stop != 0 && self.tiles[stop - 1][col] == Tile::Air {
+                while stop != 0 && self.tiles[Point::new(col, stop - 1)] == Tile::Air {
                     stop -= 1;
                 }
-                self.tiles[row][col] = Tile::Air;
-                self.tiles[stop][col] = Tile::Ball;
-            }
-        }
-    }
-
-    fn tilt_south(&mut self) {
-        let height = self.tiles.len();
-        let width = self.tiles[0].len();
-        for row in (0..height).rev() {
-            for col in 0..width {
-                if !matches!(self.tiles[row][col], Tile::Ball) {
-                    continue;
-                }
-                let mut stop = row;
-                while stop != height - 1 && self.tiles[stop + 1][col] == Tile::Air {
-                    stop += 1;
-                }
-                self.tiles[row][col] = Tile::Air;
-                self.tiles[stop][col] = Tile::Ball;
-            }
-        }
-    }
-
-    fn tilt_west(&mut self) {
-        let height = self.tiles.len();
-        let width = self.tiles[0].len();
-        for col in 0..width {
-            for row in 0..height {
-                if !matches!(self.tiles[row][col], Tile::Ball) {
-                    continue;
-                }
-                let mut stop = col;
-                while stop != 0 && self.tiles[row][stop - 1] == Tile::Air {
-                    stop -= 1;
-                }
-                self.tiles[row][col] = Tile::Air;
-                self.tiles[row][stop] = Tile::Ball;
-            }
-        }
-    }
-
-    fn tilt_east(&mut self) {
-        let height = self.tiles.len();
-        let width = self.tiles[0].len();
-        for col in (0..width).rev() {
-            for row in 0..height {
-                if !matches!(self.tiles[row][col], Tile::Ball) {
-                    continue;
-                }
-                let mut stop = col;
-                while stop != width - 1 && self.tiles[row][stop + 1] == Tile::Air {
-                    stop += 1;
-                }
-                self.tiles[row][col] = Tile::Air;
-                self.tiles[row][stop] = Tile::Ball;
+                self.tiles[Point::new(col, row)] = Tile::Air;
+                self.tiles[Point::new(col, stop)] = Tile::Ball;
             }
         }
     }
 
+    /// Tilts north, west, south and east. Rotating clockwise after each tilt
+    /// brings the next side up and leaves the panel the way it started.
     fn cycle(&mut self) {
-        self.tilt_north();
-        self.tilt_west();
-        self.tilt_south();
-        self.tilt_east();
+        for _ in 0..4 {
+            self.tilt_north();
+            self.tiles = self.tiles.rotate_clockwise();
+        }
     }
 
     fn north_load(&self) -> usize {
-        let height = self.tiles.len();
+        let height = self.tiles.height();
         self.tiles
             .iter()
-            .enumerate()
-            .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, t)| (x, y, t)))
-            .filter(|(_, _, t)| **t == Tile::Ball)
-            .map(|(_, y, _)| height - y)
+            .filter(|(_, t)| **t == Tile::Ball)
+            .map(|(pos, _)| height - pos.y)
             .sum()
     }
-
-    fn print(&self) {
-        for row in &self.tiles {
-            let line: String = row
-                .iter()
-                .map(|t| match t {
-                    Tile::Air => '.',
-                    Tile::Ball => 'O',
-                    Tile::Cube => '#',
-                })
-                .collect();
-            println!("{line}");
-        }
-        println!();
-    }
 }
 
-pub fn part_1(input: &str) -> usize {
-    let mut panel = Panel::from_str(input);
+fn load_after_tilt(mut panel: Panel) -> usize {
     panel.tilt_north();
     panel.north_load()
 }
 
-pub fn part_2(input: &str) -> usize {
-    let mut panel = Panel::from_str(input);
+fn load_after_cycles(mut panel: Panel) -> usize {
     let mut cycles_remaining = 1000000000;
     for _ in 0..100 {
         panel.cycle();
@@ -167,6 +99,10 @@ pub fn part_2(input: &str) -> usize {
     }
     // found repeating pattern
     let period = len / 2;
+    info!(
+        "cycle of period {period} detected at iteration {}",
+        1000000000 - cycles_remaining
+    );
     let remaining = cycles_remaining % period;
     for _ in 0..remaining {
         panel.cycle();
@@ -174,28 +110,33 @@ pub fn part_2(input: &str) -> usize {
     panel.north_load()
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::day14::*;
-    use std::fs;
-    #[test]
-    fn example14_part1() {
-        let input = fs::read_to_string("input/example14").unwrap();
-        assert_eq!(part_1(&input), 136);
-    }
-    #[test]
-    fn day14_part1() {
-        let input = fs::read_to_string("input/day14").unwrap();
-        assert_eq!(part_1(&input), 107053);
+pub fn try_part_1(input: &str) -> Result<usize, ParseError> {
+    Ok(load_after_tilt(Panel::from_str(input)?))
+}
+
+pub fn try_part_2(input: &str) -> Result<usize, ParseError> {
+    Ok(load_after_cycles(Panel::from_str(input)?))
+}
+
+pub fn part_1(input: &str) -> usize {
+    try_part_1(input).unwrap()
+}
+
+pub fn part_2(input: &str) -> usize {
+    try_part_2(input).unwrap()
+}
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input = Panel;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        Panel::from_str(input)
     }
-    #[test]
-    fn example14_part2() {
-        let input = fs::read_to_string("input/example14").unwrap();
-        assert_eq!(part_2(&input), 64);
+    fn part_1(panel: &Self::Input) -> Result<Answer, Error> {
+        Ok(load_after_tilt(panel.clone()).into())
     }
-    #[test]
-    fn day14_part2() {
-        let input = fs::read_to_string("input/day14").unwrap();
-        assert_eq!(part_2(&input), 88371);
+    fn part_2(panel: &Self::Input) -> Result<Answer, Error> {
+        Ok(load_after_cycles(panel.clone()).into())
     }
 }
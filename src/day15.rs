@@ -1,5 +1,11 @@
-use std::array;
+use crate::{
+    checked::{CheckedIter, OrOverflow},
+    error::{Overflow, Source},
+    input, Answer, Error, ParseError, Solution,
+};
+use std::{array, io::BufRead, str};
 
+#[allow(non_snake_case)]
 fn HASH(s: &str) -> usize {
     s.as_bytes().iter().fold(0, |mut acc, b| {
         acc += *b as usize;
@@ -9,6 +15,30 @@ fn HASH(s: &str) -> usize {
     })
 }
 
+enum Operation {
+    Insert { label: String, focal_length: u32 },
+    Remove { label: String },
+}
+
+impl Operation {
+    fn from_str(src: &Source, op: &str) -> Result<Self, ParseError> {
+        if let Some((label, f)) = op.split_once('=') {
+            Ok(Operation::Insert {
+                label: label.to_owned(),
+                focal_length: src.parse(f, "a focal length")?,
+            })
+        } else {
+            let label = op
+                .strip_suffix('-')
+                .ok_or_else(|| src.error(op, "`=` or `-` operation"))?;
+            Ok(Operation::Remove {
+                label: label.to_owned(),
+            })
+        }
+    }
+}
+
+#[allow(clippy::upper_case_acronyms)]
 struct HASHMAP {
     boxes: [Vec<(String, u32)>; 256],
 }
@@ -19,69 +49,172 @@ impl HASHMAP {
             boxes: array::from_fn(|_| vec![]),
         }
     }
-    fn operate(&mut self, op: &str) {
-        if let Some((label, f)) = op.split_once('=') {
-            let hash = HASH(label);
-            let bx = self.boxes.get_mut(hash).unwrap();
-            if let Some(lens) = bx.iter_mut().find(|(k, _v)| k == label) {
-                lens.1 = f.parse().unwrap();
-            } else {
-                bx.push((label.to_owned(), f.parse().unwrap()));
+    fn operate(&mut self, op: &Operation) {
+        match op {
+            Operation::Insert {
+                label,
+                focal_length,
+            } => {
+                let bx = self.boxes.get_mut(HASH(label)).unwrap();
+                if let Some(lens) = bx.iter_mut().find(|(k, _v)| k == label) {
+                    lens.1 = *focal_length;
+                } else {
+                    bx.push((label.clone(), *focal_length));
+                }
+            }
+            Operation::Remove { label } => {
+                let bx = self.boxes.get_mut(HASH(label)).unwrap();
+                bx.retain(|lens| lens.0 != *label);
             }
-        } else {
-            let op = op.strip_suffix('-').unwrap();
-            let hash = HASH(op);
-            let bx = self.boxes.get_mut(hash).unwrap();
-            bx.retain(|lens| lens.0 != op);
         }
     }
-    fn focusing_power(&self) -> u32 {
+    fn focusing_power(&self) -> Result<u32, Overflow> {
         self.boxes
             .iter()
             .enumerate()
             .flat_map(|(i, bx)| {
-                bx.iter()
-                    .enumerate()
-                    .map(move |(slot, (_, f))| (i + 1) as u32 * (slot + 1) as u32 * f)
+                bx.iter().enumerate().map(move |(slot, (_, f))| {
+                    ((i + 1) as u32)
+                        .checked_mul(u32::try_from(slot + 1).ok()?)?
+                        .checked_mul(*f)
+                })
             })
-            .sum()
+            .collect::<Option<Vec<_>>>()
+            .and_then(|powers| powers.into_iter().checked_sum())
+            .or_overflow(15, "focusing power")
     }
 }
 
-pub fn part_1(input: &str) -> usize {
-    input.split(',').map(|s| s.trim()).map(HASH).sum()
+/// Calls `f` with each step of an input read a step at a time, and a source
+/// of the step and its comma, both as `input::normalize` would leave them.
+fn for_each_step(
+    mut reader: impl BufRead,
+    mut f: impl FnMut(&Source, &str) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let mut buf = vec![];
+    let (mut line, mut column) = (1, 1);
+    let mut first = true;
+    loop {
+        buf.clear();
+        reader.read_until(b',', &mut buf)?;
+        let chunk = str::from_utf8(&buf).map_err(|e| Error::Io(e.to_string()))?;
+        let last = !chunk.ends_with(',');
+        let text = if last {
+            match input::normalize(chunk) {
+                // the newline ending the steps before
+                text if text.is_empty() && !first => "\n".to_owned(),
+                text => text,
+            }
+        } else {
+            let mut pieces = chunk.split('\n').peekable();
+            let mut text = String::with_capacity(chunk.len());
+            while let Some(piece) = pieces.next() {
+                if pieces.peek().is_some() {
+                    text.push_str(piece.trim_end());
+                    text.push('\n');
+                } else {
+                    text.push_str(piece);
+                }
+            }
+            text
+        };
+        let step = text.strip_suffix(',').unwrap_or(&text).trim();
+        f(&Source::at(15, &text, line, column), step)?;
+        if last {
+            return Ok(());
+        }
+        for ch in text.chars() {
+            (line, column) = if ch == '\n' {
+                (line + 1, 1)
+            } else {
+                (line, column + 1)
+            };
+        }
+        first = false;
+    }
 }
-pub fn part_2(input: &str) -> u32 {
-    let mut hm = HASHMAP::new();
+
+/// A step of the initialization sequence. Any step has a hash, but only
+/// operations can be carried out, so their errors wait for part 2.
+pub struct Step {
+    hash: usize,
+    operation: Result<Operation, ParseError>,
+}
+
+pub fn parse_steps(input: &str) -> Vec<Step> {
+    let src = Source::new(15, input);
     input
         .split(',')
         .map(|s| s.trim())
-        .for_each(|op| hm.operate(op));
-    hm.focusing_power()
-}
-
-#[cfg(test)]
-mod tests {
-    use crate::day15::*;
-    use std::fs;
-    #[test]
-    fn example15_part1() {
-        let input = fs::read_to_string("input/example15").unwrap();
-        assert_eq!(part_1(&input), 1320);
+        .map(|step| Step {
+            hash: HASH(step),
+            operation: Operation::from_str(&src, step),
+        })
+        .collect()
+}
+
+fn sum_of_hashes(steps: &[Step]) -> Result<usize, Overflow> {
+    steps
+        .iter()
+        .map(|step| step.hash)
+        .checked_sum()
+        .or_overflow(15, "sum of hashes")
+}
+
+fn initialize(steps: &[Step]) -> Result<u32, Error> {
+    let mut hm = HASHMAP::new();
+    for step in steps {
+        hm.operate(step.operation.as_ref().map_err(Clone::clone)?);
     }
-    #[test]
-    fn day15_part1() {
-        let input = fs::read_to_string("input/day15").unwrap();
-        assert_eq!(part_1(&input), 513172);
+    Ok(hm.focusing_power()?)
+}
+
+pub fn try_part_1(input: &str) -> Result<usize, Error> {
+    Ok(sum_of_hashes(&parse_steps(input))?)
+}
+pub fn try_part_2(input: &str) -> Result<u32, Error> {
+    initialize(&parse_steps(input))
+}
+
+/// `try_part_1` on an input read a step at a time.
+pub fn try_part_1_reader(reader: impl BufRead) -> Result<usize, Error> {
+    let mut sum: usize = 0;
+    for_each_step(reader, |_, step| {
+        sum = sum
+            .checked_add(HASH(step))
+            .or_overflow(15, "sum of hashes")?;
+        Ok(())
+    })?;
+    Ok(sum)
+}
+
+pub fn try_part_2_reader(reader: impl BufRead) -> Result<u32, Error> {
+    let mut hm = HASHMAP::new();
+    for_each_step(reader, |src, step| {
+        hm.operate(&Operation::from_str(src, step)?);
+        Ok(())
+    })?;
+    Ok(hm.focusing_power()?)
+}
+
+pub fn part_1(input: &str) -> usize {
+    try_part_1(input).unwrap()
+}
+pub fn part_2(input: &str) -> u32 {
+    try_part_2(input).unwrap()
+}
+
+pub struct Day15;
+
+impl Solution for Day15 {
+    type Input = Vec<Step>;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        Ok(parse_steps(input))
     }
-    #[test]
-    fn example15_part2() {
-        let input = fs::read_to_string("input/example15").unwrap();
-        assert_eq!(part_2(&input), 145);
+    fn part_1(steps: &Self::Input) -> Result<Answer, Error> {
+        Ok(sum_of_hashes(steps)?.into())
     }
-    #[test]
-    fn day15_part2() {
-        let input = fs::read_to_string("input/day15").unwrap();
-        assert_eq!(part_2(&input), 237806);
+    fn part_2(steps: &Self::Input) -> Result<Answer, Error> {
+        Ok(initialize(steps)?.into())
     }
 }
@@ -1,30 +1,18 @@
+use crate::{
+    error::Source,
+    geometry::{Dir, Point},
+    grid::{Grid, Pos},
+    trace, Answer, Error, ParseError, Solution,
+};
 use std::collections::HashSet;
 
-struct Map {
-    tiles: Vec<Vec<char>>,
-    width: usize,
-    height: usize,
-}
-impl Map {
-    fn from_str(input: &str) -> Self {
-        let width = input.lines().next().unwrap().chars().count();
-        let height = input.lines().count();
-        let tiles = input.lines().map(|l| l.chars().collect()).collect();
-        Self {
-            tiles,
-            width,
-            height,
-        }
-    }
-}
-type Pos = (i32, i32);
+pub type Map = Grid<char>;
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-enum Dir {
-    Up,
-    Down,
-    Left,
-    Right,
+fn parse_map(input: &str) -> Result<Map, ParseError> {
+    let src = Source::new(16, input);
+    Grid::parse(&src, input, "`.`, `|`, `-`, `/` or `\\`", |ch| {
+        matches!(ch, '.' | '|' | '-' | '/' | '\\').then_some(ch)
+    })
 }
 
 struct Beam {
@@ -34,21 +22,13 @@ struct Beam {
 
 impl Beam {
     fn next_pos(&mut self, map: &Map) -> bool {
-        let next_pos = match self.dir {
-            Dir::Up => (self.pos.0, self.pos.1 - 1),
-            Dir::Down => (self.pos.0, self.pos.1 + 1),
-            Dir::Left => (self.pos.0 - 1, self.pos.1),
-            Dir::Right => (self.pos.0 + 1, self.pos.1),
-        };
-        if next_pos.0 < 0
-            || next_pos.1 < 0
-            || next_pos.0 == map.width as i32
-            || next_pos.1 == map.height as i32
-        {
-            return false;
+        match map.step(self.pos, self.dir) {
+            Some(next_pos) => {
+                self.pos = next_pos;
+                true
+            }
+            None => false,
         }
-        self.pos = next_pos;
-        true
     }
 }
 
@@ -59,113 +39,89 @@ fn run_beam(start_pos: Pos, start_dir: Dir, map: &Map) -> usize {
         dir: start_dir,
         pos: start_pos,
     }];
+    // beams start on their first tile, so a split beam passes over its
+    // splitter once more in the new direction, which lets it through
     while let Some(mut beam) = beams.pop() {
-        while beam.next_pos(map) {
+        loop {
             if !visited_with_dir.insert((beam.pos, beam.dir)) {
                 break;
             }
             visited.insert(beam.pos);
-            match (
-                map.tiles[beam.pos.1 as usize][beam.pos.0 as usize],
-                beam.dir,
-            ) {
-                ('|', Dir::Left | Dir::Right) => {
-                    beams.push(Beam {
-                        dir: Dir::Up,
-                        pos: beam.pos,
-                    });
-                    beams.push(Beam {
-                        dir: Dir::Down,
-                        pos: beam.pos,
-                    });
-                    break;
-                }
-                ('-', Dir::Up | Dir::Down) => {
-                    beams.push(Beam {
-                        dir: Dir::Left,
-                        pos: beam.pos,
-                    });
-                    beams.push(Beam {
-                        dir: Dir::Right,
-                        pos: beam.pos,
-                    });
-                    break;
-                }
-                ('/', dir) => {
-                    beam.dir = match dir {
-                        Dir::Up => Dir::Right,
-                        Dir::Down => Dir::Left,
-                        Dir::Left => Dir::Down,
-                        Dir::Right => Dir::Up,
-                    }
-                }
-                ('\\', dir) => {
-                    beam.dir = match dir {
-                        Dir::Up => Dir::Left,
-                        Dir::Down => Dir::Right,
-                        Dir::Left => Dir::Up,
-                        Dir::Right => Dir::Down,
+            match (map[beam.pos], beam.dir.is_vertical()) {
+                ('|', false) | ('-', true) => {
+                    trace!("beam split at ({}, {})", beam.pos.x, beam.pos.y);
+                    for dir in [beam.dir.turn_left(), beam.dir.turn_right()] {
+                        beams.push(Beam { dir, pos: beam.pos });
                     }
+                    break;
                 }
+                ('/', true) | ('\\', false) => beam.dir = beam.dir.turn_right(),
+                ('/', false) | ('\\', true) => beam.dir = beam.dir.turn_left(),
                 _ => (),
             }
+            if !beam.next_pos(map) {
+                break;
+            }
         }
     }
     visited.len()
 }
-pub fn part_1(input: &str) -> usize {
-    let map = Map::from_str(input);
-    run_beam((-1, 0), Dir::Right, &map)
+fn energized_from_corner(map: &Map) -> usize {
+    run_beam(Point::new(0, 0), Dir::Right, map)
 }
 
-pub fn part_2(input: &str) -> usize {
-    let map = Map::from_str(input);
+fn max_energized(map: &Map) -> usize {
     let mut max = 0;
     // going down
-    for start_x in 0..map.width {
-        let energy = run_beam((start_x as i32, -1), Dir::Down, &map);
+    for start_x in 0..map.width() {
+        let energy = run_beam(Point::new(start_x, 0), Dir::Down, map);
         max = max.max(energy);
     }
     // going up
-    for start_x in 0..map.width {
-        let energy = run_beam((start_x as i32, map.height as i32), Dir::Up, &map);
+    for start_x in 0..map.width() {
+        let energy = run_beam(Point::new(start_x, map.height() - 1), Dir::Up, map);
         max = max.max(energy);
     }
     // going right
-    for start_y in 0..map.height {
-        let energy = run_beam((-1, start_y as i32), Dir::Right, &map);
+    for start_y in 0..map.height() {
+        let energy = run_beam(Point::new(0, start_y), Dir::Right, map);
         max = max.max(energy);
     }
     // going left
-    for start_y in 0..map.height {
-        let energy = run_beam((map.width as i32, start_y as i32), Dir::Left, &map);
+    for start_y in 0..map.height() {
+        let energy = run_beam(Point::new(map.width() - 1, start_y), Dir::Left, map);
         max = max.max(energy);
     }
     max
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::day16::*;
-    use std::fs;
-    #[test]
-    fn example16_part1() {
-        let input = fs::read_to_string("input/example16").unwrap();
-        assert_eq!(part_1(&input), 46);
-    }
-    #[test]
-    fn day16_part1() {
-        let input = fs::read_to_string("input/day16").unwrap();
-        assert_eq!(part_1(&input), 7482);
+pub fn try_part_1(input: &str) -> Result<usize, ParseError> {
+    Ok(energized_from_corner(&parse_map(input)?))
+}
+
+pub fn try_part_2(input: &str) -> Result<usize, ParseError> {
+    Ok(max_energized(&parse_map(input)?))
+}
+
+pub fn part_1(input: &str) -> usize {
+    try_part_1(input).unwrap()
+}
+
+pub fn part_2(input: &str) -> usize {
+    try_part_2(input).unwrap()
+}
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    type Input = Map;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_map(input)
     }
-    #[test]
-    fn example16_part2() {
-        let input = fs::read_to_string("input/example16").unwrap();
-        assert_eq!(part_2(&input), 51);
+    fn part_1(map: &Self::Input) -> Result<Answer, Error> {
+        Ok(energized_from_corner(map).into())
     }
-    #[test]
-    fn day16_part2() {
-        let input = fs::read_to_string("input/day16").unwrap();
-        assert_eq!(part_2(&input), 7896);
+    fn part_2(map: &Self::Input) -> Result<Answer, Error> {
+        Ok(max_energized(map).into())
     }
 }
@@ -1,46 +1,22 @@
+use crate::{
+    checked::OrOverflow,
+    error::{NoAnswer, Overflow, Source},
+    geometry::{Dir, Point},
+    grid::{Grid, Pos},
+    Answer, Error, ParseError, Solution,
+};
 use std::{
     cmp::Reverse,
     collections::{hash_map::Entry, BinaryHeap, HashMap},
 };
 
-struct Map {
-    tiles: Vec<Vec<u32>>,
-    width: usize,
-    height: usize,
-}
-
-impl Map {
-    fn from_str(input: &str) -> Self {
-        let width = input.lines().next().unwrap().chars().count();
-        let height = input.lines().count();
-        let tiles = input
-            .lines()
-            .map(|l| l.chars().map(|ch| ch.to_digit(10).unwrap()).collect())
-            .collect();
-        Self {
-            tiles,
-            width,
-            height,
-        }
-    }
+pub type Map = Grid<u32>;
 
-    fn cost(&self, (x, y): Pos) -> Option<u32> {
-        if x < 0 || y < 0 || x >= self.width as i16 || y >= self.height as i16 {
-            None
-        } else {
-            Some(self.tiles[y as usize][x as usize])
-        }
-    }
+fn parse_map(input: &str) -> Result<Map, ParseError> {
+    let src = Source::new(17, input);
+    Grid::parse(&src, input, "a digit", |ch| ch.to_digit(10))
 }
-type Pos = (i16, i16);
 
-#[derive(Hash, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
-enum Dir {
-    Up,
-    Down,
-    Left,
-    Right,
-}
 #[derive(Hash, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
 struct State {
     dir: Dir,
@@ -50,94 +26,33 @@ struct State {
 fn next_steps(
     map: &Map,
     cost: u32,
-    (x, y): Pos,
+    pos: Pos,
     State { dir, steps }: State,
     to_visit: &mut BinaryHeap<(Reverse<u32>, Pos, State)>,
     part: Part,
-) {
+) -> Result<(), Overflow> {
     let (min_steps, max_steps) = match part {
         Part::One => (0, 3),
         Part::Two => (4, 10),
     };
-    let up = (x, y - 1);
-    let down = (x, y + 1);
-    let right = (x + 1, y);
-    let left = (x - 1, y);
-    let up_cost = map.cost(up).map(|c| c + cost);
-    let down_cost = map.cost(down).map(|c| c + cost);
-    let left_cost = map.cost(left).map(|c| c + cost);
-    let right_cost = map.cost(right).map(|c| c + cost);
-    #[rustfmt::skip]
-    let next_up = up_cost.map(|c| {( Reverse(c), up, State {dir: Dir::Up, steps: 1})});
-    #[rustfmt::skip]
-    let next_down = down_cost.map(|c| {( Reverse(c), down, State {dir: Dir::Down, steps: 1})});
-    #[rustfmt::skip]
-    let next_left = left_cost.map(|c| {(Reverse(c), left,  State {dir: Dir::Left, steps: 1})});
-    #[rustfmt::skip]
-    let next_right = right_cost.map(|c| {(Reverse(c), right,  State {dir: Dir::Right, steps: 1}) });
-
-    match dir {
-        Dir::Up => {
-            if steps < min_steps && up_cost.is_none() {
-                return;
-            }
-            if steps < max_steps {
-                if let Some(mut n) = next_up {
-                    n.2.steps = 1 + steps;
-                    to_visit.push(n);
-                }
-            }
-            if steps >= min_steps {
-                to_visit.extend(next_right);
-                to_visit.extend(next_left);
-            }
-        }
-        Dir::Down => {
-            if steps < min_steps && down_cost.is_none() {
-                return;
-            }
-            if steps < max_steps {
-                if let Some(mut n) = next_down {
-                    n.2.steps = 1 + steps;
-                    to_visit.push(n);
-                }
-            }
-            if steps >= min_steps {
-                to_visit.extend(next_right);
-                to_visit.extend(next_left);
-            }
-        }
-        Dir::Left => {
-            if steps < min_steps && left_cost.is_none() {
-                return;
-            }
-            if steps < max_steps {
-                if let Some(mut n) = next_left {
-                    n.2.steps = 1 + steps;
-                    to_visit.push(n);
-                }
-            }
-            if steps >= min_steps {
-                to_visit.extend(next_up);
-                to_visit.extend(next_down);
-            }
-        }
-        Dir::Right => {
-            if steps < min_steps && right_cost.is_none() {
-                return;
-            }
-            if steps < max_steps {
-                if let Some(mut n) = next_right {
-                    n.2.steps = 1 + steps;
-                    to_visit.push(n);
-                }
-            }
-            if steps >= min_steps {
-                to_visit.extend(next_up);
-                to_visit.extend(next_down);
-            }
+    let mut push = |dir: Dir, steps: u8| {
+        if let Some(p) = map.step(pos, dir) {
+            let cost = map[p].checked_add(cost).or_overflow(17, "heat loss")?;
+            to_visit.push((Reverse(cost), p, State { dir, steps }));
         }
+        Ok(())
+    };
+    if steps < min_steps && map.step(pos, dir).is_none() {
+        return Ok(());
     }
+    if steps < max_steps {
+        push(dir, steps + 1)?;
+    }
+    if steps >= min_steps {
+        push(dir.turn_right(), 1)?;
+        push(dir.turn_left(), 1)?;
+    }
+    Ok(())
 }
 
 #[derive(Clone, Copy)]
@@ -146,12 +61,12 @@ enum Part {
     Two,
 }
 
-fn search(map: Map, part: Part) -> u32 {
+fn search(map: &Map, part: Part) -> Result<u32, Error> {
     let mut min_cost = HashMap::new();
     let mut to_visit_ = BinaryHeap::new();
     to_visit_.push((
         Reverse(0),
-        (0, 0),
+        Point::new(0, 0),
         State {
             dir: Dir::Right,
             steps: 0,
@@ -159,15 +74,15 @@ fn search(map: Map, part: Part) -> u32 {
     ));
     to_visit_.push((
         Reverse(0),
-        (0, 0),
+        Point::new(0, 0),
         State {
             dir: Dir::Down,
             steps: 0,
         },
     ));
-    let mut best = u32::MAX;
+    let mut best = None;
     while let Some((Reverse(cost), pos, state)) = to_visit_.pop() {
-        if cost > best {
+        if best.is_some_and(|best| cost > best) {
             continue;
         }
         match min_cost.entry((pos, state)) {
@@ -185,60 +100,66 @@ fn search(map: Map, part: Part) -> u32 {
 
         match part {
             Part::One => {
-                if pos == (map.width as i16 - 1, map.height as i16 - 1) {
-                    best = best.min(cost);
+                if pos == Point::new(map.width() - 1, map.height() - 1) {
+                    best = Some(best.map_or(cost, |best: u32| best.min(cost)));
                     continue;
                 }
             }
             Part::Two => {
-                if pos == (map.width as i16 - 1, map.height as i16 - 1) && state.steps >= 4 {
-                    best = best.min(cost);
+                if pos == Point::new(map.width() - 1, map.height() - 1) && state.steps >= 4 {
+                    best = Some(best.map_or(cost, |best: u32| best.min(cost)));
                     continue;
                 }
             }
         }
-        next_steps(&map, cost, pos, state, &mut to_visit_, part);
+        next_steps(map, cost, pos, state, &mut to_visit_, part)?;
     }
-    best
+    Ok(best.ok_or_else(|| NoAnswer {
+        day: 17,
+        why: "the crucible cannot reach the factory".to_owned(),
+    })?)
+}
+
+pub fn try_part_1(input: &str) -> Result<u32, Error> {
+    let map = parse_map(input)?;
+    search(&map, Part::One)
+}
+pub fn try_part_2(input: &str) -> Result<u32, Error> {
+    let map = parse_map(input)?;
+    search(&map, Part::Two)
 }
 
 pub fn part_1(input: &str) -> u32 {
-    let map = Map::from_str(input);
-    search(map, Part::One)
+    try_part_1(input).unwrap()
 }
 pub fn part_2(input: &str) -> u32 {
-    let map = Map::from_str(input);
-    search(map, Part::Two)
+    try_part_2(input).unwrap()
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::day17::*;
-    use std::fs;
-    #[test]
-    fn example17_part1() {
-        let input = fs::read_to_string("input/example17").unwrap();
-        assert_eq!(part_1(&input), 102);
-    }
-    #[test]
-    fn day17_part1() {
-        let input = fs::read_to_string("input/day17").unwrap();
-        assert_eq!(part_1(&input), 742);
+pub struct Day17;
+
+impl Solution for Day17 {
+    type Input = Map;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_map(input)
     }
-    #[test]
-    fn example17_part2() {
-        let input = fs::read_to_string("input/example17").unwrap();
-        assert_eq!(part_2(&input), 94);
+    fn part_1(map: &Self::Input) -> Result<Answer, Error> {
+        Ok(search(map, Part::One)?.into())
     }
-    #[test]
-    fn example17_part2_2() {
-        let input = fs::read_to_string("input/example17_2").unwrap();
-        assert_eq!(part_2(&input), 71);
+    fn part_2(map: &Self::Input) -> Result<Answer, Error> {
+        Ok(search(map, Part::Two)?.into())
     }
+}
 
+#[cfg(test)]
+mod tests {
+    use crate::day17::*;
     #[test]
-    fn day17_part2() {
-        let input = fs::read_to_string("input/day17").unwrap();
-        assert_eq!(part_2(&input), 918);
+    fn ultra_crucible_too_long_to_turn() {
+        assert_eq!(try_part_1("12\n34\n"), Ok(6));
+        assert_eq!(
+            try_part_2("12\n34\n").unwrap_err().to_string(),
+            "day17: no answer, the crucible cannot reach the factory"
+        );
     }
 }
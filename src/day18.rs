@@ -1,29 +1,34 @@
-use std::collections::HashSet;
+use crate::{
+    checked::OrOverflow,
+    error::{NoAnswer, Source},
+    Answer, Error, ParseError, Solution,
+};
 
 type Pos = (i64, i64);
 
-fn build_moat(input: &str) -> Vec<Pos> {
+fn build_moat(input: &str) -> Result<Vec<Pos>, ParseError> {
+    let src = Source::new(18, input);
     let mut pos = (0, 0);
     let mut res = vec![];
     res.push(pos);
-    for dig in input.lines() {
+    for dig in src.lines() {
         //R 6 (#70c710)
-        let (dir, rest) = dig.split_once(' ').unwrap();
-        let (distance, _color) = rest.split_once(' ').unwrap();
+        let (dir, rest) = src.split_once(dig, " ")?;
+        let (distance, _color) = src.split_once(rest, " ")?;
         let step = match dir {
             "U" => |p: Pos| (p.0, p.1 - 1),
             "D" => |p: Pos| (p.0, p.1 + 1),
             "R" => |p: Pos| (p.0 + 1, p.1),
             "L" => |p: Pos| (p.0 - 1, p.1),
-            _ => panic!(),
+            _ => return Err(src.error(dir, "`U`, `D`, `R` or `L`")),
         };
-        let distance: usize = distance.parse().unwrap();
+        let distance: usize = src.parse(distance, "a distance")?;
         for _ in 0..distance {
             pos = step(pos);
             res.push(pos);
         }
     }
-    res
+    Ok(res)
 }
 
 fn is_inside(moat: &[Pos], o: Pos) -> bool {
@@ -52,82 +57,115 @@ fn count_fill(moat: &[Pos], min_x: i64, min_y: i64, max_x: i64, max_y: i64) -> u
         .count()
 }
 
-pub fn part_1(input: &str) -> usize {
-    let moat = build_moat(input);
+fn lagoon_size_part1(moat: &[Pos]) -> usize {
     let min_x = moat.iter().map(|p| p.0).min().unwrap();
     let max_x = moat.iter().map(|p| p.0).max().unwrap();
     let min_y = moat.iter().map(|p| p.1).min().unwrap();
     let max_y = moat.iter().map(|p| p.1).max().unwrap();
-    count_fill(&moat, min_x, min_y, max_x, max_y) + moat.len() - 1
+    count_fill(moat, min_x, min_y, max_x, max_y) + moat.len() - 1
+}
+
+pub fn try_part_1(input: &str) -> Result<usize, ParseError> {
+    Ok(lagoon_size_part1(&build_moat(input)?))
 }
 
-// i64::from_str_radix("1f", 16);
+pub fn part_1(input: &str) -> usize {
+    try_part_1(input).unwrap()
+}
 
-fn build_moat_part2(input: &str) -> (Vec<Pos>, HashSet<Pos>) {
+/// The corners of the trench and its length.
+fn build_moat_part2(input: &str) -> Result<(Vec<Pos>, i64), Error> {
+    let src = Source::new(18, input);
     let mut pos = (0, 0);
     let mut res = vec![];
-    let mut set = HashSet::new();
+    let mut length: i64 = 0;
     res.push(pos);
-    set.insert(pos);
-    for dig in input.lines() {
-        let (dir, rest) = dig.split_once(' ').unwrap();
-        let (_, hex) = rest.split_once(' ').unwrap();
+    for dig in src.lines() {
+        // the colour is the instruction: 5 hex digits of distance and a direction
+        let (_, rest) = src.split_once(dig, " ")?;
+        let (_, hex) = src.split_once(rest, " ")?;
+        let hex = src.strip_suffix(src.strip_prefix(hex, "(#")?, ")")?;
+        if hex.len() != 6 || !hex.is_char_boundary(5) {
+            return Err(src.error(hex, "6 hex digits").into());
+        }
+        let (distance, dir) = hex.split_at(5);
         let step = match dir {
-            "U" => |p: Pos, dist: i64| (p.0, p.1 - dist),
-            "D" => |p: Pos, dist: i64| (p.0, p.1 + dist),
-            "R" => |p: Pos, dist: i64| (p.0 + dist, p.1),
-            "L" => |p: Pos, dist: i64| (p.0 - dist, p.1),
-            _ => panic!(),
+            "3" => |p: Pos, dist: i64| Some((p.0, p.1.checked_sub(dist)?)),
+            "1" => |p: Pos, dist: i64| Some((p.0, p.1.checked_add(dist)?)),
+            "0" => |p: Pos, dist: i64| Some((p.0.checked_add(dist)?, p.1)),
+            "2" => |p: Pos, dist: i64| Some((p.0.checked_sub(dist)?, p.1)),
+            _ => return Err(src.error(dir, "`0`, `1`, `2` or `3`").into()),
         };
-        let hex = hex.strip_prefix("(#").unwrap().strip_suffix(')').unwrap();
-        let distance: i64 = i64::from_str_radix(hex, 16).unwrap();
-        pos = step(pos, distance);
+        let distance: i64 =
+            i64::from_str_radix(distance, 16).map_err(|_| src.error(distance, "a hex distance"))?;
+        pos = step(pos, distance).or_overflow(18, "position of the trench")?;
+        length = length
+            .checked_add(distance)
+            .or_overflow(18, "length of the trench")?;
         res.push(pos);
-        set.insert(pos);
     }
-    (res, set)
+    if pos != (0, 0) {
+        return Err(NoAnswer {
+            day: 18,
+            why: "the trench does not end where it starts".to_owned(),
+        }
+        .into());
+    }
+    Ok((res, length))
 }
 
-fn find_inside(moat: &[Pos], min_x: i64, min_y: i64, max_x: i64, max_y: i64) -> Pos {
-    (min_x..=max_x)
-        .flat_map(|x| (min_y..=max_y).map(move |y| (x, y)))
-        .find(|p| is_inside(moat, *p))
-        .unwrap()
+/// Cubes in the lagoon, the trench and what it encloses. By Pick's theorem
+/// the area within the middle of the trench is `inside + length / 2 - 1`.
+fn lagoon_size(corners: &[Pos], length: i64) -> Option<i64> {
+    let twice_area = corners
+        .windows(2)
+        .try_fold(0i64, |sum, w| {
+            let ((x0, y0), (x1, y1)) = (w[0], w[1]);
+            sum.checked_add(x0.checked_mul(y1)?.checked_sub(x1.checked_mul(y0)?)?)
+        })?
+        .checked_abs()?;
+    (twice_area.checked_add(length)? / 2).checked_add(1)
 }
 
-pub fn part_2(input: &str) -> usize {
-    let (moat, moat_set) = build_moat_part2(input);
-    let min_x = moat.iter().map(|p| p.0).min().unwrap();
-    let max_x = moat.iter().map(|p| p.0).max().unwrap();
-    let min_y = moat.iter().map(|p| p.1).min().unwrap();
-    let max_y = moat.iter().map(|p| p.1).max().unwrap();
-    let inside = find_inside(&moat, min_x, min_y, max_x, max_y);
-    
-    0
+fn lagoon_size_part2((corners, length): &(Vec<Pos>, i64)) -> Result<i64, Error> {
+    Ok(lagoon_size(corners, *length).or_overflow(18, "size of the lagoon")?)
+}
+
+pub fn try_part_2(input: &str) -> Result<i64, Error> {
+    lagoon_size_part2(&build_moat_part2(input)?)
+}
+
+pub fn part_2(input: &str) -> i64 {
+    try_part_2(input).unwrap()
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::day18::*;
-    use std::fs;
-    #[test]
-    fn example18_part1() {
-        let input = fs::read_to_string("input/example18").unwrap();
-        assert_eq!(part_1(&input), 62);
+/// The trench as part 1 and part 2 read the dig plan. Each part only needs
+/// its own half of an instruction, so errors wait for the part that reads it.
+pub struct DigPlan {
+    part_1: Result<Vec<Pos>, ParseError>,
+    part_2: Result<(Vec<Pos>, i64), Error>,
+}
+
+pub fn parse_dig_plan(input: &str) -> DigPlan {
+    DigPlan {
+        part_1: build_moat(input),
+        part_2: build_moat_part2(input),
     }
-    #[test]
-    fn day18_part1() {
-        let input = fs::read_to_string("input/day18").unwrap();
-        assert_eq!(part_1(&input), 92758);
+}
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    type Input = DigPlan;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        Ok(parse_dig_plan(input))
     }
-    #[test]
-    fn example18_part2() {
-        let input = fs::read_to_string("input/example18").unwrap();
-        assert_eq!(part_2(&input), 952408144115);
+    fn part_1(plan: &Self::Input) -> Result<Answer, Error> {
+        let moat = plan.part_1.as_ref().map_err(Clone::clone)?;
+        Ok(lagoon_size_part1(moat).into())
     }
-    #[test]
-    fn day18_part2() {
-        let input = fs::read_to_string("input/day18").unwrap();
-        assert_eq!(part_2(&input), 0);
+    fn part_2(plan: &Self::Input) -> Result<Answer, Error> {
+        let trench = plan.part_2.as_ref().map_err(Clone::clone)?;
+        Ok(lagoon_size_part2(trench)?.into())
     }
 }
@@ -1,4 +1,9 @@
-use std::collections::HashMap;
+use crate::{
+    checked::{CheckedIter, OrOverflow},
+    error::{Overflow, Source},
+    Answer, Error, ParseError, Solution,
+};
+use std::collections::{HashMap, HashSet};
 enum Op {
     Less(i64),
     More(i64),
@@ -21,43 +26,101 @@ enum Rule {
 }
 
 impl Rule {
-    fn from_str(s: &str) -> Self {
+    fn from_str(src: &Source, s: &str) -> Result<Self, ParseError> {
         //"m>2090"
         let bytes = s.as_bytes();
-        let n: i64 = s[2..].parse().unwrap();
-        match (bytes[0], bytes[1]) {
-            (b'x', b'<') => Rule::X(Op::Less(n)),
-            (b'x', b'>') => Rule::X(Op::More(n)),
-            (b'm', b'<') => Rule::M(Op::Less(n)),
-            (b'm', b'>') => Rule::M(Op::More(n)),
-            (b'a', b'<') => Rule::A(Op::Less(n)),
-            (b'a', b'>') => Rule::A(Op::More(n)),
-            (b's', b'<') => Rule::S(Op::Less(n)),
-            (b's', b'>') => Rule::S(Op::More(n)),
-            _ => panic!(),
+        if bytes.len() < 3 || !s.is_char_boundary(2) {
+            return Err(src.error(s, "a condition like `m>2090`"));
+        }
+        let n: i64 = src.parse(&s[2..], "a number")?;
+        let op = match bytes[1] {
+            b'<' => Op::Less(n),
+            b'>' => Op::More(n),
+            _ => return Err(src.error(&s[1..], "`<` or `>`")),
+        };
+        match bytes[0] {
+            b'x' => Ok(Rule::X(op)),
+            b'm' => Ok(Rule::M(op)),
+            b'a' => Ok(Rule::A(op)),
+            b's' => Ok(Rule::S(op)),
+            _ => Err(src.error(s, "`x`, `m`, `a` or `s`")),
         }
     }
 }
 
-fn parse_workflows(s: &str) -> HashMap<&str, Vec<(Rule, &str)>> {
+type Workflows = HashMap<String, Vec<(Rule, String)>>;
+/// Workflows still pointing into the input, so errors can be located.
+type RawWorkflows<'a> = HashMap<&'a str, Vec<(Rule, &'a str)>>;
+
+fn parse_workflows(src: &Source, s: &str) -> Result<Workflows, ParseError> {
     //px{a<2006:qkq,m>2090:A,rfg}
-    let mut res = HashMap::new();
+    let mut res: RawWorkflows = HashMap::new();
     for l in s.lines() {
-        let (key, rules) = l.split_once('{').unwrap();
-        let rules = rules
-            .trim_end_matches('}')
+        let (key, rules) = src.split_once(l, "{")?;
+        let rules: Vec<_> = src
+            .strip_suffix(rules, "}")?
             .split(',')
             .map(|split| {
                 if let Some((op, target)) = split.split_once(':') {
-                    (Rule::from_str(op), target)
+                    Ok((Rule::from_str(src, op)?, target))
                 } else {
-                    (Rule::Else, split)
+                    Ok((Rule::Else, split))
                 }
             })
-            .collect();
-        res.insert(key.into(), rules);
+            .collect::<Result<_, _>>()?;
+        if !matches!(rules.last(), Some((Rule::Else, _))) {
+            return Err(src.error(&l[l.len() - 1..], "`,` and a rule without a condition"));
+        }
+        res.insert(key, rules);
+    }
+    for (_, target) in res.values().flatten() {
+        if !matches!(*target, "A" | "R") && !res.contains_key(target) {
+            return Err(src.error(target, "`A`, `R` or a workflow name"));
+        }
+    }
+    if !res.contains_key("in") {
+        return Err(src.error(src.end_of(s), "a workflow named `in`"));
+    }
+    let mut done = HashSet::new();
+    for key in s.lines().filter_map(|l| l.split_once('{')) {
+        check_acyclic(src, &res, key.0, &mut vec![], &mut done)?;
+    }
+    Ok(res
+        .into_iter()
+        .map(|(key, rules)| {
+            let rules = rules
+                .into_iter()
+                .map(|(rule, target)| (rule, target.to_owned()))
+                .collect();
+            (key.to_owned(), rules)
+        })
+        .collect())
+}
+
+/// Parts sent around in circles would never be accepted or rejected.
+fn check_acyclic<'a>(
+    src: &Source,
+    flow: &RawWorkflows<'a>,
+    pos: &'a str,
+    path: &mut Vec<&'a str>,
+    done: &mut HashSet<&'a str>,
+) -> Result<(), ParseError> {
+    if done.contains(pos) {
+        return Ok(());
     }
-    res
+    path.push(pos);
+    for (_, target) in flow.get(pos).into_iter().flatten() {
+        if path.contains(target) {
+            return Err(src.error(
+                target,
+                format!("a workflow that does not lead back to `{pos}`"),
+            ));
+        }
+        check_acyclic(src, flow, target, path, done)?;
+    }
+    path.pop();
+    done.insert(pos);
+    Ok(())
 }
 
 struct Tool {
@@ -68,21 +131,21 @@ struct Tool {
 }
 
 impl Tool {
-    fn from_str(s: &str) -> Self {
+    fn from_str(src: &Source, s: &str) -> Result<Self, ParseError> {
         //{x=787,m=2655,a=1222,s=2876}
-        let (x, rest) = s.trim_start_matches("{x=").split_once(',').unwrap();
-        let (m, rest) = rest.trim_start_matches("m=").split_once(',').unwrap();
-        let (a, rest) = rest.trim_start_matches("a=").split_once(',').unwrap();
-        let s = rest.trim_start_matches("s=").trim_end_matches('}');
-        Self {
-            x: x.parse().unwrap(),
-            m: m.parse().unwrap(),
-            a: a.parse().unwrap(),
-            s: s.parse().unwrap(),
-        }
+        let (x, rest) = src.split_once(src.strip_prefix(s, "{x=")?, ",")?;
+        let (m, rest) = src.split_once(src.strip_prefix(rest, "m=")?, ",")?;
+        let (a, rest) = src.split_once(src.strip_prefix(rest, "a=")?, ",")?;
+        let s = src.strip_suffix(src.strip_prefix(rest, "s=")?, "}")?;
+        Ok(Self {
+            x: src.parse(x, "a rating")?,
+            m: src.parse(m, "a rating")?,
+            a: src.parse(a, "a rating")?,
+            s: src.parse(s, "a rating")?,
+        })
     }
-    fn rating(&self) -> i64 {
-        self.x + self.m + self.a + self.s
+    fn rating(&self) -> Option<i64> {
+        [self.x, self.m, self.a, self.s].into_iter().checked_sum()
     }
     fn check(&self, rule: &Rule) -> bool {
         match rule {
@@ -94,18 +157,18 @@ impl Tool {
         }
     }
 }
-fn parse_tools(s: &str) -> Vec<Tool> {
-    s.lines().map(Tool::from_str).collect()
+fn parse_tools(src: &Source, s: &str) -> Result<Vec<Tool>, ParseError> {
+    s.lines().map(|l| Tool::from_str(src, l)).collect()
 }
 
-fn rate_tool(t: &Tool, flow: &HashMap<&str, Vec<(Rule, &str)>>) -> i64 {
+fn rate_tool(t: &Tool, flow: &Workflows) -> Option<i64> {
     let mut pos = "in";
     loop {
         if pos == "A" {
             return t.rating();
         }
         if pos == "R" {
-            return 0;
+            return Some(0);
         }
         let rules = flow.get(pos).unwrap();
         let target = &rules.iter().find(|r| t.check(&r.0)).unwrap().1;
@@ -113,15 +176,42 @@ fn rate_tool(t: &Tool, flow: &HashMap<&str, Vec<(Rule, &str)>>) -> i64 {
     }
 }
 
-fn rate_tools(tools: Vec<Tool>, workflows: HashMap<&str, Vec<(Rule, &str)>>) -> i64 {
-    tools.iter().map(|t| rate_tool(t, &workflows)).sum()
+fn rate_tools(tools: &[Tool], workflows: &Workflows) -> Result<i64, Overflow> {
+    tools
+        .iter()
+        .map(|t| rate_tool(t, workflows))
+        .collect::<Option<Vec<_>>>()
+        .and_then(|ratings| ratings.into_iter().checked_sum())
+        .or_overflow(19, "sum of ratings")
+}
+
+/// The workflows and the parts to sort. Part 2 only needs the workflows, so
+/// errors in the parts wait for part 1.
+pub struct System {
+    workflows: Workflows,
+    tools: Result<Vec<Tool>, ParseError>,
+}
+
+pub fn parse_system(input: &str) -> Result<System, ParseError> {
+    let src = Source::new(19, input);
+    let (workflows, tools) = src.split_once(input, "\n\n")?;
+    Ok(System {
+        workflows: parse_workflows(&src, workflows)?,
+        tools: parse_tools(&src, tools),
+    })
+}
+
+fn sum_of_ratings(system: &System) -> Result<i64, Error> {
+    let tools = system.tools.as_ref().map_err(Clone::clone)?;
+    Ok(rate_tools(tools, &system.workflows)?)
+}
+
+pub fn try_part_1(input: &str) -> Result<i64, Error> {
+    sum_of_ratings(&parse_system(input)?)
 }
 
 pub fn part_1(input: &str) -> i64 {
-    let (workflows, tools) = input.split_once("\n\n").unwrap();
-    let workflows = parse_workflows(workflows);
-    let tools = parse_tools(tools);
-    rate_tools(tools, workflows)
+    try_part_1(input).unwrap()
 }
 ///////////////////// Part 2
 type MatchingAndRemainder = (Option<(i64, i64)>, Option<(i64, i64)>);
@@ -171,23 +261,25 @@ impl ToolRange {
     }
 }
 
-fn count_valid_ranges(
-    mut tr: ToolRange,
-    pos: &str,
-    flow: &HashMap<&str, Vec<(Rule, &str)>>,
-) -> i64 {
+fn count_valid_ranges(mut tr: ToolRange, pos: &str, flow: &Workflows) -> Result<i64, Overflow> {
     if pos == "A" {
-        let size = |(s, e)| 1 + e - s;
-        return size(tr.x) * size(tr.m) * size(tr.a) * size(tr.s);
+        return [tr.x, tr.m, tr.a, tr.s]
+            .into_iter()
+            .try_fold(1i64, |product, (s, e)| {
+                product.checked_mul(e.checked_sub(s)?.checked_add(1)?)
+            })
+            .or_overflow(19, "number of combinations");
     }
     if pos == "R" {
-        return 0;
+        return Ok(0);
     }
-    let mut sum = 0;
+    let mut sum: i64 = 0;
     for (rule, target) in flow.get(pos).unwrap().iter() {
         let (sub_tr, complement) = tr.apply_rule(rule);
         if let Some(sub_tr) = sub_tr {
-            sum += count_valid_ranges(sub_tr, target, flow);
+            sum = sum
+                .checked_add(count_valid_ranges(sub_tr, target, flow)?)
+                .or_overflow(19, "number of combinations")?;
         }
         if let Some(complement) = complement {
             tr = complement;
@@ -195,47 +287,57 @@ fn count_valid_ranges(
             break;
         }
     }
-    sum
+    Ok(sum)
 }
 
-pub fn part_2(input: &str) -> i64 {
-    let (workflows, _tools) = input.split_once("\n\n").unwrap();
-    let workflows = parse_workflows(workflows);
+fn combinations(system: &System) -> Result<i64, Overflow> {
     let full_range = ToolRange {
         x: (1, 4000),
         m: (1, 4000),
         a: (1, 4000),
         s: (1, 4000),
     };
-    count_valid_ranges(full_range, "in", &workflows)
+    count_valid_ranges(full_range, "in", &system.workflows)
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::day19::*;
-    use std::fs;
+pub fn try_part_2(input: &str) -> Result<i64, Error> {
+    Ok(combinations(&parse_system(input)?)?)
+}
 
-    #[test]
-    fn example19_part1() {
-        let input = fs::read_to_string("input/example19").unwrap();
-        assert_eq!(part_1(&input), 19114);
-    }
+pub fn part_2(input: &str) -> i64 {
+    try_part_2(input).unwrap()
+}
 
-    #[test]
-    fn day19_part1() {
-        let input = fs::read_to_string("input/day19").unwrap();
-        assert_eq!(part_1(&input), 495298);
-    }
+pub struct Day19;
 
-    #[test]
-    fn example19_part2() {
-        let input = fs::read_to_string("input/example19").unwrap();
-        assert_eq!(part_2(&input), 167409079868000);
+impl Solution for Day19 {
+    type Input = System;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_system(input)
+    }
+    fn part_1(system: &Self::Input) -> Result<Answer, Error> {
+        Ok(sum_of_ratings(system)?.into())
     }
+    fn part_2(system: &Self::Input) -> Result<Answer, Error> {
+        Ok(combinations(system)?.into())
+    }
+}
 
+#[cfg(test)]
+mod tests {
+    use crate::day19::*;
     #[test]
-    fn day19_part2() {
-        let input = fs::read_to_string("input/day19").unwrap();
-        assert_eq!(part_2(&input), 132186256794011);
+    fn workflows_without_end() {
+        let tools = "\n\n{x=1,m=2,a=3,s=4}\n";
+        let err = try_part_1(&format!("in{{a<2006:A,m>90:R}}{tools}")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day19:1:19: expected `,` and a rule without a condition, found `}`"
+        );
+        let err = try_part_2(&format!("in{{a<2006:px,R}}\npx{{x>5:in,A}}{tools}")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day19:2:8: expected a workflow that does not lead back to `px`, found `in`"
+        );
     }
 }
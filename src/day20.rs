@@ -1,5 +1,12 @@
+use crate::{
+    checked::OrOverflow,
+    debug,
+    error::{NoAnswer, Overflow, Source},
+    warn, Answer, Error, ParseError, Solution,
+};
+use num::Integer;
 use std::{
-    collections::{HashMap, HashSet, VecDeque},
+    collections::{HashMap, VecDeque},
     vec,
 };
 
@@ -9,15 +16,15 @@ enum Pulse {
     High,
 }
 type Id = usize;
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum Type {
     FlipFlop(Pulse),
     Conj(Vec<(Id, Pulse)>),
     Broadcaster,
 }
 
-#[derive(Debug)]
-struct Module {
+#[derive(Debug, Clone)]
+pub struct Module {
     type_: Type,
     destinations: Vec<Id>,
 }
@@ -50,33 +57,56 @@ impl Module {
     }
 }
 
-fn parse_modules(s: &str) -> Vec<Module> {
-    let mut modules = vec![];
-    let id_map: HashMap<&str, usize> = s
+#[derive(Debug, Clone)]
+pub struct Network {
+    modules: Vec<Module>,
+    /// The name of each module, by id.
+    names: Vec<String>,
+    /// The modules that send pulses to `rx`.
+    to_rx: Vec<Id>,
+}
+
+fn parse_modules(s: &str) -> Result<Network, ParseError> {
+    let src = Source::new(20, s);
+    let mut modules = vec![Module {
+        type_: Type::Broadcaster,
+        destinations: vec![],
+    }];
+    let names = s
         .lines()
-        .map(|l| l.split_once(" ->").unwrap().0)
-        .filter(|name| {
-            if *name == "broadcaster" {
-                modules.push(Module {
-                    type_: Type::Broadcaster,
-                    destinations: vec![],
-                });
-                false
-            } else {
-                true
-            }
-        })
+        .map(|l| Ok(src.split_once(l, " -> ")?.0))
+        .collect::<Result<Vec<_>, _>>()?;
+    if !names.contains(&"broadcaster") {
+        return Err(src.error(src.end_of(s), "a `broadcaster` module"));
+    }
+    let id_map: HashMap<&str, usize> = names
+        .iter()
+        .filter(|name| **name != "broadcaster")
         .enumerate()
-        .map(|(i, name)| (&name[1..], i + 1))
-        .collect();
+        .map(|(i, name)| match name.strip_prefix(['%', '&']) {
+            Some(name) => Ok((name, i + 1)),
+            None => Err(src.error(name, "`%`, `&` or `broadcaster`")),
+        })
+        .collect::<Result<_, _>>()?;
+    let mut names = vec!["broadcaster".to_owned()];
+    let mut to_rx = vec![];
     for line in s.lines() {
         let (module, destinations) = line.split_once(" -> ").unwrap();
+        let id = if module == "broadcaster" {
+            0
+        } else {
+            modules.len()
+        };
+        if destinations.split(", ").any(|name| name == "rx") {
+            to_rx.push(id);
+        }
         let destinations = destinations
             .split(", ")
-            .map(|name| *id_map.get(name).unwrap_or(&0))
-            .inspect(|id| {
-                if *id == 0 {
-                    println!("untyped!");
+            .map(|name| match id_map.get(name) {
+                Some(id) => *id,
+                None => {
+                    warn!("`{name}` is not a module, pulses to it are dropped");
+                    0
                 }
             })
             .collect();
@@ -84,6 +114,7 @@ fn parse_modules(s: &str) -> Vec<Module> {
             modules[0].destinations = destinations;
             continue;
         }
+        names.push(module[1..].to_owned());
         let m = match module.as_bytes()[0] {
             b'%' => Module {
                 type_: Type::FlipFlop(Pulse::Low),
@@ -93,7 +124,7 @@ fn parse_modules(s: &str) -> Vec<Module> {
                 type_: Type::Conj(vec![]),
                 destinations,
             },
-            _ => panic!(),
+            _ => unreachable!(),
         };
         modules.push(m);
     }
@@ -104,7 +135,11 @@ fn parse_modules(s: &str) -> Vec<Module> {
             }
         }
     }
-    modules
+    Ok(Network {
+        modules,
+        names,
+        to_rx,
+    })
 }
 
 fn button_press(modules: &mut [Module]) -> (usize, usize) {
@@ -130,23 +165,53 @@ fn button_press(modules: &mut [Module]) -> (usize, usize) {
     (low_cnt, high_cnt)
 }
 
-pub fn part_1(input: &str) -> usize {
-    let mut modules = parse_modules(input);
-    let mut low = 0;
-    let mut high = 0;
+fn pulse_product(mut modules: Vec<Module>) -> Result<usize, Overflow> {
+    let mut low: usize = 0;
+    let mut high: usize = 0;
     for _ in 0..1000 {
         let (l, h) = button_press(&mut modules);
-        low += l;
-        high += h;
+        low = low.checked_add(l).or_overflow(20, "count of low pulses")?;
+        high = high
+            .checked_add(h)
+            .or_overflow(20, "count of high pulses")?;
     }
-    low * high
+    low.checked_mul(high)
+        .or_overflow(20, "product of pulse counts")
+}
+
+pub fn try_part_1(input: &str) -> Result<usize, Error> {
+    Ok(pulse_product(parse_modules(input)?.modules)?)
+}
+
+pub fn part_1(input: &str) -> usize {
+    try_part_1(input).unwrap()
 }
 
-fn button_press_part2(modules: &mut [Module], press: usize) -> bool {
+/// Presses tried before giving up on `rx`.
+const MAX_PRESSES: usize = 10_000;
+
+/// Presses the button once, noting in `highs` the first two presses on which
+/// each input of `feeder` sends it a high pulse. Returns whether `rx` got a
+/// low pulse.
+fn button_press_part2(
+    network: &mut Network,
+    feeder: Option<Id>,
+    press: usize,
+    highs: &mut HashMap<Id, Vec<usize>>,
+) -> bool {
+    let mut rx_low = false;
     let mut events: VecDeque<_> = [(0, 0, Pulse::Low)].into();
     while let Some((source, dest, pulse)) = events.pop_front() {
-        let module = &mut modules[dest];
+        if Some(dest) == feeder && matches!(pulse, Pulse::High) {
+            let presses = highs.entry(source).or_default();
+            if presses.len() < 2 && presses.last() != Some(&press) {
+                debug!("`{}` went high on press {press}", network.names[source]);
+                presses.push(press);
+            }
+        }
+        let module = &mut network.modules[dest];
         if let Some(out) = module.react(source, pulse) {
+            rx_low |= matches!(out, Pulse::Low) && network.to_rx.contains(&dest);
             events.extend(
                 module
                     .destinations
@@ -155,73 +220,100 @@ fn button_press_part2(modules: &mut [Module], press: usize) -> bool {
                     .map(|d| (dest, *d, out)),
             );
         }
-        let Type::Conj(mem) = &modules[4].type_ else{
-            unreachable!()
-        };
-        if matches!(mem[0].1, Pulse::High) {
-            println!("1:{press}");
-        }
-
-        if matches!(mem[1].1, Pulse::High) {
-            println!("2:{press}");
-        }
+    }
+    rx_low
+}
 
-        if matches!(mem[2].1, Pulse::High) {
-            println!("3:{press}");
+/// `rx` hangs off one conjunction, whose inputs each go high every so many
+/// presses. It sends a low pulse once they all do on the same press, at the
+/// least common multiple of their periods.
+fn presses_until_rx(mut network: Network) -> Result<usize, Error> {
+    let no_answer = |why: String| NoAnswer { day: 20, why };
+    if network.to_rx.is_empty() {
+        return Err(no_answer("no module sends pulses to `rx`".to_owned()).into());
+    }
+    let feeder = match network.to_rx[..] {
+        [id] => Some(id),
+        _ => None,
+    };
+    let inputs: Vec<Id> = match feeder.map(|id| &network.modules[id].type_) {
+        Some(Type::Conj(mem)) => mem.iter().map(|m| m.0).collect(),
+        _ => vec![],
+    };
+    let feeder = feeder.filter(|_| !inputs.is_empty());
+    let mut highs = HashMap::new();
+    for press in 1..=MAX_PRESSES {
+        if button_press_part2(&mut network, feeder, press, &mut highs) {
+            return Ok(press);
         }
-
-        if matches!(mem[3].1, Pulse::High) {
-            println!("4:{press}");
+        if feeder.is_some()
+            && inputs
+                .iter()
+                .all(|i| highs.get(i).is_some_and(|p| p.len() == 2))
+        {
+            break;
         }
     }
+    if feeder.is_none() {
+        return Err(no_answer(format!(
+            "`rx` got no low pulse in {MAX_PRESSES} presses and is not fed by one conjunction"
+        ))
+        .into());
+    }
+    let mut presses: usize = 1;
+    for input in inputs {
+        let period = match highs.get(&input).map(Vec::as_slice) {
+            Some(&[first, second]) if second == 2 * first => first,
+            _ => {
+                return Err(no_answer(format!(
+                    "`{}` does not go high every so many presses from the first, \
+                     within {MAX_PRESSES} presses",
+                    network.names[input]
+                ))
+                .into())
+            }
+        };
+        presses = (presses / presses.gcd(&period))
+            .checked_mul(period)
+            .or_overflow(20, "least common multiple of periods")?;
+    }
+    Ok(presses)
+}
 
-    false
+pub fn try_part_2(input: &str) -> Result<usize, Error> {
+    presses_until_rx(parse_modules(input)?)
 }
 
 pub fn part_2(input: &str) -> usize {
-    let mut modules = parse_modules(input);
-    for i in 1..5_000 {
-        if button_press_part2(&mut modules, i) {
-            return i;
-        }
+    try_part_2(input).unwrap()
+}
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    type Input = Network;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_modules(input)
+    }
+    fn part_1(network: &Self::Input) -> Result<Answer, Error> {
+        Ok(pulse_product(network.modules.clone())?.into())
+    }
+    fn part_2(network: &Self::Input) -> Result<Answer, Error> {
+        Ok(presses_until_rx(network.clone())?.into())
     }
-    0
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day20::*;
-    use std::fs;
-    #[test]
-    fn example20_part1() {
-        let input = fs::read_to_string("input/example20").unwrap();
-        assert_eq!(part_1(&input), 32000000);
-    }
-    #[test]
-    fn day20_part1() {
-        let input = fs::read_to_string("input/day20").unwrap();
-        assert_eq!(part_1(&input), 743871576);
-    }
-    #[test]
-    fn example20_part2() {
-        let input = fs::read_to_string("input/example20").unwrap();
-        assert_eq!(part_2(&input), 0);
-    }
     #[test]
-    fn day20_part2() {
-        let input = fs::read_to_string("input/day20").unwrap();
-        assert_eq!(part_2(&input), 0);
-
-        // periods
-        // 3797
-        // 3907
-        // 4021
-        // 4093
-        use num::integer::lcm;
-        let ans = [3797usize, 3907, 4021, 4093]
-            .into_iter()
-            .reduce(lcm)
-            .unwrap();
-        assert_eq!(ans, 244151741342687);
+    fn rx_without_cycles() {
+        assert_eq!(try_part_2("broadcaster -> a\n%a -> b\n&b -> rx\n"), Ok(1));
+        assert_eq!(
+            try_part_2("broadcaster -> a\n%a -> b\n&b -> c\n%c -> a\n")
+                .unwrap_err()
+                .to_string(),
+            "day20: no answer, no module sends pulses to `rx`"
+        );
     }
 }
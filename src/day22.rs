@@ -1,28 +1,45 @@
+use crate::{
+    checked::{CheckedIter, OrOverflow},
+    error::{Overflow, Source},
+    Answer, Error, ParseError, Solution,
+};
 use itertools::Itertools;
 use std::collections::{HashMap, HashSet, VecDeque};
 
 type Range = (i32, i32);
-struct Block {
+#[derive(Clone)]
+pub struct Block {
     x_range: Range,
     y_range: Range,
     z_range: Range,
 }
 
 impl Block {
-    fn from_str(line: &str) -> Option<Self> {
+    fn from_str(src: &Source, line: &str) -> Result<Self, ParseError> {
         // 1,0,1~1,2,1
-        let (start, end) = line.split_once('~')?;
-        let mut start_it = start.split(',').map_while(|n| n.parse().ok());
-        let mut end_it = end.split(',').map_while(|n| n.parse().ok());
-        Some(Self {
-            x_range: (start_it.next()?, end_it.next()?),
-            y_range: (start_it.next()?, end_it.next()?),
-            z_range: (start_it.next()?, end_it.next()?),
+        let (start, end) = src.split_once(line, "~")?;
+        let corner = |s: &str| -> Result<[i32; 3], ParseError> {
+            let (x, rest) = src.split_once(s, ",")?;
+            let (y, z) = src.split_once(rest, ",")?;
+            let coord = |n| src.parse(n, "a coordinate");
+            Ok([coord(x)?, coord(y)?, coord(z)?])
+        };
+        let [x0, y0, z0] = corner(start)?;
+        let [x1, y1, z1] = corner(end)?;
+        if x1 < x0 || y1 < y0 || z1 < z0 {
+            return Err(src.error(end, "a corner with no coordinate below the first"));
+        }
+        Ok(Self {
+            x_range: (x0, x1),
+            y_range: (y0, y1),
+            z_range: (z0, z1),
         })
     }
 }
 
-fn place_blocks_n_map_supports(mut blocks: Vec<Block>) -> HashMap<usize, Vec<usize>> {
+fn place_blocks_n_map_supports(
+    mut blocks: Vec<Block>,
+) -> Result<HashMap<usize, Vec<usize>>, Overflow> {
     let mut max_height: HashMap<(i32, i32), (i32, usize)> = HashMap::new();
     let mut supported_by = HashMap::new();
     blocks.sort_by_key(|b| b.z_range.0);
@@ -48,17 +65,26 @@ fn place_blocks_n_map_supports(mut blocks: Vec<Block>) -> HashMap<usize, Vec<usi
         supports.dedup();
         supported_by.insert(block_id, supports);
         // fill in new max heights
-        let new_height = z_max + b.z_range.1 - b.z_range.0 + 1;
+        let new_height = b
+            .z_range
+            .1
+            .checked_sub(b.z_range.0)
+            .and_then(|h| h.checked_add(z_max)?.checked_add(1))
+            .or_overflow(22, "height of the stack")?;
         for xy in xy_range {
             max_height.insert(xy, (new_height, block_id));
         }
     }
-    supported_by
+    Ok(supported_by)
 }
 
-pub fn part_1(input: &str) -> usize {
-    let blocks: Option<Vec<Block>> = input.lines().map(Block::from_str).collect();
-    let supported_by = place_blocks_n_map_supports(blocks.unwrap());
+fn parse_blocks(input: &str) -> Result<Vec<Block>, ParseError> {
+    let src = Source::new(22, input);
+    src.lines().map(|l| Block::from_str(&src, l)).collect()
+}
+
+fn count_safe_to_remove(blocks: Vec<Block>) -> Result<usize, Overflow> {
+    let supported_by = place_blocks_n_map_supports(blocks)?;
     let single_supporters: HashSet<usize> = supported_by
         .values()
         .filter_map(|supports| {
@@ -69,7 +95,15 @@ pub fn part_1(input: &str) -> usize {
             }
         })
         .collect();
-    supported_by.len() - single_supporters.len()
+    Ok(supported_by.len() - single_supporters.len())
+}
+
+pub fn try_part_1(input: &str) -> Result<usize, Error> {
+    Ok(count_safe_to_remove(parse_blocks(input)?)?)
+}
+
+pub fn part_1(input: &str) -> usize {
+    try_part_1(input).unwrap()
 }
 
 fn calculate_falls(
@@ -93,11 +127,9 @@ fn calculate_falls(
     fallen.len() - 1
 }
 
-pub fn part_2(input: &str) -> usize {
-    let blocks: Option<Vec<_>> = input.lines().map(Block::from_str).collect();
-    let blocks = blocks.unwrap();
+fn sum_chain_reactions(blocks: Vec<Block>) -> Result<usize, Overflow> {
     let n_blocks = blocks.len();
-    let supported_by = place_blocks_n_map_supports(blocks);
+    let supported_by = place_blocks_n_map_supports(blocks)?;
     let mut supports_blocks: HashMap<usize, Vec<usize>> = HashMap::new();
     for (k, v) in supported_by.iter() {
         for supporter in v {
@@ -106,31 +138,42 @@ pub fn part_2(input: &str) -> usize {
     }
     (0..n_blocks)
         .map(|i| calculate_falls(i, &supported_by, &supports_blocks))
-        .sum()
+        .checked_sum()
+        .or_overflow(22, "sum of falling bricks")
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::day22::*;
-    use std::fs;
-    #[test]
-    fn example22_part1() {
-        let input = fs::read_to_string("input/example22").unwrap();
-        assert_eq!(part_1(&input), 5);
+pub fn try_part_2(input: &str) -> Result<usize, Error> {
+    Ok(sum_chain_reactions(parse_blocks(input)?)?)
+}
+
+pub fn part_2(input: &str) -> usize {
+    try_part_2(input).unwrap()
+}
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    type Input = Vec<Block>;
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_blocks(input)
     }
-    #[test]
-    fn day22_part1() {
-        let input = fs::read_to_string("input/day22").unwrap();
-        assert_eq!(part_1(&input), 416);
+    fn part_1(blocks: &Self::Input) -> Result<Answer, Error> {
+        Ok(count_safe_to_remove(blocks.clone())?.into())
     }
-    #[test]
-    fn example22_part2() {
-        let input = fs::read_to_string("input/example22").unwrap();
-        assert_eq!(part_2(&input), 7);
+    fn part_2(blocks: &Self::Input) -> Result<Answer, Error> {
+        Ok(sum_chain_reactions(blocks.clone())?.into())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day22::*;
     #[test]
-    fn day22_part2() {
-        let input = fs::read_to_string("input/day22").unwrap();
-        assert_eq!(part_2(&input), 60963);
+    fn corners_out_of_order() {
+        assert_eq!(try_part_1("1,0,1~1,2,1\n0,0,2~2,0,2\n"), Ok(1));
+        assert_eq!(
+            try_part_1("1,2,1~1,0,1\n").unwrap_err().to_string(),
+            "day22:1:7: expected a corner with no coordinate below the first, found `1,0,1`"
+        );
     }
 }
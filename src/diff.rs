@@ -0,0 +1,125 @@
+use crate::{
+    generate::{self, Generated},
+    rng::Rng,
+    variants, Answer, Entry, Part,
+};
+use std::{fmt, panic};
+
+/// What each variant answered, or why it did not.
+type Outcomes = Vec<(String, Result<Answer, String>)>;
+
+/// An input on which the variants of a day disagree.
+#[derive(Debug)]
+pub struct Mismatch {
+    pub day: u8,
+    pub part: Part,
+    pub input: String,
+    pub outcomes: Outcomes,
+    /// Seed of the generated input this one was shrunk from.
+    pub seed: u64,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "day{:02} {:?} disagrees, shrunk from seed {}:",
+            self.day, self.part, self.seed
+        )?;
+        for (name, outcome) in &self.outcomes {
+            match outcome {
+                Ok(answer) => writeln!(f, "  {name}: {answer}")?,
+                Err(e) => writeln!(f, "  {name}: {e}")?,
+            }
+        }
+        write!(f, "input:\n{}", self.input)
+    }
+}
+
+fn outcomes(entries: &[Entry], input: &str, part: Part) -> Outcomes {
+    entries
+        .iter()
+        .map(|entry| {
+            let outcome = panic::catch_unwind(|| entry.run(input, part))
+                .map_err(|_| "panicked".to_owned())
+                .and_then(|res| res.map_err(|e| e.to_string()));
+            (entry.name(), outcome)
+        })
+        .collect()
+}
+
+fn agree(outcomes: &Outcomes) -> bool {
+    outcomes.windows(2).all(|w| w[0].1 == w[1].1)
+}
+
+/// Keeps replacing `input` with the first of its shrinks that still `fails`,
+/// until none does.
+pub fn shrink<G: Generated>(mut input: G, fails: impl Fn(&G) -> bool) -> G {
+    while let Some(smaller) = input.shrink().into_iter().find(|s| fails(s)) {
+        input = smaller;
+    }
+    input
+}
+
+fn check<G: Generated>(
+    day: u8,
+    generate: fn(&mut Rng) -> G,
+    cases: usize,
+    seed: u64,
+) -> Result<(), Mismatch> {
+    let entries = variants(day);
+    for case in 0..cases as u64 {
+        let case_seed = seed.wrapping_add(case);
+        let input = generate(&mut Rng::new(case_seed));
+        for part in [Part::One, Part::Two] {
+            let fails = |input: &G| !agree(&outcomes(&entries, &input.to_string(), part));
+            if fails(&input) {
+                let input = shrink(input, fails).to_string();
+                return Err(Mismatch {
+                    day,
+                    part,
+                    outcomes: outcomes(&entries, &input, part),
+                    input,
+                    seed: case_seed,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Runs every variant of `day` on `cases` generated inputs, the first made
+/// from `seed` and the rest from the seeds after it. `None` if there is no
+/// generator for the day.
+pub fn differential(day: u8, cases: usize, seed: u64) -> Option<Result<(), Mismatch>> {
+    match day {
+        8 => Some(check(day, generate::day08, cases, seed)),
+        9 => Some(check(day, generate::day09, cases, seed)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{days, diff::*, generate::Histories};
+    #[test]
+    fn variants_agree() {
+        for day in days() {
+            if variants(day).len() < 2 {
+                continue;
+            }
+            match differential(day, 200, 2023) {
+                None => panic!("day{day:02} has variants but no generator"),
+                Some(Err(mismatch)) => panic!("{mismatch}"),
+                Some(Ok(())) => (),
+            }
+        }
+    }
+    #[test]
+    fn shrinks_to_smallest_failure() {
+        let input = Histories(vec![vec![1, 2, 30], vec![-4, 9, 17, 12]]);
+        let fails = |h: &Histories| h.0.iter().flatten().any(|v| *v > 10);
+        let shrunk = shrink(input, fails);
+        assert_eq!(shrunk, Histories(vec![vec![11]]));
+    }
+}
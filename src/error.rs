@@ -0,0 +1,229 @@
+use std::{error, fmt, io, str::FromStr};
+
+/// Where and why an input could not be parsed. Lines and columns start at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u8,
+    pub line: usize,
+    pub column: usize,
+    pub expected: String,
+    pub found: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "day{:02}:{}:{}: expected {}, found {}",
+            self.day, self.line, self.column, self.expected, self.found
+        )
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// An intermediate value of a day's solution that does not fit its type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overflow {
+    pub day: u8,
+    /// What was being computed, e.g. "total score".
+    pub what: String,
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "day{:02}: {} overflowed", self.day, self.what)
+    }
+}
+
+impl error::Error for Overflow {}
+
+/// An input that parses, but has no answer, e.g. a pattern without a mirror.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoAnswer {
+    pub day: u8,
+    pub why: String,
+}
+
+impl fmt::Display for NoAnswer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "day{:02}: no answer, {}", self.day, self.why)
+    }
+}
+
+impl error::Error for NoAnswer {}
+
+/// Why a solution has no answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    Parse(ParseError),
+    Overflow(Overflow),
+    NoAnswer(NoAnswer),
+    /// Reading a streamed input failed, e.g. on invalid UTF-8.
+    Io(String),
+    /// The day is still the `aoc new` template.
+    Unsolved,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse(e) => write!(f, "{e}"),
+            Error::Overflow(e) => write!(f, "{e}"),
+            Error::NoAnswer(e) => write!(f, "{e}"),
+            Error::Io(e) => write!(f, "reading input: {e}"),
+            Error::Unsolved => write!(f, "not solved yet"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
+
+impl From<Overflow> for Error {
+    fn from(e: Overflow) -> Self {
+        Error::Overflow(e)
+    }
+}
+
+impl From<NoAnswer> for Error {
+    fn from(e: NoAnswer) -> Self {
+        Error::NoAnswer(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e.to_string())
+    }
+}
+
+/// The full text handed to a parser. Parsers keep slicing it with the usual
+/// `str` methods, and any slice of it can be turned back into a position.
+#[derive(Clone, Copy)]
+pub struct Source<'a> {
+    day: u8,
+    text: &'a str,
+    /// Where `text` starts in the input, for parts of a streamed input.
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Source<'a> {
+    pub fn new(day: u8, text: &'a str) -> Self {
+        Self::at(day, text, 1, 1)
+    }
+
+    /// A piece of a longer input that starts at `line` and `column` of it.
+    pub fn at(day: u8, text: &'a str, line: usize, column: usize) -> Self {
+        Self {
+            day,
+            text,
+            line,
+            column,
+        }
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    pub fn lines(&self) -> std::str::Lines<'a> {
+        self.text.lines()
+    }
+
+    /// `at` must be a slice of the source text, the error points at its start.
+    pub fn error(&self, at: &str, expected: impl Into<String>) -> ParseError {
+        let start = self.text.as_ptr() as usize;
+        let offset = (at.as_ptr() as usize)
+            .checked_sub(start)
+            .filter(|offset| *offset <= self.text.len())
+            .expect("error position outside of the source");
+        let before = &self.text[..offset];
+        let line = before.matches('\n').count() + self.line;
+        let column = match before.rfind('\n') {
+            Some(i) => before[i + 1..].chars().count() + 1,
+            None => before.chars().count() + self.column,
+        };
+        let found = match at.lines().next() {
+            Some(token) if !token.is_empty() => format!("`{token}`"),
+            _ if offset == self.text.len() => "end of input".to_owned(),
+            _ => "end of line".to_owned(),
+        };
+        ParseError {
+            day: self.day,
+            line,
+            column,
+            expected: expected.into(),
+            found,
+        }
+    }
+
+    /// An empty slice at the end of `s`, for errors about missing tokens.
+    pub fn end_of<'s>(&self, s: &'s str) -> &'s str {
+        &s[s.len()..]
+    }
+
+    pub fn parse<T: FromStr>(&self, token: &str, expected: &str) -> Result<T, ParseError> {
+        token.parse().map_err(|_| self.error(token, expected))
+    }
+
+    pub fn split_once<'s>(
+        &self,
+        s: &'s str,
+        delimiter: &str,
+    ) -> Result<(&'s str, &'s str), ParseError> {
+        s.split_once(delimiter)
+            .ok_or_else(|| self.error(s, format!("`{delimiter}`")))
+    }
+
+    pub fn strip_prefix<'s>(&self, s: &'s str, prefix: &str) -> Result<&'s str, ParseError> {
+        s.strip_prefix(prefix)
+            .ok_or_else(|| self.error(s, format!("`{prefix}`")))
+    }
+
+    pub fn strip_suffix<'s>(&self, s: &'s str, suffix: &str) -> Result<&'s str, ParseError> {
+        s.strip_suffix(suffix).ok_or_else(|| {
+            let last = s.char_indices().last().map_or(s.len(), |(i, _)| i);
+            self.error(&s[last..], format!("`{suffix}`"))
+        })
+    }
+
+    /// Every whitespace separated token of `s`, parsed as a number.
+    pub fn numbers<T: FromStr>(&self, s: &str) -> Result<Vec<T>, ParseError> {
+        s.split_whitespace()
+            .map(|n| self.parse(n, "a number"))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::*;
+    #[test]
+    fn error_points_at_token() {
+        let src = Source::new(2, "Game 1: 3 blue\nGame 2: 4 purple");
+        let token = src.lines().nth(1).unwrap().split_once(' ').unwrap().1;
+        let token = &token[5..];
+        let err = src.error(token, "a colour");
+        assert_eq!((err.line, err.column), (2, 11));
+        assert_eq!(
+            err.to_string(),
+            "day02:2:11: expected a colour, found `purple`"
+        );
+    }
+    #[test]
+    fn error_at_end() {
+        let src = Source::new(5, "seeds: 1 2");
+        let err = src.split_once(src.text(), "\n\n").unwrap_err();
+        assert_eq!(err.found, "`seeds: 1 2`");
+        let err = src.error(src.end_of(src.text()), "a map");
+        assert_eq!((err.line, err.column), (1, 11));
+        assert_eq!(err.found, "end of input");
+    }
+}
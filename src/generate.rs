@@ -0,0 +1,936 @@
+use crate::{
+    geometry::Dir,
+    grid::{Grid, Pos},
+    rng::Rng,
+};
+use std::{
+    collections::HashSet,
+    fmt::{self, Write},
+    iter,
+    ops::Range,
+};
+
+/// A generated puzzle input. It is kept structured rather than as text so that
+/// it can be shrunk, and displays as the text the day's parser reads.
+pub trait Generated: Clone + fmt::Display {
+    /// Smaller inputs that are still valid puzzle inputs, in no particular
+    /// order. Empty once the input cannot get any smaller.
+    fn shrink(&self) -> Vec<Self>;
+}
+
+/// Every copy of `items` with one element left out, keeping at least `min`.
+fn without_one<T: Clone>(items: &[T], min: usize) -> Vec<Vec<T>> {
+    if items.len() <= min {
+        return vec![];
+    }
+    (0..items.len())
+        .map(|i| [&items[..i], &items[i + 1..]].concat())
+        .collect()
+}
+
+/// Every copy of `items` with one element replaced by one of its shrinks.
+fn shrink_one<T: Clone>(items: &[T], shrink: impl Fn(&T) -> Vec<T>) -> Vec<Vec<T>> {
+    let mut res = vec![];
+    for (i, item) in items.iter().enumerate() {
+        for smaller in shrink(item) {
+            let mut copy = items.to_vec();
+            copy[i] = smaller;
+            res.push(copy);
+        }
+    }
+    res
+}
+
+fn shrink_number(n: i64) -> Vec<i64> {
+    match n {
+        0 => vec![],
+        _ if n.abs() == 1 => vec![0],
+        _ => vec![0, n / 2, n - n.signum()],
+    }
+}
+
+fn letter(rng: &mut Rng) -> char {
+    char::from(b'a' + rng.usize_in(0..26) as u8)
+}
+
+fn digit(rng: &mut Rng, digits: Range<usize>) -> char {
+    char::from(b'0' + rng.usize_in(digits) as u8)
+}
+
+/// `count` distinct lowercase names, none of them in `reserved`.
+fn names(rng: &mut Rng, count: usize, reserved: &[&str]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut res = vec![];
+    while res.len() < count {
+        let name: String = (0..rng.usize_in(2..4)).map(|_| letter(rng)).collect();
+        if !reserved.contains(&name.as_str()) && seen.insert(name.clone()) {
+            res.push(name);
+        }
+    }
+    res
+}
+
+/// A `width` by `height` grid of tiles, one row per line.
+fn tiles(
+    rng: &mut Rng,
+    width: usize,
+    height: usize,
+    mut tile: impl FnMut(&mut Rng) -> char,
+) -> String {
+    let mut grid = Grid::new(width, height, '.');
+    for pos in grid.positions().collect::<Vec<_>>() {
+        grid[pos] = tile(rng);
+    }
+    grid.to_string()
+}
+
+fn join(items: impl IntoIterator<Item = impl ToString>, sep: &str) -> String {
+    items
+        .into_iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+const DIGIT_WORDS: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+/// Lines of letters and spelled out digits, each with at least one real digit
+/// so that part 1 has a calibration value for it.
+pub fn day01(rng: &mut Rng) -> String {
+    let mut res = String::new();
+    for _ in 0..rng.usize_in(1..20) {
+        let digit_at = rng.usize_in(0..5);
+        for i in 0..5 {
+            if i == digit_at {
+                res.push(digit(rng, 1..10));
+            }
+            match rng.usize_in(0..3) {
+                0 => res += *rng.choose(&DIGIT_WORDS),
+                1 => res.push(letter(rng)),
+                _ => (),
+            }
+        }
+        res.push('\n');
+    }
+    res
+}
+
+pub fn day02(rng: &mut Rng) -> String {
+    let mut res = String::new();
+    for id in 1..=rng.usize_in(1..20) {
+        let reveals = (0..rng.usize_in(1..6)).map(|_| {
+            let mut colours = ["red", "green", "blue"];
+            rng.shuffle(&mut colours);
+            let shown = rng.usize_in(1..4);
+            join(
+                colours[..shown]
+                    .iter()
+                    .map(|colour| format!("{} {colour}", rng.usize_in(1..16))),
+                ", ",
+            )
+        });
+        writeln!(res, "Game {id}: {}", join(reveals, "; ")).unwrap();
+    }
+    res
+}
+
+const SYMBOLS: [char; 10] = ['*', '#', '+', '$', '/', '=', '%', '@', '&', '-'];
+
+/// An engine schematic with numbers of at most three digits.
+pub fn day03(rng: &mut Rng) -> String {
+    let (width, height) = (rng.usize_in(1..30), rng.usize_in(1..30));
+    let mut res = String::new();
+    for _ in 0..height {
+        let mut digits = 0;
+        for _ in 0..width {
+            if digits < 3 && rng.chance(0.3) {
+                digits += 1;
+                res.push(digit(rng, 0..10));
+                continue;
+            }
+            digits = 0;
+            res.push(match rng.chance(0.15) {
+                true => *rng.choose(&SYMBOLS),
+                false => '.',
+            });
+        }
+        res.push('\n');
+    }
+    res
+}
+
+pub fn day04(rng: &mut Rng) -> String {
+    let (winning, drawn) = (rng.usize_in(1..6), rng.usize_in(1..9));
+    let mut res = String::new();
+    for card in 1..=rng.usize_in(1..20) {
+        let mut numbers: Vec<usize> = (1..100).collect();
+        rng.shuffle(&mut numbers);
+        let mut lotto = numbers[..winning + drawn].to_vec();
+        rng.shuffle(&mut lotto);
+        let column = |numbers: &[usize]| join(numbers.iter().map(|n| format!("{n:2}")), " ");
+        writeln!(
+            res,
+            "Card {card:>3}: {} | {}",
+            column(&numbers[..winning]),
+            column(&lotto[..drawn])
+        )
+        .unwrap();
+    }
+    res
+}
+
+#[derive(Debug, Clone)]
+pub struct AlmanacConfig {
+    /// Pairs of seed numbers, read as ranges by part 2.
+    pub seed_ranges: usize,
+    /// At least one.
+    pub maps: usize,
+    pub ranges_per_map: usize,
+    /// Longest seed or map range.
+    pub max_len: i64,
+}
+
+impl Default for AlmanacConfig {
+    fn default() -> Self {
+        Self {
+            seed_ranges: 2,
+            maps: 7,
+            ranges_per_map: 4,
+            max_len: 50,
+        }
+    }
+}
+
+const CATEGORIES: [&str; 8] = [
+    "seed",
+    "soil",
+    "fertilizer",
+    "water",
+    "light",
+    "temperature",
+    "humidity",
+    "location",
+];
+
+pub fn day05(rng: &mut Rng) -> String {
+    day05_with(rng, &AlmanacConfig::default())
+}
+
+/// An almanac whose maps each have disjoint source ranges.
+pub fn day05_with(rng: &mut Rng, config: &AlmanacConfig) -> String {
+    let span = config.max_len * (config.ranges_per_map as i64 + 1) * 2;
+    let seeds = (0..config.seed_ranges)
+        .flat_map(|_| [rng.i64_in(0..span), rng.i64_in(1..config.max_len + 1)]);
+    let mut res = format!("seeds: {}\n", join(seeds, " "));
+    let category = |i: usize| match CATEGORIES.get(i) {
+        Some(name) => name.to_string(),
+        None => format!("stage{i}"),
+    };
+    for map in 0..config.maps {
+        writeln!(res, "\n{}-to-{} map:", category(map), category(map + 1)).unwrap();
+        let mut source = 0;
+        let mut ranges = vec![];
+        for _ in 0..config.ranges_per_map {
+            source += rng.i64_in(0..config.max_len);
+            let len = rng.i64_in(1..config.max_len + 1);
+            ranges.push(format!("{} {source} {len}", rng.i64_in(0..span)));
+            source += len;
+        }
+        rng.shuffle(&mut ranges);
+        for range in ranges {
+            writeln!(res, "{range}").unwrap();
+        }
+    }
+    res
+}
+
+/// Up to three races, so that part 2's single race stays short.
+pub fn day06(rng: &mut Rng) -> String {
+    let times: Vec<u64> = (0..rng.usize_in(1..4))
+        .map(|_| rng.usize_in(1..100) as u64)
+        .collect();
+    let distances: Vec<u64> = times
+        .iter()
+        .map(|t| {
+            let best = (t / 2) * (t - t / 2);
+            rng.usize_in(0..best as usize + 1) as u64
+        })
+        .collect();
+    let column = |numbers: &[u64]| join(numbers.iter().map(|n| format!("{n:>4}")), " ");
+    format!(
+        "Time:     {}\nDistance: {}\n",
+        column(&times),
+        column(&distances)
+    )
+}
+
+const CARDS: [char; 13] = [
+    'A', 'K', 'Q', 'J', 'T', '9', '8', '7', '6', '5', '4', '3', '2',
+];
+
+/// Distinct hands, as in the puzzle.
+pub fn day07(rng: &mut Rng) -> String {
+    let count = rng.usize_in(1..30);
+    let mut hands = HashSet::new();
+    let mut res = String::new();
+    while hands.len() < count {
+        let hand: String = (0..5).map(|_| *rng.choose(&CARDS)).collect();
+        if hands.insert(hand.clone()) {
+            writeln!(res, "{hand} {}", rng.usize_in(1..1000)).unwrap();
+        }
+    }
+    res
+}
+
+/// A ghost of day 8: its start node, one or two nodes at each depth after
+/// it, and its end node, from which it goes back to depth 1. Whatever the
+/// instructions, it is at its end after every `period` steps and only then.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ghost {
+    /// Whether the depth has a second node, reached by going right.
+    pub twins: Vec<bool>,
+}
+
+impl Ghost {
+    pub fn period(&self) -> usize {
+        self.twins.len() + 1
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Network {
+    pub instructions: Vec<char>,
+    /// The first ghost goes from `AAA` to `ZZZ`, for part 1.
+    pub ghosts: Vec<Ghost>,
+}
+
+pub fn day08(rng: &mut Rng) -> Network {
+    let instructions = (0..rng.usize_in(1..6))
+        .map(|_| *rng.choose(&['L', 'R']))
+        .collect();
+    let ghosts = (0..rng.usize_in(1..5))
+        .map(|_| Ghost {
+            twins: (0..rng.usize_in(0..11)).map(|_| rng.chance(0.5)).collect(),
+        })
+        .collect();
+    Network {
+        instructions,
+        ghosts,
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let instructions: String = self.instructions.iter().collect();
+        writeln!(f, "{instructions}\n")?;
+        for (g, ghost) in self.ghosts.iter().enumerate() {
+            let (start, end) = match g {
+                0 => ("AAA".to_owned(), "ZZZ".to_owned()),
+                _ => (format!("{g}SA"), format!("{g}EZ")),
+            };
+            let node = |depth: usize, twin: bool| match depth {
+                _ if depth == ghost.period() => end.clone(),
+                _ => format!("{g}{depth:X}{}", if twin { 'N' } else { 'M' }),
+            };
+            // the nodes one step after a node at `depth`
+            let next = |depth: usize| {
+                let depth = if depth == ghost.period() {
+                    1
+                } else {
+                    depth + 1
+                };
+                let twin = ghost.twins.get(depth - 1).copied().unwrap_or(false);
+                (node(depth, false), node(depth, twin))
+            };
+            let (left, right) = next(0);
+            writeln!(f, "{start} = ({left}, {right})")?;
+            for (i, twin) in ghost.twins.iter().enumerate() {
+                let (left, right) = next(i + 1);
+                writeln!(f, "{} = ({left}, {right})", node(i + 1, false))?;
+                if *twin {
+                    writeln!(f, "{} = ({left}, {right})", node(i + 1, true))?;
+                }
+            }
+            let (left, right) = next(ghost.period());
+            writeln!(f, "{end} = ({left}, {right})")?;
+        }
+        Ok(())
+    }
+}
+
+impl Generated for Network {
+    fn shrink(&self) -> Vec<Self> {
+        let mut res = vec![];
+        for instructions in without_one(&self.instructions, 1) {
+            res.push(Network {
+                instructions,
+                ..self.clone()
+            });
+        }
+        // the first ghost has to stay for part 1
+        for rest in without_one(&self.ghosts[1..], 0) {
+            let ghosts = [&self.ghosts[..1], &rest].concat();
+            res.push(Network {
+                ghosts,
+                ..self.clone()
+            });
+        }
+        let shrink_ghost = |ghost: &Ghost| {
+            let mut res: Vec<_> = without_one(&ghost.twins, 0)
+                .into_iter()
+                .map(|twins| Ghost { twins })
+                .collect();
+            res.extend(
+                shrink_one(
+                    &ghost.twins,
+                    |twin| if *twin { vec![false] } else { vec![] },
+                )
+                .into_iter()
+                .map(|twins| Ghost { twins }),
+            );
+            res
+        };
+        for ghosts in shrink_one(&self.ghosts, shrink_ghost) {
+            res.push(Network {
+                ghosts,
+                ..self.clone()
+            });
+        }
+        res
+    }
+}
+
+/// Day 9 histories, each the values of a small polynomial.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histories(pub Vec<Vec<i64>>);
+
+pub fn day09(rng: &mut Rng) -> Histories {
+    let histories = (0..rng.usize_in(1..9))
+        .map(|_| {
+            let coefficients: Vec<_> = (0..rng.usize_in(1..5)).map(|_| rng.i64_in(-3..4)).collect();
+            let offset = rng.i64_in(-20..21);
+            (0..rng.usize_in(1..11) as i64)
+                .map(|x| offset + coefficients.iter().rev().fold(0, |acc, c| acc * x + c))
+                .collect()
+        })
+        .collect();
+    Histories(histories)
+}
+
+impl fmt::Display for Histories {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for history in &self.0 {
+            let values: Vec<_> = history.iter().map(|v| v.to_string()).collect();
+            writeln!(f, "{}", values.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Generated for Histories {
+    fn shrink(&self) -> Vec<Self> {
+        let shrink_history = |history: &Vec<i64>| {
+            let mut res = without_one(history, 1);
+            res.extend(shrink_one(history, |v| shrink_number(*v)));
+            res
+        };
+        without_one(&self.0, 1)
+            .into_iter()
+            .chain(shrink_one(&self.0, shrink_history))
+            .map(Histories)
+            .collect()
+    }
+}
+
+/// The cells of a random polyomino with no gaps in any column, and with
+/// neighbouring columns overlapping, so that its boundary is a single loop
+/// that never touches itself.
+fn polyomino(rng: &mut Rng, width: usize, height: usize) -> Grid<bool> {
+    let mut inside = Grid::new(width, height, false);
+    let (mut top, mut bottom) = (0, height);
+    for x in 0..width {
+        let new_top = rng.usize_in(0..bottom);
+        bottom = rng.usize_in(new_top.max(top) + 1..height + 1);
+        top = new_top;
+        for y in top..bottom {
+            inside[Pos::new(x, y)] = true;
+        }
+    }
+    inside
+}
+
+/// The directions of the boundary edges of `inside` that meet at the top
+/// left corner of cell `(x, y)`, in the order of `Dir::ALL`.
+fn boundary(inside: &Grid<bool>, x: usize, y: usize) -> Vec<Dir> {
+    let cell = |dx: usize, dy: usize| {
+        x.checked_sub(dx)
+            .zip(y.checked_sub(dy))
+            .and_then(|(x, y)| inside.get(Pos::new(x, y)))
+            .copied()
+            .unwrap_or(false)
+    };
+    let (nw, ne, sw, se) = (cell(1, 1), cell(0, 1), cell(1, 0), cell(0, 0));
+    [
+        (Dir::Up, nw != ne),
+        (Dir::Right, ne != se),
+        (Dir::Down, sw != se),
+        (Dir::Left, nw != sw),
+    ]
+    .into_iter()
+    .filter_map(|(dir, edge)| edge.then_some(dir))
+    .collect()
+}
+
+const PIPES: [char; 6] = ['|', '-', 'L', 'J', '7', 'F'];
+
+/// A loop around a polyomino, with junk pipes around it that never point at
+/// the start.
+pub fn day10(rng: &mut Rng) -> String {
+    let (width, height) = (rng.usize_in(1..15), rng.usize_in(1..15));
+    let inside = polyomino(rng, width, height);
+    let pad = rng.usize_in(0..3);
+    let mut map = Grid::new(
+        inside.width() + 1 + 2 * pad,
+        inside.height() + 1 + 2 * pad,
+        '.',
+    );
+    let mut pipes = vec![];
+    for y in 0..=inside.height() {
+        for x in 0..=inside.width() {
+            let pipe = match boundary(&inside, x, y)[..] {
+                [] => continue,
+                [Dir::Up, Dir::Down] => '|',
+                [Dir::Right, Dir::Left] => '-',
+                [Dir::Up, Dir::Right] => 'L',
+                [Dir::Up, Dir::Left] => 'J',
+                [Dir::Down, Dir::Left] => '7',
+                [Dir::Right, Dir::Down] => 'F',
+                _ => unreachable!("the boundary touches itself"),
+            };
+            let pos = Pos::new(x + pad, y + pad);
+            map[pos] = pipe;
+            pipes.push(pos);
+        }
+    }
+    let start = *rng.choose(&pipes);
+    map[start] = 'S';
+    let next_to_start: Vec<_> = map.neighbours(start).collect();
+    for pos in map.positions().collect::<Vec<_>>() {
+        if map[pos] == '.' && !next_to_start.contains(&pos) && rng.chance(0.3) {
+            map[pos] = *rng.choose(&PIPES);
+        }
+    }
+    map.to_string()
+}
+
+pub fn day11(rng: &mut Rng) -> String {
+    let (width, height) = (rng.usize_in(1..30), rng.usize_in(1..30));
+    tiles(rng, width, height, |rng| match rng.chance(0.1) {
+        true => '#',
+        false => '.',
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct SpringConfig {
+    pub records: usize,
+    /// At least one.
+    pub max_groups: usize,
+    /// At least one.
+    pub max_group_len: usize,
+    /// Chance of a spring being shown as `?`.
+    pub unknown: f64,
+}
+
+impl Default for SpringConfig {
+    fn default() -> Self {
+        Self {
+            records: 10,
+            max_groups: 5,
+            max_group_len: 4,
+            unknown: 0.4,
+        }
+    }
+}
+
+pub fn day12(rng: &mut Rng) -> String {
+    day12_with(rng, &SpringConfig::default())
+}
+
+/// Records of real rows of springs with some of them hidden, so each has at
+/// least one arrangement.
+pub fn day12_with(rng: &mut Rng, config: &SpringConfig) -> String {
+    let mut res = String::new();
+    for _ in 0..config.records {
+        let groups: Vec<usize> = (0..rng.usize_in(1..config.max_groups + 1))
+            .map(|_| rng.usize_in(1..config.max_group_len + 1))
+            .collect();
+        let mut springs = String::new();
+        for (i, group) in groups.iter().enumerate() {
+            let gap = rng.usize_in(usize::from(i > 0)..3);
+            springs.extend(
+                iter::repeat('.')
+                    .take(gap)
+                    .chain(iter::repeat('#').take(*group)),
+            );
+        }
+        springs.extend(iter::repeat('.').take(rng.usize_in(0..3)));
+        let springs: String = springs
+            .chars()
+            .map(|ch| match rng.chance(config.unknown) {
+                true => '?',
+                false => ch,
+            })
+            .collect();
+        writeln!(res, "{springs} {}", join(&groups, ",")).unwrap();
+    }
+    res
+}
+
+/// A pattern with a perfect vertical mirror, and a horizontal one that is
+/// off by a single smudge.
+fn pattern(rng: &mut Rng) -> Grid<bool> {
+    let (width, height) = (rng.usize_in(3..12), rng.usize_in(2..12));
+    let mut grid = Grid::new(width, height, false);
+    for pos in grid.positions().collect::<Vec<_>>() {
+        grid[pos] = rng.chance(0.5);
+    }
+    // the smudge goes in a column the vertical mirror does not reflect
+    let column = loop {
+        let column = rng.usize_in(1..width);
+        if 2 * column != width {
+            break column;
+        }
+    };
+    let row = rng.usize_in(1..height);
+    let row_reach = row.min(height - row);
+    for dy in 0..row_reach {
+        for x in 0..width {
+            grid[Pos::new(x, row + dy)] = grid[Pos::new(x, row - 1 - dy)];
+        }
+    }
+    let column_reach = column.min(width - column);
+    for dx in 0..column_reach {
+        for y in 0..height {
+            grid[Pos::new(column + dx, y)] = grid[Pos::new(column - 1 - dx, y)];
+        }
+    }
+    let unreflected = match 2 * column < width {
+        true => 2 * column..width,
+        false => 0..2 * column - width,
+    };
+    let smudge = Pos::new(
+        rng.usize_in(unreflected),
+        rng.usize_in(row - row_reach..row + row_reach),
+    );
+    grid[smudge] = !grid[smudge];
+    grid
+}
+
+pub fn day13(rng: &mut Rng) -> String {
+    let patterns = (0..rng.usize_in(1..6)).map(|_| {
+        pattern(rng)
+            .map(|ash| if *ash { '#' } else { '.' })
+            .to_string()
+    });
+    join(patterns, "\n")
+}
+
+pub fn day14(rng: &mut Rng) -> String {
+    let (width, height) = (rng.usize_in(1..20), rng.usize_in(1..20));
+    tiles(rng, width, height, |rng| {
+        *rng.choose(&['O', 'O', '#', '.', '.', '.', '.'])
+    })
+}
+
+pub fn day15(rng: &mut Rng) -> String {
+    let count = rng.usize_in(1..8);
+    let labels = names(rng, count, &[]);
+    let steps = (0..rng.usize_in(1..30)).map(|_| {
+        let label = rng.choose(&labels);
+        match rng.chance(0.3) {
+            true => format!("{label}-"),
+            false => format!("{label}={}", rng.usize_in(1..10)),
+        }
+    });
+    join(steps, ",") + "\n"
+}
+
+pub fn day16(rng: &mut Rng) -> String {
+    let (width, height) = (rng.usize_in(1..20), rng.usize_in(1..20));
+    tiles(rng, width, height, |rng| match rng.chance(0.2) {
+        true => *rng.choose(&['/', '\\', '|', '-']),
+        false => '.',
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct HeatConfig {
+    /// At least one, as is `height`.
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Default for HeatConfig {
+    fn default() -> Self {
+        Self {
+            width: 13,
+            height: 13,
+        }
+    }
+}
+
+/// A heat loss map of random size, up to 20 by 20.
+pub fn day17(rng: &mut Rng) -> String {
+    let config = HeatConfig {
+        width: rng.usize_in(2..21),
+        height: rng.usize_in(2..21),
+    };
+    day17_with(rng, &config)
+}
+
+pub fn day17_with(rng: &mut Rng, config: &HeatConfig) -> String {
+    tiles(rng, config.width, config.height, |rng| digit(rng, 1..10))
+}
+
+/// A dig plan around a polyomino. The colours are the same plan scaled up,
+/// each a five digit distance followed by the direction.
+pub fn day18(rng: &mut Rng) -> String {
+    let (width, height) = (rng.usize_in(1..15), rng.usize_in(1..15));
+    let inside = polyomino(rng, width, height);
+    // the first corner found has edges going right and down
+    let first = (0..=inside.height())
+        .flat_map(|y| (0..=inside.width()).map(move |x| Pos::new(x, y)))
+        .find(|pos| !boundary(&inside, pos.x, pos.y).is_empty())
+        .unwrap();
+    let mut runs: Vec<(Dir, usize)> = vec![];
+    let (mut pos, mut dir) = (first, Dir::Right);
+    loop {
+        match runs.last_mut() {
+            Some((last, len)) if *last == dir => *len += 1,
+            _ => runs.push((dir, 1)),
+        }
+        pos = pos.step(dir).unwrap();
+        if pos == first {
+            break;
+        }
+        dir = boundary(&inside, pos.x, pos.y)
+            .into_iter()
+            .find(|d| *d != dir.reverse())
+            .unwrap();
+    }
+    let scale = rng.usize_in(1..1000);
+    let mut res = String::new();
+    for (dir, len) in runs {
+        let (name, code) = match dir {
+            Dir::Right => ('R', 0),
+            Dir::Down => ('D', 1),
+            Dir::Left => ('L', 2),
+            Dir::Up => ('U', 3),
+        };
+        writeln!(res, "{name} {len} (#{:05x}{code})", len * scale).unwrap();
+    }
+    res
+}
+
+/// Workflows that only send parts on to workflows after them, so no part
+/// goes round in circles.
+pub fn day19(rng: &mut Rng) -> String {
+    let mut workflows = vec!["in".to_owned()];
+    let count = rng.usize_in(0..8);
+    workflows.extend(names(rng, count, &["in"]));
+    let mut res = String::new();
+    for (i, name) in workflows.iter().enumerate() {
+        let targets: Vec<&str> = ["A", "R"]
+            .into_iter()
+            .chain(workflows[i + 1..].iter().map(String::as_str))
+            .collect();
+        let mut rules: Vec<String> = (0..rng.usize_in(0..4))
+            .map(|_| {
+                format!(
+                    "{}{}{}:{}",
+                    rng.choose(&['x', 'm', 'a', 's']),
+                    rng.choose(&['<', '>']),
+                    rng.usize_in(1..4001),
+                    rng.choose(&targets)
+                )
+            })
+            .collect();
+        rules.push(rng.choose(&targets).to_string());
+        writeln!(res, "{name}{{{}}}", join(&rules, ",")).unwrap();
+    }
+    res.push('\n');
+    for _ in 0..rng.usize_in(1..10) {
+        let [x, m, a, s] = [(); 4].map(|_| rng.usize_in(1..4001));
+        writeln!(res, "{{x={x},m={m},a={a},s={s}}}").unwrap();
+    }
+    res
+}
+
+#[derive(Debug, Clone)]
+pub struct ModuleConfig {
+    pub flip_flops: usize,
+    pub conjunctions: usize,
+    /// At least one.
+    pub max_destinations: usize,
+}
+
+impl Default for ModuleConfig {
+    fn default() -> Self {
+        Self {
+            flip_flops: 8,
+            conjunctions: 3,
+            max_destinations: 2,
+        }
+    }
+}
+
+pub fn day20(rng: &mut Rng) -> String {
+    day20_with(rng, &ModuleConfig::default())
+}
+
+/// A network in which modules only send pulses to modules listed after them,
+/// so every button press ends. Modules with nothing after them send to `rx`.
+pub fn day20_with(rng: &mut Rng, config: &ModuleConfig) -> String {
+    let mut kinds: Vec<char> = iter::repeat('%')
+        .take(config.flip_flops)
+        .chain(iter::repeat('&').take(config.conjunctions))
+        .collect();
+    rng.shuffle(&mut kinds);
+    let modules = names(rng, kinds.len(), &["rx"]);
+    let destinations = |rng: &mut Rng, after: usize| {
+        let mut later: Vec<&str> = modules[after..].iter().map(String::as_str).collect();
+        if later.is_empty() {
+            return "rx".to_owned();
+        }
+        rng.shuffle(&mut later);
+        later.truncate(rng.usize_in(1..config.max_destinations + 1));
+        later.join(", ")
+    };
+    let mut lines = vec![format!("broadcaster -> {}", destinations(rng, 0))];
+    for (i, (kind, name)) in kinds.iter().zip(&modules).enumerate() {
+        lines.push(format!("{kind}{name} -> {}", destinations(rng, i + 1)));
+    }
+    rng.shuffle(&mut lines);
+    join(lines, "\n") + "\n"
+}
+
+#[derive(Debug, Clone)]
+pub struct BrickConfig {
+    pub bricks: usize,
+    /// At least one.
+    pub max_len: usize,
+    /// Bricks start at x and y below this, at least one.
+    pub area: usize,
+}
+
+impl Default for BrickConfig {
+    fn default() -> Self {
+        Self {
+            bricks: 20,
+            max_len: 4,
+            area: 5,
+        }
+    }
+}
+
+pub fn day22(rng: &mut Rng) -> String {
+    day22_with(rng, &BrickConfig::default())
+}
+
+/// A snapshot of falling bricks, each of them higher up than the one before
+/// so that none overlap, listed in random order.
+pub fn day22_with(rng: &mut Rng, config: &BrickConfig) -> String {
+    let mut z = 1;
+    let mut bricks = vec![];
+    for _ in 0..config.bricks {
+        let start = [
+            rng.usize_in(0..config.area),
+            rng.usize_in(0..config.area),
+            z + rng.usize_in(0..3),
+        ];
+        let mut end = start;
+        end[rng.usize_in(0..3)] += rng.usize_in(0..config.max_len);
+        z = end[2] + 1;
+        bricks.push(format!("{}~{}", join(start, ","), join(end, ",")));
+    }
+    rng.shuffle(&mut bricks);
+    join(bricks, "\n") + "\n"
+}
+
+/// A generated input for `day`, or `None` if there is no generator for it.
+pub fn input(day: u8, rng: &mut Rng) -> Option<String> {
+    let generate: fn(&mut Rng) -> String = match day {
+        1 => day01,
+        2 => day02,
+        3 => day03,
+        4 => day04,
+        5 => day05,
+        6 => day06,
+        7 => day07,
+        8 => |rng| day08(rng).to_string(),
+        9 => |rng| day09(rng).to_string(),
+        10 => day10,
+        11 => day11,
+        12 => day12,
+        13 => day13,
+        14 => day14,
+        15 => day15,
+        16 => day16,
+        17 => day17,
+        18 => day18,
+        19 => day19,
+        20 => day20,
+        22 => day22,
+        _ => return None,
+    };
+    Some(generate(rng))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{days, generate::*, lookup, Error, Part};
+    use std::panic;
+    #[test]
+    fn generated_inputs_parse() {
+        let mut rng = Rng::new(1);
+        for _ in 0..50 {
+            let network = day08(&mut rng);
+            let day08 = lookup(8, Some("fast")).unwrap();
+            let steps = day08.run(&network.to_string(), Part::One).unwrap();
+            assert_eq!(steps.to_string(), network.ghosts[0].period().to_string());
+            for smaller in network.shrink() {
+                assert!(day08.run(&smaller.to_string(), Part::Two).is_ok());
+            }
+            let histories = day09(&mut rng);
+            assert!(lookup(9, None)
+                .unwrap()
+                .parse(&histories.to_string())
+                .is_ok());
+        }
+    }
+    #[test]
+    fn every_day_has_a_generator() {
+        for day in days() {
+            let entry = lookup(day, None).unwrap();
+            // a day just made from the template gets a generator once solved
+            if input(day, &mut Rng::new(0)).is_none()
+                && entry.run("", Part::One) == Err(Error::Unsolved)
+            {
+                continue;
+            }
+            for seed in 0..20 {
+                let input = input(day, &mut Rng::new(seed))
+                    .unwrap_or_else(|| panic!("day{day:02} has no generator"));
+                if let Err(e) = entry.run(&input, Part::One) {
+                    panic!("seed {seed}: {e}\n{input}");
+                }
+                // part 2 may have no answer for an input, but must say so
+                let part_2 = panic::catch_unwind(|| entry.run(&input, Part::Two));
+                assert!(part_2.is_ok(), "seed {seed}: part 2 panicked\n{input}");
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::ops::{Add, Mul, Sub};
+
+/// A direction on screen, so `Up` lowers `y`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dir {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Dir {
+    /// Clockwise, starting with `Up`.
+    pub const ALL: [Dir; 4] = [Dir::Up, Dir::Right, Dir::Down, Dir::Left];
+
+    pub fn turn_right(self) -> Self {
+        match self {
+            Dir::Up => Dir::Right,
+            Dir::Right => Dir::Down,
+            Dir::Down => Dir::Left,
+            Dir::Left => Dir::Up,
+        }
+    }
+
+    pub fn turn_left(self) -> Self {
+        self.turn_right().reverse()
+    }
+
+    pub fn reverse(self) -> Self {
+        self.turn_right().turn_right()
+    }
+
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Dir::Up | Dir::Down)
+    }
+
+    /// `(dx, dy)` of a single step.
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Dir::Up => (0, -1),
+            Dir::Right => (1, 0),
+            Dir::Down => (0, 1),
+            Dir::Left => (-1, 0),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Point<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+
+    pub fn manhattan(self, other: Self) -> T
+    where
+        T: Copy + Ord + Add<Output = T> + Sub<Output = T>,
+    {
+        let dist = |a: T, b: T| a.max(b) - a.min(b);
+        dist(self.x, other.x) + dist(self.y, other.y)
+    }
+}
+
+macro_rules! point_step {
+    ($($t:ty),*) => {
+        $(impl Point<$t> {
+            /// `n` steps towards `dir`, `None` if a coordinate would overflow.
+            pub fn step_n(self, dir: Dir, n: $t) -> Option<Self> {
+                let shift = |v: $t, d: isize| match d {
+                    -1 => v.checked_sub(n),
+                    1 => v.checked_add(n),
+                    _ => Some(v),
+                };
+                let (dx, dy) = dir.delta();
+                Some(Self::new(shift(self.x, dx)?, shift(self.y, dy)?))
+            }
+
+            pub fn step(self, dir: Dir) -> Option<Self> {
+                self.step_n(dir, 1)
+            }
+        })*
+    };
+}
+point_step!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl<T: Add<Output = T>> Add for Point<T> {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point<T> {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<T: Mul<Output = T> + Copy> Mul<T> for Point<T> {
+    type Output = Self;
+    fn mul(self, factor: T) -> Self {
+        Self::new(self.x * factor, self.y * factor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::*;
+    #[test]
+    fn turns() {
+        for dir in Dir::ALL {
+            assert_eq!(dir.turn_left().turn_right(), dir);
+            assert_eq!(dir.reverse().reverse(), dir);
+            assert_ne!(dir.is_vertical(), dir.turn_right().is_vertical());
+        }
+        assert_eq!(Dir::Up.turn_left(), Dir::Left);
+        assert_eq!(Dir::Left.reverse(), Dir::Right);
+    }
+    #[test]
+    fn steps() {
+        let p = Point::new(0usize, 2);
+        assert_eq!(p.step(Dir::Left), None);
+        assert_eq!(p.step(Dir::Up), Some(Point::new(0, 1)));
+        assert_eq!(p.step_n(Dir::Right, 3), Some(Point::new(3, 2)));
+        assert_eq!(Point::new(i8::MAX, 0).step(Dir::Right), None);
+        let q = Point::new(-3i64, 4);
+        assert_eq!(q.manhattan(Point::default()), 7);
+        assert_eq!(q * 2 - q + Point::new(1, 1), Point::new(-2, 5));
+    }
+}
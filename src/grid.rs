@@ -0,0 +1,276 @@
+use crate::{
+    error::Source,
+    geometry::{Dir, Point},
+    ParseError,
+};
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+/// `(0, 0)` is the top left cell.
+pub type Pos = Point<usize>;
+
+const NEIGHBOURS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+];
+
+/// A rectangular grid stored row by row in a single `Vec`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            cells: vec![fill; width * height],
+            width,
+            height,
+        }
+    }
+
+    /// Parses the character layout in `s`, which must be a slice of `src`.
+    /// Every line is a row, and `tile` maps each character to a cell or
+    /// rejects it, in which case the error says `expected`.
+    pub fn parse(
+        src: &Source,
+        s: &str,
+        expected: &str,
+        mut tile: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, ParseError> {
+        let width = s.lines().next().map_or(0, |l| l.chars().count());
+        if width == 0 {
+            return Err(src.error(s, "a grid"));
+        }
+        let mut cells = Vec::with_capacity(s.len());
+        let mut height = 0;
+        for l in s.lines() {
+            if l.chars().count() != width {
+                return Err(src.error(l, format!("a row of width {width}")));
+            }
+            for (i, ch) in l.char_indices() {
+                cells.push(tile(ch).ok_or_else(|| src.error(&l[i..], expected))?);
+            }
+            height += 1;
+        }
+        Ok(Self {
+            cells,
+            width,
+            height,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, pos: Pos) -> bool {
+        pos.x < self.width && pos.y < self.height
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.contains(pos)
+            .then(|| &self.cells[pos.y * self.width + pos.x])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        self.contains(pos)
+            .then(|| &mut self.cells[pos.y * self.width + pos.x])
+    }
+
+    /// The next cell from `pos` towards `dir`, if there is one.
+    pub fn step(&self, pos: Pos, dir: Dir) -> Option<Pos> {
+        pos.step(dir).filter(|p| self.contains(*p))
+    }
+
+    /// `pos` moved by `(dx, dy)`, if that is still inside the grid.
+    pub fn offset(&self, pos: Pos, (dx, dy): (isize, isize)) -> Option<Pos> {
+        let x = pos.x.checked_add_signed(dx)?;
+        let y = pos.y.checked_add_signed(dy)?;
+        Some(Point::new(x, y)).filter(|p| self.contains(*p))
+    }
+
+    /// The up to 4 cells sharing an edge with `pos`.
+    pub fn neighbours(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        Dir::ALL
+            .into_iter()
+            .filter_map(move |dir| self.step(pos, dir))
+    }
+
+    /// The up to 8 cells sharing an edge or a corner with `pos`.
+    pub fn neighbours_8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        NEIGHBOURS_8
+            .iter()
+            .filter_map(move |d| self.offset(pos, *d))
+    }
+
+    /// Every position, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.cells.len()).map(move |i| Point::new(i % width, i / width))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width)
+    }
+
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> {
+        assert!(x < self.width, "column {x} out of bounds");
+        self.cells[x..].iter().step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// Mirrors the grid along its main diagonal, rows become columns.
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            cells: self.columns().flatten().cloned().collect(),
+            width: self.height,
+            height: self.width,
+        }
+    }
+
+    /// Turns the grid a quarter clockwise, the left column becomes the top row.
+    pub fn rotate_clockwise(&self) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            cells: (0..self.width)
+                .flat_map(|x| self.column(x).rev())
+                .cloned()
+                .collect(),
+            width: self.height,
+            height: self.width,
+        }
+    }
+
+    /// Turns the grid a quarter counterclockwise, the right column becomes the
+    /// top row.
+    pub fn rotate_counterclockwise(&self) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            cells: (0..self.width)
+                .rev()
+                .flat_map(|x| self.column(x))
+                .cloned()
+                .collect(),
+            width: self.height,
+            height: self.width,
+        }
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+    fn index(&self, pos: Pos) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{pos:?} outside of {}x{} grid", self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{pos:?} outside of {width}x{height} grid"))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::*;
+    fn parse(s: &str) -> Result<Grid<char>, ParseError> {
+        Grid::parse(&Source::new(0, s), s, "`.` or `#`", |ch| {
+            matches!(ch, '.' | '#').then_some(ch)
+        })
+    }
+    #[test]
+    fn parse_errors() {
+        let err = parse("..#\n.#\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        assert_eq!(err.expected, "a row of width 3");
+        let err = parse("..#\n.x#\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 2));
+        assert_eq!(err.found, "`x#`");
+        assert!(parse("").is_err());
+    }
+    #[test]
+    fn neighbours_stay_inside() {
+        let grid = parse("...\n...\n").unwrap();
+        let mut corner: Vec<_> = grid.neighbours_8(Point::new(0, 0)).collect();
+        corner.sort();
+        assert_eq!(
+            corner,
+            [(0, 1), (1, 0), (1, 1)].map(|(x, y)| Point::new(x, y))
+        );
+        assert_eq!(grid.neighbours(Point::new(1, 1)).count(), 3);
+        assert_eq!(grid.neighbours_8(Point::new(1, 0)).count(), 5);
+        assert_eq!(grid.step(Point::new(2, 1), Dir::Right), None);
+    }
+    #[test]
+    fn views_and_turns() {
+        let grid = parse("#..\n##.\n").unwrap();
+        assert_eq!(grid.row(1), ['#', '#', '.']);
+        assert!(grid.column(2).all(|ch| *ch == '.'));
+        assert_eq!(grid[Point::new(1, 1)], '#');
+        assert_eq!(grid.transpose().to_string(), "##\n.#\n..\n");
+        assert_eq!(grid.rotate_clockwise().to_string(), "##\n#.\n..\n");
+        assert_eq!(grid.rotate_counterclockwise().to_string(), "..\n.#\n##\n");
+        assert_eq!(grid.transpose().transpose(), grid);
+        let turned = (0..4).fold(grid.clone(), |g, _| g.rotate_clockwise());
+        assert_eq!(turned, grid);
+    }
+}
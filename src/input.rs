@@ -0,0 +1,111 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// Overrides the directory inputs are read from, `input/` in the crate root.
+pub const ENV: &str = "AOC_INPUT_DIR";
+
+/// Where inputs are, whatever the current directory.
+pub fn dir() -> PathBuf {
+    env::var_os(ENV).map_or_else(
+        || Path::new(env!("CARGO_MANIFEST_DIR")).join("input"),
+        PathBuf::from,
+    )
+}
+
+pub fn path(name: &str) -> PathBuf {
+    dir().join(name)
+}
+
+/// Name of the puzzle input of `day`, e.g. `day07`.
+pub fn real(day: u8) -> String {
+    format!("day{day:02}")
+}
+
+/// Names of the examples of `day` in `dir()`, sorted: `exampleNN` and any
+/// `exampleNN_SUFFIX`, e.g. `example10_part2_big`.
+pub fn examples(day: u8) -> Vec<String> {
+    let prefix = format!("example{day:02}");
+    let Ok(entries) = fs::read_dir(dir()) else {
+        return vec![];
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| {
+            name.strip_prefix(&prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('_'))
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+/// Every input of `day` there is, the puzzle input first.
+pub fn all(day: u8) -> Vec<String> {
+    let real = real(day);
+    let mut names = vec![];
+    if path(&real).is_file() {
+        names.push(real);
+    }
+    names.extend(examples(day));
+    names
+}
+
+/// Unix line endings, no whitespace at the end of lines, no blank lines at
+/// the end, and a final newline unless the text is empty, so that parsers
+/// only have to handle the one form.
+pub fn normalize(text: &str) -> String {
+    let mut res = String::with_capacity(text.len() + 1);
+    for line in text.lines() {
+        res.push_str(line.trim_end());
+        res.push('\n');
+    }
+    res.truncate(res.trim_end().len());
+    if !res.is_empty() {
+        res.push('\n');
+    }
+    res
+}
+
+/// Reads the input called `name` in `dir()`, normalized.
+pub fn read(name: &str) -> Result<String, String> {
+    read_path(&path(name))
+}
+
+/// Reads an input from anywhere, normalized.
+pub fn read_path(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path)
+        .map(|text| normalize(&text))
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{input::*, lookup, Answer, Part};
+    #[test]
+    fn finds_examples_and_normalizes() {
+        assert_eq!(
+            examples(10),
+            [
+                "example10",
+                "example10_part2",
+                "example10_part2_big",
+                "example10_part2_big2"
+            ]
+        );
+        assert_eq!(all(17), ["day17", "example17", "example17_2"]);
+        assert!(examples(25).is_empty());
+        assert_eq!(normalize("a \r\nb\r\n\r\n \n"), "a\nb\n");
+        assert_eq!(normalize("\n\n"), "");
+
+        let example = read("example05").unwrap();
+        let crlf = example.replace('\n', " \r\n") + "\r\n";
+        assert_eq!(normalize(&crlf), example);
+        let day05 = lookup(5, None).unwrap();
+        assert_eq!(
+            day05.run(&normalize(&crlf), Part::One),
+            Ok(Answer::Signed(35))
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+
+/// A JSON value, written out compactly by `Display`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    /// Kept as text so that 64 and 128 bit integers are written exactly.
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    /// Fields in the order they are written.
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn object<'a>(fields: impl IntoIterator<Item = (&'a str, Json)>) -> Self {
+        Json::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.to_owned(), value))
+                .collect(),
+        )
+    }
+}
+
+macro_rules! json_number {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Json {
+            fn from(n: $t) -> Self {
+                Json::Number(n.to_string())
+            }
+        })*
+    };
+}
+json_number!(u8, u32, u64, u128, usize, i32, i64, i128);
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Json::Bool(b)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::String(s.to_owned())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Json::String(s)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(items: Vec<T>) -> Self {
+        Json::Array(items.into_iter().map(Into::into).collect())
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for ch in s.chars() {
+        match ch {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            ch if ch.is_control() => write!(f, "\\u{:04x}", ch as u32)?,
+            ch => write!(f, "{ch}")?,
+        }
+    }
+    f.write_str("\"")
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{b}"),
+            Json::Number(n) => f.write_str(n),
+            Json::String(s) => write_string(f, s),
+            Json::Array(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                f.write_str("]")
+            }
+            Json::Object(fields) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::*;
+    #[test]
+    fn writes_nested_values_escaped() {
+        let json = Json::object([
+            ("name", "day08:\"fast\"\n".into()),
+            ("big", u64::MAX.into()),
+            ("none", None::<u8>.into()),
+            ("list", vec![true, false].into()),
+            ("empty", Json::Object(vec![])),
+        ]);
+        assert_eq!(
+            json.to_string(),
+            r#"{"name":"day08:\"fast\"\n","big":18446744073709551615,"none":null,"list":[true,false],"empty":{}}"#
+        );
+        assert_eq!(Json::from("\u{1}").to_string(), r#""\u0001""#);
+    }
+}
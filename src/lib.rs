@@ -1,5 +1,9 @@
+pub mod answers;
+pub mod bench;
+pub mod checked;
 pub mod day01;
 pub mod day02;
+pub mod day02_bayes;
 pub mod day03;
 pub mod day04;
 pub mod day05;
@@ -20,3 +24,86 @@ pub mod day18;
 pub mod day19;
 pub mod day20;
 pub mod day22;
+pub mod diff;
+pub mod error;
+pub mod generate;
+pub mod geometry;
+pub mod grid;
+pub mod input;
+pub mod json;
+pub mod log;
+pub mod pool;
+pub mod puzzle;
+pub mod report;
+pub mod rng;
+pub mod scaffold;
+pub mod solution;
+pub mod stream;
+pub mod submit;
+
+pub use error::{Error, ParseError};
+pub use solution::{Answer, Entry, Part, Solution};
+
+/// Every solution in the crate, ordered by day. Alternative implementations of
+/// a day are listed after its default one, under a variant name.
+pub fn registry() -> Vec<Entry> {
+    vec![
+        Entry::new::<day01::Day01>(1, None),
+        Entry::new::<day02::Day02>(2, None),
+        Entry::new::<day03::Day03>(3, None),
+        Entry::new::<day04::Day04>(4, None),
+        Entry::new::<day05::Day05>(5, None),
+        Entry::new::<day06::Day06>(6, None),
+        Entry::new::<day07::Day07>(7, None),
+        Entry::new::<day08::Day08>(8, None),
+        Entry::new::<day08::Day08Fast>(8, Some("fast")),
+        Entry::new::<day09::Day09>(9, None),
+        Entry::new::<day09_inplace::Day09Inplace>(9, Some("inplace")),
+        Entry::new::<day10::Day10>(10, None),
+        Entry::new::<day11::Day11>(11, None),
+        Entry::new::<day12::Day12>(12, None),
+        Entry::new::<day13::Day13>(13, None),
+        Entry::new::<day14::Day14>(14, None),
+        Entry::new::<day15::Day15>(15, None),
+        Entry::new::<day16::Day16>(16, None),
+        Entry::new::<day17::Day17>(17, None),
+        Entry::new::<day18::Day18>(18, None),
+        Entry::new::<day19::Day19>(19, None),
+        Entry::new::<day20::Day20>(20, None),
+        Entry::new::<day22::Day22>(22, None),
+    ]
+}
+
+pub fn days() -> Vec<u8> {
+    let mut days: Vec<u8> = registry().iter().map(|e| e.day).collect();
+    days.dedup();
+    days
+}
+
+pub fn lookup(day: u8, variant: Option<&str>) -> Option<Entry> {
+    registry()
+        .into_iter()
+        .find(|e| e.day == day && e.variant == variant)
+}
+
+pub fn variants(day: u8) -> Vec<Entry> {
+    registry().into_iter().filter(|e| e.day == day).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::collections::HashSet;
+    #[test]
+    fn registry_names_are_unique() {
+        let names: HashSet<String> = registry().iter().map(|e| e.name()).collect();
+        assert_eq!(names.len(), registry().len());
+    }
+    #[test]
+    fn lookup_runs_variant() {
+        let input = input::read("example08_part2").unwrap();
+        let fast = lookup(8, Some("fast")).unwrap();
+        assert_eq!(fast.name(), "day08:fast");
+        assert_eq!(fast.run(&input, Part::Two), Ok(Answer::Unsigned(6)));
+    }
+}
@@ -0,0 +1,254 @@
+use std::{
+    cell::RefCell,
+    env, fmt,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        RwLock,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// Something about the input or the answer that is worth knowing.
+    Warn = 1,
+    /// Milestones of a solution, like a cycle being found.
+    Info = 2,
+    /// Intermediate values, for working on a solution.
+    Debug = 3,
+    /// Every step, e.g. each beam split.
+    Trace = 4,
+}
+
+impl Level {
+    const ALL: [Level; 4] = [Level::Warn, Level::Info, Level::Debug, Level::Trace];
+
+    fn name(self) -> &'static str {
+        match self {
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The variable `Filter::from_env` reads, e.g. `AOC_LOG=warn,day16=trace`.
+pub const ENV: &str = "AOC_LOG";
+
+/// Which events are shown, by target. Targets are module paths within the
+/// crate, so each day is its own target, e.g. `day16`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+    /// The most verbose level shown for targets no directive names, if any.
+    default: Option<Level>,
+    /// Targets and their most verbose level. The longest matching one wins.
+    targets: Vec<(String, Option<Level>)>,
+}
+
+impl Filter {
+    /// Comma separated directives, each a level (`off`, `warn`, `info`,
+    /// `debug` or `trace`) for all targets, `TARGET=LEVEL`, or a bare
+    /// `TARGET` to show everything from it. Later directives win.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let level = |s: &str| match s {
+            "off" => Ok(None),
+            _ => Level::ALL
+                .into_iter()
+                .find(|l| l.name() == s)
+                .map(Some)
+                .ok_or_else(|| format!("bad log level: {s}")),
+        };
+        let mut filter = Filter::default();
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, lvl)) => filter.targets.push((target.to_owned(), level(lvl)?)),
+                None => match level(directive) {
+                    Ok(lvl) => filter.default = lvl,
+                    Err(_) => filter
+                        .targets
+                        .push((directive.to_owned(), Some(Level::Trace))),
+                },
+            }
+        }
+        Ok(filter)
+    }
+
+    /// The filter in `ENV`, showing nothing if it is unset. A bad one is
+    /// reported and ignored.
+    pub fn from_env() -> Self {
+        let Ok(spec) = env::var(ENV) else {
+            return Filter::default();
+        };
+        Filter::parse(&spec).unwrap_or_else(|e| {
+            eprintln!("{ENV}: {e}");
+            Filter::default()
+        })
+    }
+
+    fn level_of(&self, target: &str) -> Option<Level> {
+        let matches = |t: &str| {
+            target
+                .strip_prefix(t)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+        };
+        self.targets
+            .iter()
+            .filter(|(t, _)| matches(t))
+            .max_by_key(|(t, _)| t.len())
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    pub fn allows(&self, target: &str, level: Level) -> bool {
+        self.level_of(target).is_some_and(|max| level <= max)
+    }
+
+    fn max_level(&self) -> Option<Level> {
+        self.targets
+            .iter()
+            .map(|(_, l)| *l)
+            .fold(self.default, Ord::max)
+    }
+}
+
+static FILTER: RwLock<Filter> = RwLock::new(Filter {
+    default: None,
+    targets: Vec::new(),
+});
+
+const UNSET: u8 = u8::MAX;
+
+/// The most verbose level any target shows, 0 if none, so that disabled
+/// events cost no more than a load.
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(UNSET);
+
+thread_local! {
+    static CAPTURED: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+/// Replaces the filter, which is read from `ENV` on first use otherwise.
+pub fn set_filter(filter: Filter) {
+    let max = filter.max_level().map_or(0, |l| l as u8);
+    *FILTER.write().unwrap() = filter;
+    MAX_LEVEL.store(max, Ordering::Relaxed);
+}
+
+/// `module_path!()` without the crate name.
+pub fn target(module_path: &'static str) -> &'static str {
+    module_path
+        .split_once("::")
+        .map_or(module_path, |(_, rest)| rest)
+}
+
+pub fn enabled(target: &str, level: Level) -> bool {
+    let mut max = MAX_LEVEL.load(Ordering::Relaxed);
+    if max == UNSET {
+        set_filter(Filter::from_env());
+        max = MAX_LEVEL.load(Ordering::Relaxed);
+    }
+    level as u8 <= max && FILTER.read().unwrap().allows(target, level)
+}
+
+/// Used by the event macros. Events go to stderr, never stdout, if the filter
+/// allows them, except that warnings raised inside `capture` are collected
+/// instead.
+pub fn event(target: &str, level: Level, args: fmt::Arguments) {
+    if level == Level::Warn {
+        let captured = CAPTURED.with(|captured| match &mut *captured.borrow_mut() {
+            Some(warnings) => {
+                warnings.push(args.to_string());
+                true
+            }
+            None => false,
+        });
+        if captured {
+            return;
+        }
+    }
+    if enabled(target, level) {
+        eprintln!("{level} {target}: {args}");
+    }
+}
+
+/// Runs `f`, returning the warnings it raised on this thread along with its
+/// result, whatever the filter.
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, Vec<String>) {
+    let outer = CAPTURED.with(|captured| captured.replace(Some(vec![])));
+    let res = f();
+    let warnings = CAPTURED
+        .with(|captured| captured.replace(outer))
+        .unwrap_or_default();
+    (res, warnings)
+}
+
+/// `event!(level, "format", args..)` emits an event targeted at the calling
+/// module, `event!(level, target: "name", "format", args..)` at `name`. The
+/// message is only formatted if it is going to be used.
+#[macro_export]
+macro_rules! event {
+    ($level:expr, target: $target:expr, $($arg:tt)+) => {{
+        let level = $level;
+        if level == $crate::log::Level::Warn || $crate::log::enabled($target, level) {
+            $crate::log::event($target, level, format_args!($($arg)+))
+        }
+    }};
+    ($level:expr, $($arg:tt)+) => {
+        $crate::event!($level, target: $crate::log::target(module_path!()), $($arg)+)
+    };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => { $crate::event!($crate::log::Level::Warn, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => { $crate::event!($crate::log::Level::Info, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => { $crate::event!($crate::log::Level::Debug, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)+) => { $crate::event!($crate::log::Level::Trace, $($arg)+) };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::log::*;
+    #[test]
+    fn captures_warnings_of_this_thread() {
+        let (n, warnings) = capture(|| {
+            crate::warn!("odd input {}", 1);
+            let (_, inner) = capture(|| crate::warn!(target: "day20", "inner"));
+            assert_eq!(inner, ["inner"]);
+            crate::debug!("not a warning");
+            2
+        });
+        assert_eq!(n, 2);
+        assert_eq!(warnings, ["odd input 1"]);
+    }
+    #[test]
+    fn filters_by_target_and_level() {
+        let filter = Filter::parse("warn, day16=trace,day20,day20::conj=off").unwrap();
+        assert!(filter.allows("day01", Level::Warn));
+        assert!(!filter.allows("day01", Level::Info));
+        assert!(filter.allows("day16", Level::Trace));
+        assert!(!filter.allows("day160", Level::Trace));
+        assert!(filter.allows("day20", Level::Debug));
+        assert!(!filter.allows("day20::conj", Level::Warn));
+        assert_eq!(Filter::parse("").unwrap(), Filter::default());
+        assert!(!Filter::default().allows("day01", Level::Warn));
+        assert!(Filter::parse("day16=loud").is_err());
+        assert_eq!(target(module_path!()), "log::tests");
+    }
+}
@@ -0,0 +1,173 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+pub type Job<T> = Box<dyn FnOnce() -> T + Send>;
+
+#[derive(Debug)]
+pub enum Completion<T> {
+    Returned(T, Duration),
+    /// The panic message, if it had one.
+    Panicked(String, Duration),
+    TimedOut,
+}
+
+enum Message<T> {
+    Started(usize, Instant),
+    Finished(usize, thread::Result<T>, Duration),
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    match (
+        payload.downcast_ref::<&str>(),
+        payload.downcast_ref::<String>(),
+    ) {
+        (Some(s), _) => s.to_string(),
+        (_, Some(s)) => s.clone(),
+        _ => String::new(),
+    }
+}
+
+/// Runs `jobs` on `workers` threads and returns how each went, in the order of
+/// `jobs`. Threads cannot be stopped, so a job running longer than `timeout`
+/// is left running in the background while a new thread takes its place, and
+/// its thread exits once the job is done.
+pub fn run<T: Send + 'static>(
+    jobs: Vec<Job<T>>,
+    workers: usize,
+    timeout: Option<Duration>,
+) -> Vec<Completion<T>> {
+    let count = jobs.len();
+    let queue = Arc::new(Mutex::new(
+        jobs.into_iter().enumerate().collect::<VecDeque<_>>(),
+    ));
+    // whether each job has finished or timed out, whichever came first
+    let settled = Arc::new(Mutex::new(vec![false; count]));
+    let (tx, rx) = mpsc::channel();
+    let spawn_worker = || {
+        let queue = Arc::clone(&queue);
+        let settled = Arc::clone(&settled);
+        let tx = tx.clone();
+        thread::spawn(move || loop {
+            let Some((i, job)) = queue.lock().unwrap().pop_front() else {
+                break;
+            };
+            let start = Instant::now();
+            if tx.send(Message::Started(i, start)).is_err() {
+                break;
+            }
+            let res = panic::catch_unwind(AssertUnwindSafe(job));
+            // another thread has taken the place of this one
+            if std::mem::replace(&mut settled.lock().unwrap()[i], true) {
+                break;
+            }
+            if tx.send(Message::Finished(i, res, start.elapsed())).is_err() {
+                break;
+            }
+        });
+    };
+    for _ in 0..workers.clamp(1, count.max(1)) {
+        spawn_worker();
+    }
+
+    let mut completions: Vec<Option<Completion<T>>> = (0..count).map(|_| None).collect();
+    let mut running: HashMap<usize, Instant> = HashMap::new();
+    let mut left = count;
+    while left > 0 {
+        let deadline = timeout.and_then(|t| running.values().min().map(|start| *start + t));
+        let message = match deadline {
+            Some(deadline) => {
+                match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(message) => message,
+                    Err(RecvTimeoutError::Timeout) => {
+                        let now = Instant::now();
+                        let late: Vec<usize> = running
+                            .iter()
+                            .filter(|(_, start)| now >= **start + timeout.unwrap())
+                            .map(|(i, _)| *i)
+                            .collect();
+                        for i in late {
+                            running.remove(&i);
+                            // unless it finished just now, and says so next
+                            if !std::mem::replace(&mut settled.lock().unwrap()[i], true) {
+                                completions[i] = Some(Completion::TimedOut);
+                                left -= 1;
+                                spawn_worker();
+                            }
+                        }
+                        continue;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => unreachable!("`tx` is still here"),
+                }
+            }
+            None => rx.recv().expect("`tx` is still here"),
+        };
+        match message {
+            Message::Started(i, start) => {
+                running.insert(i, start);
+            }
+            // jobs that timed out never get here
+            Message::Finished(i, res, elapsed) => {
+                running.remove(&i);
+                completions[i] = Some(match res {
+                    Ok(value) => Completion::Returned(value, elapsed),
+                    Err(payload) => Completion::Panicked(panic_message(&*payload), elapsed),
+                });
+                left -= 1;
+            }
+        }
+    }
+    completions.into_iter().map(Option::unwrap).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pool::*;
+    #[test]
+    fn times_out_without_blocking_others() {
+        let jobs: Vec<Job<u32>> = vec![
+            Box::new(|| 1),
+            Box::new(|| {
+                thread::sleep(Duration::from_secs(5));
+                2
+            }),
+            Box::new(|| panic!("boom")),
+            Box::new(|| 4),
+        ];
+        let start = Instant::now();
+        let res = run(jobs, 2, Some(Duration::from_millis(200)));
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert!(matches!(res[0], Completion::Returned(1, _)));
+        assert!(matches!(res[1], Completion::TimedOut));
+        assert!(matches!(&res[2], Completion::Panicked(msg, _) if msg == "boom"));
+        assert!(matches!(res[3], Completion::Returned(4, _)));
+    }
+    #[test]
+    fn timed_out_threads_retire() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let running = Arc::new(AtomicUsize::new(0));
+        let most = Arc::new(AtomicUsize::new(0));
+        let mut jobs: Vec<Job<()>> = vec![Box::new(|| thread::sleep(Duration::from_millis(300)))];
+        for _ in 0..20 {
+            let (running, most) = (Arc::clone(&running), Arc::clone(&most));
+            jobs.push(Box::new(move || {
+                most.fetch_max(running.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(50));
+                running.fetch_sub(1, Ordering::SeqCst);
+            }));
+        }
+        let res = run(jobs, 2, Some(Duration::from_millis(100)));
+        assert!(matches!(res[0], Completion::TimedOut));
+        assert!(res[1..]
+            .iter()
+            .all(|c| matches!(c, Completion::Returned(..))));
+        assert!(most.load(Ordering::SeqCst) <= 2);
+    }
+}
@@ -0,0 +1,302 @@
+use crate::{
+    answers::{self, Check, Expected},
+    input,
+    scaffold::{day_of, insert_sorted},
+    Part,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The example of a part of a puzzle, as found on its page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Example {
+    pub part: Part,
+    /// Input name, `exampleNN`, or `exampleNN_part2` if part 2 has an example
+    /// of its own.
+    pub name: String,
+    /// Normalized, see `input::normalize`.
+    pub input: String,
+    pub answer: Option<String>,
+}
+
+/// The text between each `open` and the `close` after it. `open` may be the
+/// start of a tag with attributes, in which case the text starts after it.
+pub(crate) fn sections<'a>(text: &'a str, open: &str, close: &str) -> Vec<(usize, &'a str)> {
+    let mut res = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find(open) {
+        let mut after = &rest[start + open.len()..];
+        if !open.ends_with('>') {
+            let Some(gt) = after.find('>') else { break };
+            after = &after[gt + 1..];
+        }
+        let Some(end) = after.find(close) else { break };
+        res.push((text.len() - after.len(), &after[..end]));
+        rest = &after[end + close.len()..];
+    }
+    res
+}
+
+fn unescape(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        res.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let entity = rest.find(';').map(|semi| &rest[1..semi]);
+        let ch = entity.and_then(|entity| match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => {
+                let code = entity.strip_prefix('#')?;
+                let code = match code.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => code.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+        match (ch, entity) {
+            (Some(ch), Some(entity)) => {
+                res.push(ch);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                res.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    res.push_str(rest);
+    res
+}
+
+/// The text of some HTML, e.g. without the `<em>` that highlights parts of an
+/// example.
+pub(crate) fn text_of(html: &str) -> String {
+    let mut res = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(lt) = rest.find('<') {
+        res.push_str(&rest[..lt]);
+        rest = rest[lt..].find('>').map_or("", |gt| &rest[lt + gt + 1..]);
+    }
+    res.push_str(rest);
+    unescape(&res)
+}
+
+/// The last emphasized code of a part, where the puzzles give the answer for
+/// their example. Emphasized bits of longer code are not answers.
+fn last_answer(article: &str) -> Option<String> {
+    [
+        ("<code><em>", "</em></code>"),
+        ("<em><code>", "</code></em>"),
+    ]
+    .into_iter()
+    .flat_map(|(open, close)| sections(article, open, close))
+    .filter(|(_, html)| !html.contains('<'))
+    .max_by_key(|(at, _)| *at)
+    .map(|(_, html)| text_of(html).trim().to_owned())
+}
+
+/// Finds the examples in a saved puzzle page of `day`, one per part shown on
+/// it. The example of a part is the first block of code in its description,
+/// or part 1's if there is none, and its answer the last emphasized code,
+/// which is how nearly all puzzles are written. The few others need fixing by
+/// hand.
+pub fn extract(day: u8, html: &str) -> Result<Vec<Example>, String> {
+    let articles = sections(html, "<article", "</article>");
+    if articles.is_empty() {
+        return Err("no puzzle description found, is this a puzzle page?".to_owned());
+    }
+    let mut examples: Vec<Example> = vec![];
+    for ((_, article), part) in articles.into_iter().zip([Part::One, Part::Two]) {
+        let block = sections(article, "<pre><code>", "</code></pre>")
+            .first()
+            .map(|(_, html)| input::normalize(&text_of(html)));
+        let (name, input) = match (block, examples.first()) {
+            (Some(input), Some(first)) if input != first.input => {
+                (format!("example{day:02}_part2"), input)
+            }
+            (Some(input), _) => (format!("example{day:02}"), input),
+            (None, Some(first)) => (first.name.clone(), first.input.clone()),
+            (None, None) => return Err("part 1 has no example".to_owned()),
+        };
+        examples.push(Example {
+            part,
+            name,
+            input,
+            answer: last_answer(article),
+        });
+    }
+    Ok(examples)
+}
+
+fn check_line(day: u8, example: &Example, answer: &str) -> String {
+    let part = match example.part {
+        Part::One => 1,
+        Part::Two => 2,
+    };
+    format!("{day:02} {part} {} {answer}", example.name)
+}
+
+/// `answers` with the answer of `example` in it, replacing an unknown answer
+/// for the same example, or one for an example `new_day` guessed the name of.
+fn add_answer(answers: &str, day: u8, example: &Example, answer: &str) -> Result<String, String> {
+    let lines: Vec<&str> = answers.lines().collect();
+    let checks: Vec<Option<Check>> = lines
+        .iter()
+        .map(|l| Check::from_line(l.split('#').next().unwrap()).ok())
+        .collect();
+    let of_part =
+        |check: &Check| check.day == day && check.variant.is_none() && check.part == example.part;
+    let same = checks.iter().position(|c| {
+        c.as_ref()
+            .is_some_and(|c| of_part(c) && c.input == example.name)
+    });
+    let guessed = checks.iter().position(|c| {
+        c.as_ref().is_some_and(|c| {
+            of_part(c) && c.input.starts_with("example") && c.expected == Expected::Unknown
+        })
+    });
+    let replace = match (same, guessed) {
+        (Some(i), _) => match &checks[i].as_ref().unwrap().expected {
+            Expected::Unknown => i,
+            Expected::Exact(known) if known == answer => return Ok(answers.to_owned()),
+            known => {
+                return Err(format!(
+                    "{} has {known} for {}, the puzzle says {answer}",
+                    answers::PATH,
+                    example.name
+                ))
+            }
+        },
+        (None, Some(i)) => i,
+        (None, None) => {
+            let line = check_line(day, example, answer);
+            return Ok(insert_sorted(answers, day, &line, |l| day_of(l, "")));
+        }
+    };
+    let line = check_line(day, example, answer);
+    let mut res: Vec<&str> = lines;
+    res[replace] = &line;
+    Ok(res.join("\n") + "\n")
+}
+
+/// Saves `examples` of `day` under the crate directory `root`, the inputs in
+/// `input/` and the answers in `answers.txt`. An existing input is left alone
+/// if it is the same, and replaced only if it is empty, like the ones
+/// `new_day` creates. Nothing is touched if anything conflicts. Returns the
+/// files written.
+pub fn save(root: &Path, day: u8, examples: &[Example]) -> Result<Vec<PathBuf>, String> {
+    let answers_path = root.join(answers::PATH);
+    let read =
+        |path: &Path| fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()));
+    let mut writes: Vec<(PathBuf, String)> = vec![];
+    for example in examples {
+        let path = root.join("input").join(&example.name);
+        if writes.iter().any(|(p, _)| *p == path) {
+            continue;
+        }
+        if path.exists() {
+            let existing = read(&path)?;
+            if input::normalize(&existing) == example.input {
+                continue;
+            }
+            if !existing.trim().is_empty() {
+                return Err(format!(
+                    "{} differs from the example on the page",
+                    path.display()
+                ));
+            }
+        }
+        writes.push((path, example.input.clone()));
+    }
+    let old_answers = read(&answers_path)?;
+    let mut answers = old_answers.clone();
+    for example in examples {
+        if let Some(answer) = &example.answer {
+            answers = add_answer(&answers, day, example, answer)?;
+        }
+    }
+    if answers != old_answers {
+        writes.push((answers_path, answers));
+    }
+    for (path, contents) in &writes {
+        fs::write(path, contents).map_err(|e| format!("{}: {e}", path.display()))?;
+    }
+    Ok(writes.into_iter().map(|(path, _)| path).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{puzzle::*, scaffold};
+    use std::env;
+    const DAY01: &str = include_str!("../fixtures/day01.html");
+    const DAY19: &str = include_str!("../fixtures/day19.html");
+    #[test]
+    fn extracts_examples_and_answers() {
+        let examples = extract(1, DAY01).unwrap();
+        assert_eq!(examples.len(), 2);
+        assert_eq!(examples[0].name, "example01");
+        assert_eq!(
+            examples[0].input,
+            input::read("example01").unwrap().as_str()
+        );
+        assert_eq!(examples[0].answer.as_deref(), Some("142"));
+        assert_eq!(examples[1].name, "example01_part2");
+        assert_eq!(examples[1].input, input::read("example01_part2").unwrap());
+        assert_eq!(examples[1].answer.as_deref(), Some("281"));
+
+        let examples = extract(19, DAY19).unwrap();
+        assert_eq!(examples.len(), 1);
+        assert_eq!(examples[0].input, input::read("example19").unwrap());
+        assert_eq!(examples[0].answer.as_deref(), Some("19114"));
+
+        assert_eq!(
+            text_of("<em>S</em>-7 &lt;&#62;&#x26;&amp;c &nbsp;"),
+            "S-7 <>&&c &nbsp;"
+        );
+        assert!(extract(1, "<html></html>").is_err());
+    }
+    #[test]
+    fn saves_over_new_day() {
+        let root = env::temp_dir().join(format!("aoc-puzzle-{}", std::process::id()));
+        fs::create_dir_all(root.join("input")).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "").unwrap();
+        fs::write(root.join("answers.txt"), "02 1 example02 8\n").unwrap();
+        scaffold::new_day(&root, 1).unwrap();
+
+        let examples = extract(1, DAY01).unwrap();
+        let written = save(&root, 1, &examples).unwrap();
+        assert_eq!(written.len(), 3);
+        assert_eq!(
+            fs::read_to_string(root.join("answers.txt")).unwrap(),
+            "01 1 example01 142\n01 1 day01 unknown\n01 2 example01_part2 281\n\
+             01 2 day01 unknown\n02 1 example02 8\n"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("input/example01_part2")).unwrap(),
+            examples[1].input
+        );
+        assert!(save(&root, 1, &examples).unwrap().is_empty());
+
+        fs::write(root.join("input/example01"), "edited\n").unwrap();
+        assert!(save(&root, 1, &examples)
+            .unwrap_err()
+            .ends_with("differs from the example on the page"));
+        let mut wrong = examples.clone();
+        wrong[1].answer = Some("280".to_owned());
+        fs::write(root.join("input/example01"), &examples[0].input).unwrap();
+        assert_eq!(
+            save(&root, 1, &wrong).unwrap_err(),
+            "answers.txt has 281 for example01_part2, the puzzle says 280"
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
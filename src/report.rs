@@ -0,0 +1,94 @@
+use crate::{json::Json, log, Answer, Entry, Part};
+use std::time::{Duration, Instant};
+
+/// The outcome of running one part of a solution on an input, for scripts.
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub day: u8,
+    pub part: Part,
+    pub variant: Option<String>,
+    /// `input_hash` of the input, to tell which input was used.
+    pub input_hash: u64,
+    /// The error message if the input did not parse or the part failed.
+    pub answer: Result<Answer, String>,
+    /// Parsing and solving together.
+    pub elapsed: Duration,
+    pub warnings: Vec<String>,
+}
+
+/// 64 bit FNV-1a, stable across runs and platforms unlike the std hashers.
+pub fn input_hash(input: &str) -> u64 {
+    input.bytes().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Runs each of `parts` of `entry` on `input`, parsing it again for each so
+/// that each report stands on its own.
+pub fn run(entry: &Entry, input: &str, parts: &[Part]) -> Vec<Report> {
+    parts
+        .iter()
+        .map(|&part| {
+            let start = Instant::now();
+            let (answer, warnings) = log::capture(|| entry.run(input, part));
+            Report {
+                day: entry.day,
+                part,
+                variant: entry.variant.map(str::to_owned),
+                input_hash: input_hash(input),
+                answer: answer.map_err(|e| e.to_string()),
+                elapsed: start.elapsed(),
+                warnings,
+            }
+        })
+        .collect()
+}
+
+impl Report {
+    /// Answers are written as strings, so that big ones survive JSON readers
+    /// that only have doubles. `error` is null unless `answer` is.
+    pub fn to_json(&self) -> Json {
+        let (answer, error) = match &self.answer {
+            Ok(answer) => (Some(answer.to_string()), None),
+            Err(e) => (None, Some(e.clone())),
+        };
+        Json::object([
+            ("day", self.day.into()),
+            (
+                "part",
+                match self.part {
+                    Part::One => 1,
+                    Part::Two => 2,
+                }
+                .into(),
+            ),
+            ("variant", self.variant.clone().into()),
+            ("input_hash", format!("{:016x}", self.input_hash).into()),
+            ("answer", answer.into()),
+            ("error", error.into()),
+            ("elapsed_ns", self.elapsed.as_nanos().into()),
+            ("warnings", self.warnings.clone().into()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lookup, report::*};
+    #[test]
+    fn reports_answers_and_warnings() {
+        let entry = lookup(20, None).unwrap();
+        let input = "broadcaster -> a\n%a -> rx\n";
+        let reports = run(&entry, input, &[Part::One]);
+        let json = reports[0].to_json().to_string();
+        assert!(json.starts_with(&format!(
+            r#"{{"day":20,"part":1,"variant":null,"input_hash":"{:016x}","answer":"#,
+            input_hash(input)
+        )));
+        assert!(json.ends_with(r#""warnings":["`rx` is not a module, pulses to it are dropped"]}"#));
+        let reports = run(&entry, "nonsense\n", &[Part::One, Part::Two]);
+        assert_eq!(reports.len(), 2);
+        assert!(reports.iter().all(|r| r.answer.is_err()));
+        assert_eq!(input_hash(""), 0xcbf29ce484222325);
+    }
+}
@@ -0,0 +1,70 @@
+use std::ops::Range;
+
+/// SplitMix64, small and good enough to generate puzzle inputs. The same seed
+/// always gives the same numbers.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `range`, which must not be empty.
+    pub fn i64_in(&mut self, range: Range<i64>) -> i64 {
+        assert!(!range.is_empty(), "empty range {range:?}");
+        let len = range.end.abs_diff(range.start);
+        range.start.wrapping_add((self.next_u64() % len) as i64)
+    }
+
+    /// Uniform in `range`, which must not be empty.
+    pub fn usize_in(&mut self, range: Range<usize>) -> usize {
+        self.i64_in(range.start as i64..range.end as i64) as usize
+    }
+
+    /// True with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    /// `items` must not be empty.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.usize_in(0..items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.usize_in(0..i + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rng::*;
+    #[test]
+    fn seeded_and_in_range() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        for _ in 0..1000 {
+            let n = a.i64_in(-3..4);
+            assert_eq!(n, b.i64_in(-3..4));
+            assert!((-3..4).contains(&n));
+        }
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+        let mut items: Vec<_> = (0..10).collect();
+        a.shuffle(&mut items);
+        items.sort();
+        assert_eq!(items, (0..10).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,155 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// `src/day00.rs`, with `XX` standing in for the zero padded day.
+const TEMPLATE: &str = include_str!("day00.rs");
+
+pub(crate) fn day_of(line: &str, prefix: &str) -> Option<u8> {
+    let rest = line.trim_start().strip_prefix(prefix)?;
+    let end = rest.find(|ch: char| !ch.is_ascii_digit())?;
+    rest[..end].parse().ok()
+}
+
+/// Puts `new` in front of the first line whose day, as read by `day`, is
+/// later than `nr`, or after the last line that has a day at all.
+pub(crate) fn insert_sorted(
+    text: &str,
+    nr: u8,
+    new: &str,
+    day: impl Fn(&str) -> Option<u8>,
+) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let at = match lines.iter().position(|l| day(l).is_some_and(|d| d > nr)) {
+        Some(at) => at,
+        None => lines
+            .iter()
+            .rposition(|l| day(l).is_some())
+            .map_or(lines.len(), |i| i + 1),
+    };
+    let mut res: Vec<&str> = lines[..at].to_vec();
+    res.extend(new.lines());
+    res.extend(&lines[at..]);
+    res.join("\n") + "\n"
+}
+
+pub(crate) fn create_new(path: &Path, contents: &str) -> Result<(), String> {
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .and_then(|mut f| f.write_all(contents.as_bytes()))
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// Creates `src/dayNN.rs` from the template under the crate directory `root`,
+/// declares and registers it in `src/lib.rs`, adds its checks to `answers.txt`
+/// as unknown, and creates empty `input/dayNN` and `input/exampleNN`. Nothing
+/// is touched if any of that already exists. Returns the created files.
+pub fn new_day(root: &Path, day: u8) -> Result<Vec<PathBuf>, String> {
+    if !(1..=25).contains(&day) {
+        return Err(format!("bad day: {day}"));
+    }
+    let name = format!("day{day:02}");
+    let source = root.join(format!("src/{name}.rs"));
+    let inputs = [
+        root.join(format!("input/{name}")),
+        root.join(format!("input/example{day:02}")),
+    ];
+    let lib_path = root.join("src/lib.rs");
+    let answers_path = root.join("answers.txt");
+    let read =
+        |path: &Path| fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()));
+    let lib = read(&lib_path)?;
+    let answers = read(&answers_path)?;
+
+    if let Some(existing) = [&source, &inputs[0], &inputs[1]]
+        .into_iter()
+        .find(|p| p.exists())
+    {
+        return Err(format!("{name} already exists: {}", existing.display()));
+    }
+    if lib.lines().any(|l| day_of(l, "pub mod day") == Some(day)) {
+        return Err(format!("{name} already exists in {}", lib_path.display()));
+    }
+    let lib = insert_sorted(&lib, day, &format!("pub mod {name};"), |l| {
+        day_of(l, "pub mod day")
+    });
+    let registration = format!("        Entry::new::<{name}::Day{day:02}>({day}, None),");
+    let lib = insert_sorted(&lib, day, &registration, |l| day_of(l, "Entry::new::<day"));
+    let checks = [
+        ("1", "example"),
+        ("1", "day"),
+        ("2", "example"),
+        ("2", "day"),
+    ]
+    .map(|(part, input)| format!("{day:02} {part} {input}{day:02} unknown"))
+    .join("\n");
+    let answers = insert_sorted(&answers, day, &checks, |l| day_of(l, ""));
+
+    create_new(&source, &TEMPLATE.replace("XX", &format!("{day:02}")))?;
+    for input in &inputs {
+        create_new(input, "")?;
+    }
+    fs::write(&lib_path, lib).map_err(|e| format!("{}: {e}", lib_path.display()))?;
+    fs::write(&answers_path, answers).map_err(|e| format!("{}: {e}", answers_path.display()))?;
+    Ok([source].into_iter().chain(inputs).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scaffold::*;
+    use std::env;
+    const LIB: &str = "\
+pub mod day01;
+pub mod day22;
+pub mod error;
+
+pub fn registry() -> Vec<Entry> {
+    vec![
+        Entry::new::<day01::Day01>(1, None),
+        Entry::new::<day22::Day22>(22, None),
+    ]
+}
+";
+    #[test]
+    fn new_day_is_registered_in_order() {
+        let root = env::temp_dir().join(format!("aoc-scaffold-{}", std::process::id()));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("input")).unwrap();
+        fs::write(root.join("src/lib.rs"), LIB).unwrap();
+        fs::write(
+            root.join("answers.txt"),
+            "# day part\n01 1 day01 1\n22 1 day22 2\n",
+        )
+        .unwrap();
+
+        let created = new_day(&root, 21).unwrap();
+        assert_eq!(created.len(), 3);
+        let lib = fs::read_to_string(root.join("src/lib.rs")).unwrap();
+        assert!(lib.contains("pub mod day01;\npub mod day21;\npub mod day22;\n"));
+        assert!(lib.contains(
+            "(1, None),\n        Entry::new::<day21::Day21>(21, None),\n        Entry::new::<day22"
+        ));
+        let answers = fs::read_to_string(root.join("answers.txt")).unwrap();
+        assert!(answers.contains("01 1 day01 1\n21 1 example21 unknown\n"));
+        assert!(answers.ends_with("21 2 day21 unknown\n22 1 day22 2\n"));
+        let source = fs::read_to_string(root.join("src/day21.rs")).unwrap();
+        assert!(source.contains("impl Solution for Day21 {"));
+        assert!(!source.contains("XX"));
+        assert!(source.contains("fn example21() {"));
+
+        fs::write(root.join("src/day21.rs"), "edited").unwrap();
+        assert!(new_day(&root, 21)
+            .unwrap_err()
+            .starts_with("day21 already exists"));
+        assert_eq!(
+            fs::read_to_string(root.join("src/day21.rs")).unwrap(),
+            "edited"
+        );
+        assert_eq!(fs::read_to_string(root.join("src/lib.rs")).unwrap(), lib);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
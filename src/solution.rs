@@ -0,0 +1,101 @@
+use crate::{Error, ParseError};
+use std::{any::Any, fmt};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Signed(i128),
+    Unsigned(u128),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Signed(n) => write!(f, "{n}"),
+            Answer::Unsigned(n) => write!(f, "{n}"),
+            Answer::Text(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+macro_rules! answer_from {
+    ($variant:ident: $($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(n: $t) -> Self {
+                Answer::$variant(n as _)
+            }
+        })*
+    };
+}
+answer_from!(Signed: i32, i64, i128);
+answer_from!(Unsigned: u32, u64, u128, usize);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+/// A day's puzzle, split into a shared parse step and the two parts. Days that
+/// read the input differently for each part do so in the parts, which is why
+/// those can fail to parse too. The parts also fail rather than wrap when a
+/// value gets too big for its type.
+pub trait Solution {
+    type Input: 'static;
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+    fn part_1(input: &Self::Input) -> Result<Answer, Error>;
+    fn part_2(input: &Self::Input) -> Result<Answer, Error>;
+}
+
+/// Type-erased handle to a `Solution`, as stored in the registry.
+#[derive(Clone, Copy)]
+pub struct Entry {
+    pub day: u8,
+    pub variant: Option<&'static str>,
+    parse: fn(&str) -> Result<Box<dyn Any>, ParseError>,
+    solve: fn(&dyn Any, Part) -> Result<Answer, Error>,
+}
+
+impl Entry {
+    pub fn new<S: Solution>(day: u8, variant: Option<&'static str>) -> Self {
+        Entry {
+            day,
+            variant,
+            parse: |input| Ok(Box::new(S::parse(input)?)),
+            solve: |parsed, part| {
+                let input = parsed.downcast_ref::<S::Input>().unwrap();
+                match part {
+                    Part::One => S::part_1(input),
+                    Part::Two => S::part_2(input),
+                }
+            },
+        }
+    }
+
+    /// "day08" for the default variant, "day08:fast" for a named one.
+    pub fn name(&self) -> String {
+        match self.variant {
+            Some(variant) => format!("day{:02}:{variant}", self.day),
+            None => format!("day{:02}", self.day),
+        }
+    }
+
+    pub fn parse(&self, input: &str) -> Result<Box<dyn Any>, ParseError> {
+        (self.parse)(input)
+    }
+
+    /// `parsed` must come from `self.parse`.
+    pub fn solve(&self, parsed: &dyn Any, part: Part) -> Result<Answer, Error> {
+        (self.solve)(parsed, part)
+    }
+
+    pub fn run(&self, input: &str, part: Part) -> Result<Answer, Error> {
+        self.solve(self.parse(input)?.as_ref(), part)
+    }
+}
@@ -0,0 +1,206 @@
+use crate::{
+    checked::OrOverflow, day01, day02, day04, day06, day07, day09, day12, day15, error::Source,
+    Answer, Error, ParseError, Part,
+};
+use num::{CheckedAdd, Zero};
+use std::io::{self, BufRead, Read};
+
+/// The days that can read their input from a `BufRead`, see `run`.
+pub const DAYS: [u8; 8] = [1, 2, 4, 6, 7, 9, 12, 15];
+
+/// The longest line, in bytes and without its line ending, that
+/// `for_each_line` reads.
+pub const MAX_LINE: usize = 1 << 16;
+
+/// Calls `f` with each line of `reader` as `input::normalize` would leave it,
+/// and a `Source` of just that line whose errors point into the whole input.
+/// Only one line of at most `MAX_LINE` bytes is held at a time, longer lines
+/// are an error. Returns the number of lines.
+pub fn for_each_line(
+    day: u8,
+    mut reader: impl BufRead,
+    mut f: impl FnMut(&Source, &str) -> Result<(), Error>,
+) -> Result<usize, Error> {
+    let mut bytes = vec![];
+    let mut nr = 0;
+    // blank lines are held back until it is clear they do not end the input
+    let mut blank = 0;
+    loop {
+        bytes.clear();
+        // room for a `\r\n` after the longest line
+        let limit = MAX_LINE as u64 + 2;
+        if (&mut reader).take(limit).read_until(b'\n', &mut bytes)? == 0 {
+            return Ok(nr - blank);
+        }
+        nr += 1;
+        let too_long = || ParseError {
+            day,
+            line: nr,
+            column: 1,
+            expected: format!("a line of at most {MAX_LINE} bytes"),
+            found: "a longer line".to_owned(),
+        };
+        if bytes.len() as u64 == limit && bytes.last() != Some(&b'\n') {
+            return Err(too_long().into());
+        }
+        let mut buf = String::from_utf8(std::mem::take(&mut bytes)).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            )
+        })?;
+        buf.truncate(buf.trim_end().len());
+        if buf.len() > MAX_LINE {
+            return Err(too_long().into());
+        }
+        if buf.is_empty() {
+            blank += 1;
+            continue;
+        }
+        for blank_nr in nr - blank..nr {
+            let src = Source::at(day, "\n", blank_nr, 1);
+            f(&src, &src.text()[..0])?;
+        }
+        blank = 0;
+        // the line ending stays in the source, so that errors at the end of
+        // the line are not at the end of the input
+        buf.push('\n');
+        let src = Source::at(day, &buf, nr, 1);
+        f(&src, &buf[..buf.len() - 1])?;
+        bytes = buf.into_bytes();
+    }
+}
+
+/// Adds up `value` of each line like `checked_sum`, failing with an overflow
+/// of `what` instead of wrapping.
+pub fn sum_lines<T: CheckedAdd + Zero>(
+    day: u8,
+    reader: impl BufRead,
+    what: &str,
+    mut value: impl FnMut(&Source, &str) -> Result<T, Error>,
+) -> Result<T, Error> {
+    let mut sum = T::zero();
+    for_each_line(day, reader, |src, line| {
+        sum = sum.checked_add(&value(src, line)?).or_overflow(day, what)?;
+        Ok(())
+    })?;
+    Ok(sum)
+}
+
+/// `part` of `day` on the input in `reader`, or `None` if the day has no
+/// streaming version. The answer is the one `Entry::run` gives for the text.
+pub fn run(day: u8, part: Part, reader: impl BufRead) -> Option<Result<Answer, Error>> {
+    Some(match (day, part) {
+        (1, Part::One) => day01::try_part_1_reader(reader).map(Answer::from),
+        (1, Part::Two) => day01::try_part_2_reader(reader).map(Answer::from),
+        (2, Part::One) => day02::try_part_1_reader(reader).map(Answer::from),
+        (2, Part::Two) => day02::try_part_2_reader(reader).map(Answer::from),
+        (4, Part::One) => day04::try_part_1_reader(reader).map(Answer::from),
+        (4, Part::Two) => day04::try_part_2_reader(reader).map(Answer::from),
+        (6, Part::One) => day06::try_part_1_reader(reader).map(Answer::from),
+        (6, Part::Two) => day06::try_part_2_reader(reader).map(Answer::from),
+        (7, Part::One) => day07::try_part_1_reader(reader).map(Answer::from),
+        (7, Part::Two) => day07::try_part_2_reader(reader).map(Answer::from),
+        (9, Part::One) => day09::try_part_1_reader(reader).map(Answer::from),
+        (9, Part::Two) => day09::try_part_2_reader(reader).map(Answer::from),
+        (12, Part::One) => day12::try_part_1_reader(reader).map(Answer::from),
+        (12, Part::Two) => day12::try_part_2_reader(reader).map(Answer::from),
+        (15, Part::One) => day15::try_part_1_reader(reader).map(Answer::from),
+        (15, Part::Two) => day15::try_part_2_reader(reader).map(Answer::from),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{generate, input, lookup, rng::Rng, stream::*};
+    use std::io::{self, Read};
+
+    /// Reads a few bytes at a time, so that lines span reads.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = buf.len().min(self.0.len()).min(3);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn same_answers_and_errors_as_text() {
+        for day in DAYS {
+            let entry = lookup(day, None).unwrap();
+            for seed in 0..10 {
+                let text = generate::input(day, &mut Rng::new(seed)).unwrap();
+                let crlf = text.replace('\n', " \r\n") + "\r\n\n";
+                for part in [Part::One, Part::Two] {
+                    let reader = io::BufReader::new(Trickle(crlf.as_bytes()));
+                    assert_eq!(
+                        run(day, part, reader).unwrap(),
+                        entry.run(&input::normalize(&text), part),
+                        "day {day} part {part:?} seed {seed}"
+                    );
+                }
+            }
+        }
+        for (day, text) in [
+            (2, "Game 1: 3 blue\n\nGame 2: 1 red, 4 purple\n"),
+            (6, "Time: 7 15\nDistance: 9 x\n"),
+            (6, "Time: 7 15\n"),
+            (6, ""),
+            (6, "Time: 7 15\nDistance: 9 40\n200\n"),
+            (15, "rn=1,cm-,qp=x,cm=2\n"),
+            (15, "rn=1,\nqp=3,\n"),
+            (15, ""),
+        ] {
+            let entry = lookup(day, None).unwrap();
+            for part in [Part::One, Part::Two] {
+                assert_eq!(
+                    run(day, part, text.as_bytes()).unwrap(),
+                    entry.run(text, part),
+                    "day {day} part {part:?} on {text:?}"
+                );
+            }
+        }
+        // enough distinct hands with a bid of 1 that the winnings overflow
+        let hands: String = (0..92_682)
+            .map(|i: u32| {
+                let hand: String = (0..5)
+                    .map(|k| "23456789TJQKA".as_bytes()[(i / 13u32.pow(k) % 13) as usize] as char)
+                    .collect();
+                format!("{hand} 1\n")
+            })
+            .collect();
+        let entry = lookup(7, None).unwrap();
+        for part in [Part::One, Part::Two] {
+            assert_eq!(
+                run(7, part, hands.as_bytes()).unwrap(),
+                entry.run(&hands, part)
+            );
+            assert!(matches!(entry.run(&hands, part), Err(Error::Overflow(_))));
+        }
+        let invalid: &[u8] = b"1abc2\n\xff\n";
+        assert!(matches!(
+            run(1, Part::One, invalid).unwrap(),
+            Err(Error::Io(_))
+        ));
+    }
+
+    #[test]
+    fn long_lines() {
+        let count = |text: &str| for_each_line(1, text.as_bytes(), |_, _| Ok(()));
+        let longest = "x".repeat(MAX_LINE);
+        assert_eq!(count(&format!("{longest}\r\n{longest}")), Ok(2));
+        let err = Error::Parse(ParseError {
+            day: 1,
+            line: 2,
+            column: 1,
+            expected: format!("a line of at most {MAX_LINE} bytes"),
+            found: "a longer line".to_owned(),
+        });
+        assert_eq!(count(&format!("1\n{longest}x\n")), Err(err.clone()));
+        assert_eq!(count(&format!("1\n{longest}é")), Err(err));
+    }
+}
@@ -0,0 +1,542 @@
+use crate::{puzzle, Part};
+use std::{
+    fmt, fs,
+    io::{Read, Write},
+    net::TcpStream,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The base URL answers are posted to, as `BASE/day/N/answer`.
+pub const URL_ENV: &str = "AOC_URL";
+/// The value of the `session` cookie to log in with.
+pub const SESSION_ENV: &str = "AOC_SESSION";
+
+/// Every submitted answer, one per line:
+///
+/// ```text
+/// # day part answer response time
+/// 10 2 325 too-low 1702193521
+/// ```
+///
+/// `response` is one of `right`, `too-high`, `too-low`, `wrong`,
+/// `rate-limited` or `wrong-level`, and `time` is in seconds since the epoch.
+pub const HISTORY: &str = "submissions.txt";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Response {
+    Right,
+    TooHigh,
+    TooLow,
+    /// Wrong, without saying which way.
+    Wrong,
+    /// Nothing was checked, there is still this long to wait if the page said.
+    RateLimited(Option<Duration>),
+    /// The part was solved already, or part 1 not yet.
+    WrongLevel,
+}
+
+impl Response {
+    fn name(self) -> &'static str {
+        match self {
+            Response::Right => "right",
+            Response::TooHigh => "too-high",
+            Response::TooLow => "too-low",
+            Response::Wrong => "wrong",
+            Response::RateLimited(_) => "rate-limited",
+            Response::WrongLevel => "wrong-level",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [
+            Response::Right,
+            Response::TooHigh,
+            Response::TooLow,
+            Response::Wrong,
+            Response::RateLimited(None),
+            Response::WrongLevel,
+        ]
+        .into_iter()
+        .find(|r| r.name() == name)
+    }
+}
+
+impl fmt::Display for Response {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Response::Right => write!(f, "that's the right answer"),
+            Response::TooHigh => write!(f, "too high"),
+            Response::TooLow => write!(f, "too low"),
+            Response::Wrong => write!(f, "not the right answer"),
+            Response::RateLimited(Some(left)) => {
+                write!(f, "answered too recently, {}s left to wait", left.as_secs())
+            }
+            Response::RateLimited(None) => write!(f, "answered too recently"),
+            Response::WrongLevel => write!(f, "not the right level, solved already?"),
+        }
+    }
+}
+
+/// `1m 5s` in "You have 1m 5s left to wait".
+fn wait_left(text: &str) -> Option<Duration> {
+    let (before, _) = text.split_once(" left to wait")?;
+    let (_, wait) = before.rsplit_once("You have ")?;
+    wait.split_whitespace()
+        .map(|n| {
+            let (n, unit) = if let Some(n) = n.strip_suffix('h') {
+                (n, 3600)
+            } else if let Some(n) = n.strip_suffix('m') {
+                (n, 60)
+            } else {
+                (n.strip_suffix('s')?, 1)
+            };
+            n.parse::<u64>().ok()?.checked_mul(unit)
+        })
+        .sum::<Option<u64>>()
+        .map(Duration::from_secs)
+}
+
+/// Reads the page that answers a submission. Its message is the error if it
+/// is not one of the known ones.
+pub fn parse_response(html: &str) -> Result<Response, String> {
+    let text = match puzzle::sections(html, "<article", "</article>").first() {
+        Some((_, article)) => puzzle::text_of(article),
+        None => puzzle::text_of(html),
+    };
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.contains("That's the right answer") {
+        Ok(Response::Right)
+    } else if text.contains("That's not the right answer") {
+        Ok(if text.contains("too high") {
+            Response::TooHigh
+        } else if text.contains("too low") {
+            Response::TooLow
+        } else {
+            Response::Wrong
+        })
+    } else if text.contains("You gave an answer too recently") {
+        Ok(Response::RateLimited(wait_left(&text)))
+    } else if text.contains("You don't seem to be solving the right level") {
+        Ok(Response::WrongLevel)
+    } else {
+        Err(format!("unexpected response: {text}"))
+    }
+}
+
+fn url_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+/// The body of a chunked response.
+fn dechunk(mut body: &[u8]) -> Result<Vec<u8>, String> {
+    let mut res = vec![];
+    loop {
+        let end = body
+            .windows(2)
+            .position(|w| w == b"\r\n")
+            .ok_or("bad chunk")?;
+        let size = String::from_utf8_lossy(&body[..end]);
+        let size = size.split(';').next().unwrap().trim();
+        let size = usize::from_str_radix(size, 16).map_err(|_| "bad chunk size")?;
+        body = &body[end + 2..];
+        if size == 0 {
+            return Ok(res);
+        }
+        res.extend(body.get(..size).ok_or("truncated chunk")?);
+        body = body.get(size + 2..).ok_or("truncated chunk")?;
+    }
+}
+
+/// Posts answers to a server with the Advent of Code interface. Speaks plain
+/// HTTP/1.1 only, so real submissions need an `http://` base URL that
+/// forwards to the site, e.g. a local TLS proxy.
+#[derive(Debug, Clone)]
+pub struct Client {
+    /// `host:port`
+    address: String,
+    host: String,
+    /// Path of the base URL, without a trailing `/`.
+    prefix: String,
+    session: Option<String>,
+    pub timeout: Duration,
+}
+
+impl Client {
+    pub fn new(base_url: &str, session: Option<String>) -> Result<Self, String> {
+        let rest = match base_url.split_once("://") {
+            Some(("http", rest)) => rest,
+            Some((scheme, _)) => return Err(format!("{scheme} URLs are not supported")),
+            None => return Err(format!("bad URL: {base_url}")),
+        };
+        let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+        if authority.is_empty() {
+            return Err(format!("bad URL: {base_url}"));
+        }
+        let host = match authority.rsplit_once(':') {
+            Some((host, port)) if port.parse::<u16>().is_ok() => host,
+            _ => authority,
+        };
+        let address = match host == authority {
+            true => format!("{authority}:80"),
+            false => authority.to_owned(),
+        };
+        Ok(Client {
+            address,
+            host: authority.to_owned(),
+            prefix: path.trim_end_matches('/').to_owned(),
+            session,
+            timeout: Duration::from_secs(30),
+        })
+    }
+
+    /// `Client::new` with `URL_ENV` and `SESSION_ENV`.
+    pub fn from_env() -> Result<Self, String> {
+        let url = std::env::var(URL_ENV).map_err(|_| format!("{URL_ENV} is not set"))?;
+        Client::new(&url, std::env::var(SESSION_ENV).ok())
+    }
+
+    fn post(&self, path: &str, form: &str) -> Result<(u16, String), String> {
+        let err = |e: std::io::Error| format!("{}: {e}", self.address);
+        let mut stream = TcpStream::connect(&self.address).map_err(err)?;
+        stream.set_read_timeout(Some(self.timeout)).map_err(err)?;
+        let cookie = match &self.session {
+            Some(session) => format!("Cookie: session={session}\r\n"),
+            None => String::new(),
+        };
+        let request = format!(
+            "POST {}{path} HTTP/1.1\r\nHost: {}\r\nUser-Agent: {}\r\n{cookie}\
+             Content-Type: application/x-www-form-urlencoded\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{form}",
+            self.prefix,
+            self.host,
+            env!("CARGO_PKG_NAME"),
+            form.len()
+        );
+        stream.write_all(request.as_bytes()).map_err(err)?;
+        let mut response = vec![];
+        stream.read_to_end(&mut response).map_err(err)?;
+
+        let split = response
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .ok_or("no end of headers in response")?;
+        let head = String::from_utf8_lossy(&response[..split]);
+        let body = &response[split + 4..];
+        let status = head
+            .split_whitespace()
+            .nth(1)
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| format!("bad status line: {}", head.lines().next().unwrap()))?;
+        let chunked = head.lines().any(|l| {
+            l.split_once(':').is_some_and(|(name, value)| {
+                name.eq_ignore_ascii_case("transfer-encoding") && value.trim() == "chunked"
+            })
+        });
+        let body = match chunked {
+            true => dechunk(body)?,
+            false => body.to_vec(),
+        };
+        Ok((status, String::from_utf8_lossy(&body).into_owned()))
+    }
+
+    pub fn submit(&self, day: u8, part: Part, answer: &str) -> Result<Response, String> {
+        let level = match part {
+            Part::One => 1,
+            Part::Two => 2,
+        };
+        let form = format!("level={level}&answer={}", url_encode(answer));
+        let (status, body) = self.post(&format!("/day/{day}/answer"), &form)?;
+        match status {
+            200 => parse_response(&body),
+            _ => Err(format!("HTTP status {status}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attempt {
+    pub day: u8,
+    pub part: Part,
+    pub answer: String,
+    pub response: Response,
+    /// Seconds since the epoch.
+    pub time: u64,
+}
+
+impl Attempt {
+    fn from_line(line: &str) -> Result<Self, String> {
+        let [day, part, answer, response, time] = line.split_whitespace().collect::<Vec<_>>()[..]
+        else {
+            return Err("expected `day part answer response time`".to_owned());
+        };
+        Ok(Attempt {
+            day: day.parse().map_err(|_| format!("bad day `{day}`"))?,
+            part: match part {
+                "1" => Part::One,
+                "2" => Part::Two,
+                _ => return Err(format!("bad part `{part}`")),
+            },
+            answer: answer.to_owned(),
+            response: Response::from_name(response)
+                .ok_or_else(|| format!("bad response `{response}`"))?,
+            time: time.parse().map_err(|_| format!("bad time `{time}`"))?,
+        })
+    }
+}
+
+impl fmt::Display for Attempt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let part = match self.part {
+            Part::One => 1,
+            Part::Two => 2,
+        };
+        write!(
+            f,
+            "{:02} {part} {} {} {}",
+            self.day,
+            self.answer,
+            self.response.name(),
+            self.time
+        )
+    }
+}
+
+/// The attempts in a history file, which is added to as answers are submitted.
+#[derive(Debug)]
+pub struct History {
+    path: PathBuf,
+    pub attempts: Vec<Attempt>,
+}
+
+impl History {
+    /// A missing file is an empty history.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("{}: {e}", path.display())),
+        };
+        let attempts = text
+            .lines()
+            .enumerate()
+            .map(|(i, l)| (i + 1, l.split('#').next().unwrap().trim()))
+            .filter(|(_, l)| !l.is_empty())
+            .map(|(nr, l)| {
+                Attempt::from_line(l).map_err(|e| format!("{}:{nr}: {e}", path.display()))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(History {
+            path: path.to_owned(),
+            attempts,
+        })
+    }
+
+    /// Why `answer` should not be submitted, if the history says it is wrong
+    /// or out of bounds, or the part is solved already.
+    pub fn refusal(&self, day: u8, part: Part, answer: &str) -> Option<String> {
+        let mut attempts = self
+            .attempts
+            .iter()
+            .filter(|a| a.day == day && a.part == part);
+        let n: Option<i128> = answer.parse().ok();
+        attempts.find_map(|a| {
+            let other = a.answer.parse::<i128>().ok();
+            match (a.response, n, other) {
+                (Response::Right, _, _) => Some(format!("already solved with {}", a.answer)),
+                (Response::TooLow | Response::TooHigh | Response::Wrong, _, _)
+                    if a.answer == answer =>
+                {
+                    Some(format!("{answer} was {} before", a.response))
+                }
+                (Response::TooLow, Some(n), Some(low)) if n < low => {
+                    Some(format!("{low} was too low already"))
+                }
+                (Response::TooHigh, Some(n), Some(high)) if n > high => {
+                    Some(format!("{high} was too high already"))
+                }
+                _ => None,
+            }
+        })
+    }
+
+    /// Adds `attempt` to the history and its file.
+    pub fn record(&mut self, attempt: Attempt) -> Result<(), String> {
+        let err = |e: std::io::Error| format!("{}: {e}", self.path.display());
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(err)?;
+        writeln!(file, "{attempt}").map_err(err)?;
+        self.attempts.push(attempt);
+        Ok(())
+    }
+}
+
+/// Submits `answer` unless `history` refuses it, and records the attempt.
+pub fn submit(
+    client: &Client,
+    history: &mut History,
+    day: u8,
+    part: Part,
+    answer: &str,
+) -> Result<Response, String> {
+    if answer.is_empty() || answer.contains(char::is_whitespace) {
+        return Err(format!("bad answer `{answer}`"));
+    }
+    if let Some(why) = history.refusal(day, part, answer) {
+        return Err(format!("not submitting {answer}: {why}"));
+    }
+    let response = client.submit(day, part, answer)?;
+    history.record(Attempt {
+        day,
+        part,
+        answer: answer.to_owned(),
+        response,
+        time: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |t| t.as_secs()),
+    })?;
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::submit::*;
+    use std::{env, io::BufRead, io::BufReader, net::TcpListener, thread};
+
+    fn page(message: &str) -> String {
+        format!("<html><body><main><article><p>{message}</p></article></main></body></html>")
+    }
+
+    /// Serves `count` requests like the puzzle site would, with 54573 right,
+    /// and returns the requests it got.
+    fn stand_in(count: usize) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/2023", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let mut requests = vec![];
+            for stream in listener.incoming().take(count) {
+                let mut reader = BufReader::new(stream.unwrap());
+                let mut request = String::new();
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some(n) = line.strip_prefix("Content-Length: ") {
+                        length = n.trim().parse().unwrap();
+                    }
+                    request += &line;
+                    if line == "\r\n" {
+                        break;
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                let body = String::from_utf8(body).unwrap();
+                let message = match body.rsplit_once("answer=").unwrap().1 {
+                    "54573" => "That's the right answer! You are one gold star closer.",
+                    "1" => "That's not the right answer; your answer is too low.",
+                    "99999" => "That's not the right answer; your answer is too high.",
+                    "a%2Bb" => "That's not the right answer.",
+                    _ => {
+                        "You gave an answer too recently; you have to wait after \
+                          submitting an answer before trying again. You have 1m 5s left to wait."
+                    }
+                };
+                let html = page(message);
+                // chunked, like the real site
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{html}\r\n0\r\n\r\n",
+                    html.len()
+                );
+                let mut stream = reader.into_inner();
+                stream.write_all(response.as_bytes()).unwrap();
+                requests.push(request + &body);
+            }
+            requests
+        });
+        (url, server)
+    }
+
+    #[test]
+    fn submits_to_stand_in_and_records() {
+        let (url, server) = stand_in(5);
+        let client = Client::new(&url, Some("abc".to_owned())).unwrap();
+        let path = env::temp_dir().join(format!("aoc-submissions-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut history = History::load(&path).unwrap();
+
+        let mut submit = |part, answer| submit(&client, &mut history, 1, part, answer);
+        assert_eq!(submit(Part::One, "1"), Ok(Response::TooLow));
+        assert_eq!(submit(Part::One, "99999"), Ok(Response::TooHigh));
+        assert_eq!(
+            submit(Part::One, "1").unwrap_err(),
+            "not submitting 1: 1 was too low before"
+        );
+        assert_eq!(
+            submit(Part::One, "100000").unwrap_err(),
+            "not submitting 100000: 99999 was too high already"
+        );
+        assert_eq!(
+            submit(Part::One, "54574"),
+            Ok(Response::RateLimited(Some(Duration::from_secs(65))))
+        );
+        assert_eq!(submit(Part::One, "54573"), Ok(Response::Right));
+        assert!(submit(Part::One, "54573")
+            .unwrap_err()
+            .ends_with("already solved with 54573"));
+        assert_eq!(submit(Part::Two, "a+b"), Ok(Response::Wrong));
+        assert!(submit(Part::Two, "a b").is_err());
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("POST /2023/day/1/answer HTTP/1.1\r\n"));
+        assert!(requests[0].contains("Cookie: session=abc\r\n"));
+        assert!(requests[0].ends_with("\r\n\r\nlevel=1&answer=1"));
+        assert!(requests[4].ends_with("level=2&answer=a%2Bb"));
+
+        let history = History::load(&path).unwrap();
+        let responses: Vec<_> = history.attempts.iter().map(|a| a.response).collect();
+        assert_eq!(
+            responses,
+            [
+                Response::TooLow,
+                Response::TooHigh,
+                Response::RateLimited(None),
+                Response::Right,
+                Response::Wrong
+            ]
+        );
+        assert_eq!(history.attempts[4].answer, "a+b");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn parses_responses() {
+        let level =
+            page("You don't seem to be solving the right level.  Did you already complete it?");
+        assert_eq!(parse_response(&level), Ok(Response::WrongLevel));
+        assert_eq!(
+            parse_response(&page(
+                "You gave an answer too recently; You have 34s left to wait."
+            )),
+            Ok(Response::RateLimited(Some(Duration::from_secs(34))))
+        );
+        assert_eq!(
+            parse_response(&page(
+                "You gave an answer too recently; You have 3é left to wait."
+            )),
+            Ok(Response::RateLimited(None))
+        );
+        assert!(parse_response("<html>Puzzle inputs differ by user.</html>").is_err());
+        assert!(Client::new("https://adventofcode.com/2023", None).is_err());
+    }
+}